@@ -0,0 +1,17 @@
+#version 450
+
+// Every invocation writes a single pixel, the image size doesn't have to be a multiple of the work group size
+layout(local_size_x = 8, local_size_y = 8) in;
+
+layout(set = 0, binding = 0, rgba8) uniform writeonly image2D targetImage;
+
+void main() {
+    ivec2 size = imageSize(targetImage);
+    ivec2 pixel = ivec2(gl_GlobalInvocationID.xy);
+    if (pixel.x >= size.x || pixel.y >= size.y) {
+        return;
+    }
+
+    vec2 gradient = vec2(pixel) / vec2(max(size - 1, 1));
+    imageStore(targetImage, pixel, vec4(gradient, 0.5, 1.0));
+}
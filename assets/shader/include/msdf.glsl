@@ -0,0 +1,14 @@
+// Helper functions for rendering multi-channel signed distance fields
+
+// The distance range of the atlas in pixels, which is specialized by the font renderer
+layout(constant_id = 0) const float PX_RANGE = 8.0;
+
+float screenPxRange(sampler2D msdfSampler, vec2 texCoord) {
+    vec2 unitRange = vec2(PX_RANGE) / vec2(textureSize(msdfSampler, 0));
+    vec2 screenTexSize = vec2(1.0) / fwidth(texCoord);
+    return max(0.5 * dot(unitRange, screenTexSize), 1.0);
+}
+
+float median(float r, float g, float b) {
+    return max(min(r, g), min(max(r, g), b));
+}
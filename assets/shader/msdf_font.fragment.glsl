@@ -1,7 +1,10 @@
 #version 450
 #extension GL_ARB_separate_shader_objects : enable
+#extension GL_GOOGLE_include_directive : enable
 
-// TODO: Make shader with back color compatible + make pxRange dynamic
+#include <include/msdf.glsl>
+
+// TODO: Make shader with back color compatible
 layout(set = 0, binding = 0) uniform sampler2D msdfSampler;
 
 layout(location = 0) in vec4 inColor;
@@ -9,21 +12,16 @@ layout(location = 1) in vec2 texCoord;
 
 layout(location = 0) out vec4 outColor;
 
-float screenPxRange() {
-    // 8 = pxRange
-    vec2 unitRange = vec2(8) / vec2(textureSize(msdfSampler, 0));
-    vec2 screenTexSize = vec2(1.0) / fwidth(texCoord);
-    return max(0.5 * dot(unitRange, screenTexSize), 1.0);
-}
-
-float median(float r, float g, float b) {
-    return max(min(r, g), min(max(r, g), b));
-}
-
 void main() {
     // Shader
     vec3 msd = texture(msdfSampler, texCoord).rgb;
-    float screenPxDistance = screenPxRange() * (median(msd.r, msd.g, msd.b) - 0.5);
+    float screenPxDistance = screenPxRange(msdfSampler, texCoord) * (median(msd.r, msd.g, msd.b) - 0.5);
     float opacity = clamp(screenPxDistance + 0.5, 0.0, 1.0);
+#ifdef OUTLINE
+    // The black outline extends the glyph by one screen pixel
+    float outlineOpacity = clamp(screenPxDistance + 1.5, 0.0, 1.0);
+    outColor = vec4(inColor.rgb * opacity, inColor.a * outlineOpacity);
+#else
     outColor = inColor * vec4(1.0, 1.0, 1.0, opacity);
-}
\ No newline at end of file
+#endif
+}
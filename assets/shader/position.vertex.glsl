@@ -0,0 +1,35 @@
+#version 450
+#extension GL_ARB_separate_shader_objects : enable
+
+// The vertex attributes are selected by the defines of the pipeline. WITH_COLOR adds the vertex color and WITH_TEXCOORD
+// adds the texture coordinate, the locations follow the order of the attributes in the vertex format.
+out gl_PerVertex {
+    vec4 gl_Position;
+};
+
+layout(location = 0) in vec2 position;
+
+#ifdef WITH_COLOR
+layout(location = 1) in vec4 color;
+layout(location = 0) out vec4 outColor;
+#define TEXCOORD_LOCATION 2
+#define OUT_TEXCOORD_LOCATION 1
+#else
+#define TEXCOORD_LOCATION 1
+#define OUT_TEXCOORD_LOCATION 0
+#endif
+
+#ifdef WITH_TEXCOORD
+layout(location = TEXCOORD_LOCATION) in vec2 inTexCoord;
+layout(location = OUT_TEXCOORD_LOCATION) out vec2 outTexCoord;
+#endif
+
+void main() {
+    gl_Position = vec4(position, 0.0, 1.0);
+#ifdef WITH_COLOR
+    outColor = color;
+#endif
+#ifdef WITH_TEXCOORD
+    outTexCoord = inTexCoord;
+#endif
+}
@@ -1,16 +0,0 @@
-#version 450
-#extension GL_ARB_separate_shader_objects : enable
-
-out gl_PerVertex {
-    vec4 gl_Position;
-};
-
-layout(location = 0) in vec2 position;
-layout(location = 1) in vec2 inTexCoord;
-
-layout(location = 0) out vec2 outTexCoord;
-
-void main() {
-    gl_Position = vec4(position, 0.0, 1.0);
-    outTexCoord = inTexCoord;
-}
@@ -0,0 +1,13 @@
+#version 450
+#extension GL_ARB_separate_shader_objects : enable
+
+layout(set = 0, binding = 0) uniform sampler2D atlasSampler;
+
+layout(location = 0) in vec4 inColor;
+layout(location = 1) in vec2 texCoord;
+
+layout(location = 0) out vec4 outColor;
+
+void main() {
+    outColor = texture(atlasSampler, texCoord) * inColor;
+}
@@ -0,0 +1,28 @@
+#version 450
+#extension GL_ARB_separate_shader_objects : enable
+
+// Every sprite is an instance of the unit quad. The corner of the quad is the only per-vertex attribute, all other
+// attributes are read per instance (see SpriteInstance).
+out gl_PerVertex {
+    vec4 gl_Position;
+};
+
+layout(push_constant) uniform Screen {
+    vec2 size;
+} screen;
+
+layout(location = 0) in vec2 corner;
+layout(location = 1) in vec2 position;
+layout(location = 2) in vec2 scale;
+layout(location = 3) in vec4 color;
+layout(location = 4) in vec4 atlasRect;
+
+layout(location = 0) out vec4 outColor;
+layout(location = 1) out vec2 outTexCoord;
+
+void main() {
+    vec2 pixel = position + corner * scale;
+    gl_Position = vec4(pixel * 2.0 / screen.size - 1.0, 0.0, 1.0);
+    outColor = color;
+    outTexCoord = mix(atlasRect.xy, atlasRect.zw, corner);
+}
@@ -0,0 +1,41 @@
+use std::env;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// The validator checks the asset tree in the specified directory (or the current working directory), because the
+/// pipelines reference their shaders relative to the root of the asset tree.
+fn main() -> ExitCode {
+    simple_logger::init_with_level(log::Level::Warn).unwrap();
+
+    let root_directory = match env::args().nth(1).as_deref() {
+        Some("-h" | "--help") => {
+            println!("Usage: vesuvius-assets [directory containing the 'assets' directory]");
+            return ExitCode::SUCCESS;
+        }
+        Some(directory) => PathBuf::from(directory),
+        None => PathBuf::from("."),
+    };
+    if let Err(error) = env::set_current_dir(&root_directory) {
+        eprintln!(
+            "Unable to open asset tree {:?} => {}",
+            root_directory, error
+        );
+        return ExitCode::FAILURE;
+    }
+
+    let diagnostics = vesuvius_engine::assets::validate_assets();
+    for diagnostic in diagnostics.iter() {
+        eprintln!("{}", diagnostic);
+    }
+
+    match diagnostics.len() {
+        0 => {
+            println!("All assets are valid");
+            ExitCode::SUCCESS
+        }
+        problems => {
+            eprintln!("Found {} problems in the assets", problems);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// This function creates an empty asset tree with the specified name in the temporary directory
+fn asset_tree(name: &str) -> PathBuf {
+    let root_directory =
+        std::env::temp_dir().join(format!("vesuvius-assets-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root_directory);
+    fs::create_dir_all(root_directory.join("assets/pipelines")).unwrap();
+    fs::create_dir_all(root_directory.join("assets/shader")).unwrap();
+    fs::create_dir_all(root_directory.join("assets/resources/fonts")).unwrap();
+    root_directory
+}
+
+/// This function writes the GLSL sources of the specified shader kinds into the asset tree. The shaders are named
+/// like `<name>.<kind>.glsl` and the version directive is prepended to every source.
+fn write_shaders(root_directory: &Path, name: &str, shaders: &[(&str, &str)]) {
+    for (kind, source) in shaders {
+        fs::write(
+            root_directory.join(format!("assets/shader/{}.{}.glsl", name, kind)),
+            format!("#version 450\n{}\n", source),
+        )
+        .unwrap();
+    }
+}
+
+fn validate(root_directory: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_vesuvius-assets"))
+        .arg(root_directory)
+        .output()
+        .expect("Unable to run vesuvius-assets")
+}
+
+#[test]
+fn workspace_assets_are_valid() {
+    let root_directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+    let output = validate(&root_directory);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn invalid_assets_are_reported() {
+    let root_directory = asset_tree("invalid");
+    let pipelines_directory = root_directory.join("assets/pipelines");
+    fs::write(pipelines_directory.join("broken.json"), "{ \"name\": ").unwrap();
+    fs::write(
+        pipelines_directory.join("missing_shader.json"),
+        r#"{
+  "name": "missing_shader",
+  "shader": [
+    { "kind": "vertex", "resource": "assets/shader/missing.vertex.glsl" },
+    { "kind": "fragment", "resource": "assets/shader/missing.fragment.glsl" }
+  ]
+}"#,
+    )
+    .unwrap();
+
+    // Files without the extension of pipeline configurations are ignored
+    fs::write(pipelines_directory.join("notes.txt"), "{ \"name\": ").unwrap();
+
+    let font_directory = root_directory.join("assets/resources/fonts/empty");
+    fs::create_dir_all(&font_directory).unwrap();
+    fs::write(font_directory.join("atlas.json"), "{}").unwrap();
+
+    let output = validate(&root_directory);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success(), "{}", stderr);
+    assert!(stderr.contains("[broken]"), "{}", stderr);
+    assert!(stderr.contains("missing.vertex.glsl"), "{}", stderr);
+    assert!(stderr.contains("missing.fragment.glsl"), "{}", stderr);
+    assert!(stderr.contains("[empty]"), "{}", stderr);
+    assert!(!stderr.contains("notes.txt"), "{}", stderr);
+    assert!(stderr.contains("problems in the assets"), "{}", stderr);
+
+    fs::remove_dir_all(&root_directory).unwrap();
+}
+
+#[test]
+fn vertex_format_mismatch() {
+    let root_directory = asset_tree("vertex_format");
+    let shaders = [
+        (
+            "vertex",
+            "layout(location = 0) in vec2 position;\n\
+             layout(location = 1) in vec3 color;\n\
+             layout(location = 0) out vec4 outColor;\n\
+             void main() { gl_Position = vec4(position, 0.0, 1.0); outColor = vec4(color, 1.0); }",
+        ),
+        (
+            "fragment",
+            "layout(location = 0) in vec4 inColor;\n\
+             layout(location = 0) out vec4 outColor;\n\
+             void main() { outColor = inColor; }",
+        ),
+    ];
+    write_shaders(&root_directory, "colored", &shaders);
+    for (name, vertex_format) in [
+        ("colored", "position_color"),
+        ("textured", "position_tex_coord"),
+    ] {
+        fs::write(
+            root_directory.join(format!("assets/pipelines/{}.json", name)),
+            format!(
+                r#"{{
+  "name": "{}",
+  "shader": [
+    {{ "kind": "vertex", "resource": "assets/shader/colored.vertex.glsl" }},
+    {{ "kind": "fragment", "resource": "assets/shader/colored.fragment.glsl" }}
+  ],
+  "vertex_format": "{}"
+}}"#,
+                name, vertex_format
+            ),
+        )
+        .unwrap();
+    }
+
+    let output = validate(&root_directory);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success(), "{}", stderr);
+    assert!(
+        stderr.contains("[colored]")
+            && stderr.contains(
+                "The vertex input at location 1 is R32G32B32_SFLOAT, but the attribute 'color' is \
+                 R32G32B32A32_SFLOAT"
+            ),
+        "{}",
+        stderr
+    );
+    assert!(
+        stderr.contains("[textured]")
+            && stderr.contains(
+                "The vertex input at location 1 is R32G32B32_SFLOAT, but the attribute 'uv' is \
+                 R32G32_SFLOAT"
+            ),
+        "{}",
+        stderr
+    );
+
+    fs::remove_dir_all(&root_directory).unwrap();
+}
+
+/// This function writes a pipeline with a vertex, a fragment and the specified tessellation shaders into the asset tree
+fn write_tessellation_pipeline(
+    root_directory: &Path,
+    name: &str,
+    tessellation_shaders: &[&str],
+    patch_control_points: Option<u32>,
+) {
+    let shaders = ["vertex", "fragment"]
+        .iter()
+        .chain(tessellation_shaders.iter())
+        .map(|kind| {
+            format!(
+                r#"{{ "kind": "{}", "resource": "assets/shader/tessellation.{}.glsl" }}"#,
+                kind, kind
+            )
+        })
+        .collect::<Vec<_>>();
+    let patch_control_points = patch_control_points
+        .map(|patch_control_points| {
+            format!(r#", "patch_control_points": {}"#, patch_control_points)
+        })
+        .unwrap_or_default();
+    fs::write(
+        root_directory.join(format!("assets/pipelines/{}.json", name)),
+        format!(
+            r#"{{ "name": "{}", "topology": "patch_list"{}, "shader": [{}] }}"#,
+            name,
+            patch_control_points,
+            shaders.join(", ")
+        ),
+    )
+    .unwrap();
+}
+
+#[test]
+fn tessellation_patch_size() {
+    let root_directory = asset_tree("tessellation");
+    let shaders = [
+        (
+            "vertex",
+            "layout(location = 0) in vec2 position;\n\
+             void main() { gl_Position = vec4(position, 0.0, 1.0); }",
+        ),
+        (
+            "tessellation_control",
+            "layout(vertices = 4) out;\n\
+             void main() {\n\
+                 gl_out[gl_InvocationID].gl_Position = gl_in[gl_InvocationID].gl_Position;\n\
+                 gl_TessLevelOuter[0] = 1.0;\n\
+                 gl_TessLevelOuter[1] = 1.0;\n\
+                 gl_TessLevelOuter[2] = 1.0;\n\
+                 gl_TessLevelOuter[3] = 1.0;\n\
+                 gl_TessLevelInner[0] = 1.0;\n\
+                 gl_TessLevelInner[1] = 1.0;\n\
+             }",
+        ),
+        (
+            "tessellation_evaluation",
+            "layout(quads, equal_spacing, cw) in;\n\
+             void main() {\n\
+                 vec4 top = mix(gl_in[0].gl_Position, gl_in[1].gl_Position, gl_TessCoord.x);\n\
+                 vec4 bottom = mix(gl_in[3].gl_Position, gl_in[2].gl_Position, gl_TessCoord.x);\n\
+                 gl_Position = mix(top, bottom, gl_TessCoord.y);\n\
+             }",
+        ),
+        (
+            "fragment",
+            "layout(location = 0) out vec4 outColor;\n\
+             void main() { outColor = vec4(1.0); }",
+        ),
+    ];
+    write_shaders(&root_directory, "tessellation", &shaders);
+
+    let both_shaders = ["tessellation_control", "tessellation_evaluation"];
+    write_tessellation_pipeline(&root_directory, "patch_valid", &both_shaders, Some(4));
+    write_tessellation_pipeline(&root_directory, "patch_limit", &both_shaders, Some(32));
+    write_tessellation_pipeline(&root_directory, "patch_too_large", &both_shaders, Some(33));
+    write_tessellation_pipeline(&root_directory, "patch_empty", &both_shaders, Some(0));
+    write_tessellation_pipeline(&root_directory, "patch_missing", &both_shaders, None);
+    write_tessellation_pipeline(
+        &root_directory,
+        "control_only",
+        &["tessellation_control"],
+        Some(4),
+    );
+
+    let output = validate(&root_directory);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success(), "{}", stderr);
+    assert!(!stderr.contains("[patch_valid]"), "{}", stderr);
+    assert!(!stderr.contains("[patch_limit]"), "{}", stderr);
+    assert!(
+        stderr.contains("33 control points per patch, but only 1 to 32 are supported"),
+        "{}",
+        stderr
+    );
+    assert!(
+        stderr.contains("0 control points per patch, but only 1 to 32 are supported"),
+        "{}",
+        stderr
+    );
+    assert!(
+        stderr.contains("[patch_missing]") && stderr.contains("'patch_control_points'"),
+        "{}",
+        stderr
+    );
+    assert!(
+        stderr.contains("[control_only]")
+            && stderr
+                .contains("requires both a tessellation control and a tessellation evaluation"),
+        "{}",
+        stderr
+    );
+
+    fs::remove_dir_all(&root_directory).unwrap();
+}
@@ -0,0 +1,152 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, token, Data, DeriveInput, Error, Field, Fields};
+
+/// This enum represents the meaning of a vertex attribute, which is marked with `#[vertex(...)]`
+enum Semantic {
+    /// The attribute is the position in pixels, which is converted into normalized device coordinates
+    Position,
+
+    /// The attribute is a sRGB encoded color, which is converted into linear space for sRGB render targets
+    Color,
+}
+
+/// This derive macro implements `VertexLayout` for a `#[repr(C)]` struct with named fields. Every field is a vertex
+/// attribute, the locations follow the order of the fields and the offsets are the offsets of the fields in the
+/// struct. The field marked with `#[vertex(position)]` is converted from pixels into normalized device coordinates and
+/// the field marked with `#[vertex(color)]` is converted into linear space for sRGB render targets, before the vertices
+/// are written into the vertex buffer.
+#[proc_macro_derive(VertexLayout, attributes(vertex))]
+pub fn derive_vertex_layout(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_vertex_layout(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_vertex_layout(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    if !is_repr_c(input)? {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "VertexLayout requires #[repr(C)], so the fields are laid out in the order of their declaration",
+        ));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "VertexLayout requires a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "VertexLayout can only be derived for structs",
+            ))
+        }
+    };
+
+    // The offsets are taken from the struct, because fields like `Vec4` are aligned and may be preceded by padding
+    let layout = quote!(::vesuvius_engine::render::buffer::layout);
+    let (mut attributes, mut conversions) = (Vec::new(), Vec::new());
+    for field in fields.iter() {
+        let (ident, ty) = (field.ident.as_ref().unwrap(), &field.ty);
+        let name = ident.to_string();
+        attributes.push(quote! {
+            #layout::VertexAttribute {
+                name: #name,
+                format: <#ty as #layout::AttributeFormat>::FORMAT,
+                offset: {
+                    // The field is never read, so the address can be computed on uninitialized memory
+                    let vertex = ::core::mem::MaybeUninit::<Self>::uninit();
+                    let base = vertex.as_ptr();
+                    unsafe {
+                        (::core::ptr::addr_of!((*base).#ident) as *const u8)
+                            .offset_from(base as *const u8) as u32
+                    }
+                },
+            }
+        });
+
+        match semantic(field)? {
+            Some(Semantic::Position) => {
+                conversions.push(quote!(self.#ident = target.position(self.#ident);))
+            }
+            Some(Semantic::Color) => {
+                conversions.push(quote!(self.#ident = target.color(self.#ident);))
+            }
+            None => {}
+        }
+    }
+
+    // Layouts without position and color keep the default implementation, which doesn't convert anything
+    let prepare = match conversions.is_empty() {
+        true => quote!(),
+        false => quote! {
+            fn prepare(&mut self, target: &#layout::VertexTarget) {
+                #(#conversions)*
+            }
+        },
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #layout::VertexLayout for #ident #type_generics #where_clause {
+            const ATTRIBUTES: &'static [#layout::VertexAttribute] = &[#(#attributes),*];
+
+            #prepare
+        }
+    })
+}
+
+/// This function returns true, if the struct is annotated with `#[repr(C)]`
+fn is_repr_c(input: &DeriveInput) -> syn::Result<bool> {
+    let mut repr_c = false;
+    for attribute in input
+        .attrs
+        .iter()
+        .filter(|attribute| attribute.path().is_ident("repr"))
+    {
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") {
+                repr_c = true;
+            }
+
+            // Representations like align(16) have arguments, which are skipped
+            if meta.input.peek(token::Paren) {
+                let _arguments;
+                syn::parenthesized!(_arguments in meta.input);
+            }
+            Ok(())
+        })?;
+    }
+    Ok(repr_c)
+}
+
+/// This function returns the semantic of the field, which is declared by `#[vertex(position)]` or `#[vertex(color)]`
+fn semantic(field: &Field) -> syn::Result<Option<Semantic>> {
+    let mut semantic = None;
+    for attribute in field
+        .attrs
+        .iter()
+        .filter(|attribute| attribute.path().is_ident("vertex"))
+    {
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("position") {
+                semantic = Some(Semantic::Position);
+            } else if meta.path.is_ident("color") {
+                semantic = Some(Semantic::Color);
+            } else {
+                return Err(meta.error("expected `position` or `color`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(semantic)
+}
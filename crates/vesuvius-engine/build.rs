@@ -0,0 +1,82 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The crates, whose versions identify the shader compiler. The shaderc-sys crate pins the bundled glslang version.
+const SHADER_COMPILER_CRATES: [&str; 2] = ["shaderc", "shaderc-sys"];
+
+/// The environment variables, which make shaderc-sys link a shaderc library of the system instead of the bundled one.
+/// The glslang version of these libraries isn't known before the runtime, so their locations are part of the version.
+const SHADER_COMPILER_VARIABLES: [&str; 2] = ["SHADERC_LIB_DIR", "VULKAN_SDK"];
+
+/// This build script writes the version of the shader compiler into `VESUVIUS_SHADER_COMPILER_VERSION`, so the shader
+/// cache ignores shaders compiled by another version of shaderc or glslang. The versions are read from the lock file of
+/// the workspace, which builds the engine.
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    if env::var_os("CARGO_FEATURE_SHADERC").is_none() {
+        return;
+    }
+
+    let mut version = Vec::new();
+    match find_lock_file() {
+        Some(lock_file) => {
+            println!("cargo:rerun-if-changed={}", lock_file.display());
+            let lock_file = fs::read_to_string(lock_file).expect("Unable to read lock file");
+            version.extend(
+                locked_packages(&lock_file)
+                    .into_iter()
+                    .filter(|(name, _)| SHADER_COMPILER_CRATES.contains(&name.as_str()))
+                    .map(|(name, version)| format!("{} {}", name, version)),
+            );
+        }
+        None => println!(
+            "cargo:warning=Unable to find the lock file, the shader cache isn't invalidated when shaderc is updated"
+        ),
+    }
+
+    for variable in SHADER_COMPILER_VARIABLES {
+        println!("cargo:rerun-if-env-changed={}", variable);
+        if let Some(value) = env::var_os(variable) {
+            version.push(format!("{}={}", variable, value.to_string_lossy()));
+        }
+    }
+    println!(
+        "cargo:rustc-env=VESUVIUS_SHADER_COMPILER_VERSION={}",
+        version.join(", ")
+    );
+}
+
+/// This function returns the lock file of the workspace. The target directory is usually placed next to the lock file,
+/// so the parents of the output directory are searched first and the parents of the manifest directory afterwards.
+fn find_lock_file() -> Option<PathBuf> {
+    ["OUT_DIR", "CARGO_MANIFEST_DIR"]
+        .into_iter()
+        .filter_map(env::var_os)
+        .flat_map(|directory| {
+            Path::new(&directory)
+                .ancestors()
+                .map(|ancestor| ancestor.join("Cargo.lock"))
+                .collect::<Vec<_>>()
+        })
+        .find(|path| path.is_file())
+}
+
+/// This function returns the names and versions of all packages in the lock file
+fn locked_packages(lock_file: &str) -> Vec<(String, String)> {
+    let value = |line: &str, key: &str| {
+        line.strip_prefix(key)
+            .and_then(|line| line.trim().strip_prefix('='))
+            .map(|value| value.trim().trim_matches('"').to_string())
+    };
+
+    let mut packages = Vec::new();
+    for package in lock_file.split("[[package]]").skip(1) {
+        let name = package.lines().find_map(|line| value(line, "name"));
+        let version = package.lines().find_map(|line| value(line, "version"));
+        if let (Some(name), Some(version)) = (name, version) {
+            packages.push((name, version));
+        }
+    }
+    packages
+}
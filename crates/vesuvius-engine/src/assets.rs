@@ -0,0 +1,198 @@
+use crate::error::Error;
+use crate::render::pipeline::config::{self, PipelineConfiguration, PipelineKind};
+use crate::render::pipeline::diagnostic::Diagnostic;
+use crate::render::pipeline::{shader, specialization};
+use crate::render::text::config::FontAtlas;
+use crate::render::{pipeline, PIPELINES_DIRECTORY};
+use crate::Result;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+/// The directory, which contains the font atlases. Every font is a directory with the `atlas.json` and `atlas.png`.
+pub const FONTS_DIRECTORY: &str = "assets/resources/fonts";
+
+/// This function validates the pipelines and fonts of the asset tree in the current working directory. The pipelines
+/// are parsed, their shaders are compiled and reflected, and the font atlases are checked against their images. This
+/// doesn't require a device, so the assets can be validated without a GPU. All problems are returned as diagnostics.
+pub fn validate_assets() -> Vec<Diagnostic> {
+    let mut diagnostics = validate_pipelines();
+    diagnostics.extend(validate_fonts());
+    diagnostics
+}
+
+/// This function validates every pipeline configuration and all variants of the pipelines
+pub fn validate_pipelines() -> Vec<Diagnostic> {
+    let directory = Path::new(PIPELINES_DIRECTORY);
+    let mut config_files = match list_directory(directory) {
+        Ok(config_files) => config_files,
+        Err(error) => return Diagnostic::from_error("", directory, error),
+    };
+    config_files.retain(|path| config::is_pipeline_configuration(path));
+
+    let mut diagnostics = Vec::new();
+    for config_file in config_files {
+        let pipeline_config = match PipelineConfiguration::from_file(&config_file) {
+            Ok(pipeline_config) => pipeline_config,
+            Err(error) => {
+                diagnostics.extend(Diagnostic::from_error(
+                    &config::config_file_name(&config_file),
+                    &config_file,
+                    error,
+                ));
+                continue;
+            }
+        };
+
+        for variant in pipeline_config.variant_names() {
+            if let Err(error) = validate_pipeline(&pipeline_config, variant) {
+                diagnostics.extend(Diagnostic::from_error(
+                    &pipeline_config.variant_name(variant),
+                    &config_file,
+                    error,
+                ));
+            }
+        }
+    }
+    diagnostics
+}
+
+/// This function compiles and reflects the shaders of the pipeline variant and validates their interfaces like the
+/// renderer does while compiling the pipeline.
+fn validate_pipeline(pipeline_config: &PipelineConfiguration, variant: Option<&str>) -> Result<()> {
+    let (name, defines) = pipeline_config.variant(variant)?;
+
+    // The shaders are validated independently, so the problems of all shaders are reported
+    let mut reflections = Vec::new();
+    let mut diagnostics = Vec::new();
+    for shader_configuration in pipeline_config.shader.iter() {
+        let shader_path = Path::new(&shader_configuration.resource);
+        let entry_point = shader_configuration
+            .entry_point
+            .as_deref()
+            .unwrap_or(shader::DEFAULT_ENTRY_POINT);
+        let reflection = shader::load_file(
+            shader_path,
+            shader_configuration.kind,
+            entry_point,
+            &defines,
+        )
+        .and_then(|(shader_ir_code, _)| {
+            shader::reflect_spirv(
+                shader_path,
+                &shader_ir_code,
+                shader_configuration.kind,
+                entry_point,
+            )
+        });
+        match reflection {
+            Ok(reflection) => reflections.push(reflection),
+            Err(error) => diagnostics.extend(Diagnostic::from_error(&name, shader_path, error)),
+        }
+    }
+    if !diagnostics.is_empty() {
+        return Err(Error::Diagnostics(diagnostics));
+    }
+
+    pipeline::merge_descriptor_sets(&name, &reflections)?;
+    pipeline::validate_interface(
+        &name,
+        &pipeline_config.path,
+        pipeline_config.kind,
+        pipeline_config.vertex_format,
+        pipeline_config.instance_location,
+        &reflections,
+    )?;
+    specialization::specialize(
+        &name,
+        &pipeline_config.path,
+        &pipeline_config.specialization,
+        &reflections,
+    )?;
+    match pipeline_config.kind {
+        PipelineKind::Graphics => pipeline::validate_tessellation(
+            &name,
+            &pipeline_config.path,
+            &pipeline_config.state,
+            &reflections,
+            pipeline::GUARANTEED_TESSELLATION_PATCH_SIZE,
+        ),
+        PipelineKind::Compute => Ok(()),
+    }
+}
+
+/// This function validates every font atlas in the fonts directory
+pub fn validate_fonts() -> Vec<Diagnostic> {
+    let directory = Path::new(FONTS_DIRECTORY);
+    let font_directories = match list_directory(directory) {
+        Ok(font_directories) => font_directories,
+        Err(error) => return Diagnostic::from_error("", directory, error),
+    };
+
+    font_directories
+        .iter()
+        .filter(|path| path.is_dir())
+        .flat_map(|font_directory| validate_font(font_directory))
+        .collect()
+}
+
+/// This function checks, that the atlas configuration of the font can be read and that the size of the atlas image and
+/// the bounds of all glyphs match the configuration.
+fn validate_font(font_directory: &Path) -> Vec<Diagnostic> {
+    let name = config::config_file_name(font_directory);
+    let (config_path, image_path) = (
+        font_directory.join("atlas.json"),
+        font_directory.join("atlas.png"),
+    );
+    let atlas = match fs::read(&config_path) {
+        Ok(content) => match serde_json::from_slice::<FontAtlas>(&content) {
+            Ok(atlas) => atlas,
+            Err(error) => return vec![Diagnostic::from_json_error(&name, &config_path, &error)],
+        },
+        Err(error) => return Diagnostic::from_error(&name, &config_path, error.into()),
+    };
+
+    let (width, height) = match ::image::image_dimensions(&image_path) {
+        Ok(dimensions) => dimensions,
+        Err(error) => return Diagnostic::from_error(&name, &image_path, error.into()),
+    };
+
+    let mut diagnostics = Vec::new();
+    if (width, height) != (atlas.atlas.width as u32, atlas.atlas.height as u32) {
+        diagnostics.push(Diagnostic::new(
+            &name,
+            &image_path,
+            format!(
+                "The atlas image is {}x{}, but the atlas configuration declares {}x{}",
+                width, height, atlas.atlas.width, atlas.atlas.height
+            ),
+        ));
+    }
+
+    for glyph in atlas.glyphs.iter() {
+        let bounds = &glyph.atlas_bounds;
+        let horizontal =
+            bounds.left.min(bounds.right) >= 0.0 && bounds.left.max(bounds.right) <= width as f32;
+        let vertical =
+            bounds.top.min(bounds.bottom) >= 0.0 && bounds.top.max(bounds.bottom) <= height as f32;
+        if !horizontal || !vertical {
+            diagnostics.push(Diagnostic::new(
+                &name,
+                &config_path,
+                format!(
+                    "The atlas bounds of glyph {} are outside of the {}x{} atlas image",
+                    glyph.unicode, width, height
+                ),
+            ));
+        }
+    }
+    diagnostics
+}
+
+/// This function returns the sorted paths of all entries in the directory, so the diagnostics have a stable order
+fn list_directory(directory: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    paths.sort_unstable();
+    Ok(paths)
+}
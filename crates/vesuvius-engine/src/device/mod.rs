@@ -0,0 +1,528 @@
+pub mod selection;
+
+use crate::error::Error;
+use crate::Result;
+use ash::extensions::ext::DebugUtils;
+use ash::vk::PhysicalDevice;
+use ash::{vk, Device, Instance};
+use log::warn;
+use std::ffi::{CStr, CString};
+use std::fmt::{Display, Formatter};
+use std::slice;
+use std::sync::Arc;
+use vk_mem_alloc::{Allocator, AllocatorCreateInfo};
+
+/// This structure contains the indices of the queue families used by the engine. On drivers with a single queue
+/// family, all indices point to the same family.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct QueueFamilies {
+    pub graphics: u32,
+    pub present: u32,
+    pub transfer: u32,
+}
+
+impl QueueFamilies {
+    /// This function returns the unique indices of the queue families, every family gets a single queue
+    pub fn unique(&self) -> Vec<u32> {
+        let mut families = vec![self.graphics, self.present, self.transfer];
+        families.sort_unstable();
+        families.dedup();
+        families
+    }
+
+    /// This function returns true, if the transfer queue is in another family than the graphics queue. Resources
+    /// uploaded by the transfer queue have to be transferred to the graphics family in this case.
+    #[inline]
+    pub fn has_separate_transfer(&self) -> bool {
+        self.transfer != self.graphics
+    }
+}
+
+pub struct WrappedDeviceInner {
+    vk_instance: Instance,
+    physical_device: vk::PhysicalDevice,
+    virtual_device: Device,
+    allocator: Allocator,
+    queue_families: QueueFamilies,
+    graphics_queue: vk::Queue,
+    present_queue: vk::Queue,
+    transfer_queue: vk::Queue,
+    enabled_features: vk::PhysicalDeviceFeatures,
+
+    /// The loader of the debug utils, this is none when the extension isn't available
+    debug_utils: Option<DebugUtils>,
+}
+
+impl Drop for WrappedDeviceInner {
+    fn drop(&mut self) {
+        unsafe {
+            vk_mem_alloc::destroy_allocator(self.allocator);
+            self.virtual_device.destroy_device(None);
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct WrappedDevice(Arc<WrappedDeviceInner>);
+
+impl Display for WrappedDevice {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        let device_name = unsafe {
+            self.0
+                .vk_instance
+                .get_physical_device_properties(self.0.physical_device)
+        }
+        .device_name;
+        write!(
+            formatter,
+            "{}",
+            unsafe { CStr::from_ptr(device_name.as_ptr()) }
+                .to_str()
+                .unwrap()
+        )
+    }
+}
+
+impl WrappedDevice {
+    /// This function creates the logical device with a single queue of every specified queue family. The swapchain
+    /// extension is only enabled when the device has to present into a window, so headless devices also work on
+    /// drivers without presentation support.
+    pub fn new(
+        vk_instance: Instance,
+        physical_device: vk::PhysicalDevice,
+        queue_families: QueueFamilies,
+        presentable: bool,
+        debug_utils: Option<DebugUtils>,
+    ) -> Result<Self> {
+        let queue_create_infos = queue_families
+            .unique()
+            .into_iter()
+            .map(|queue_family_index| {
+                vk::DeviceQueueCreateInfo::default()
+                    .queue_family_index(queue_family_index)
+                    .queue_priorities(slice::from_ref(&1.0))
+            })
+            .collect::<Vec<_>>();
+
+        let mut vulkan13_features =
+            vk::PhysicalDeviceVulkan13Features::default().dynamic_rendering(true);
+
+        // Optional features are only enabled, if the device supports them
+        let supported_features =
+            unsafe { vk_instance.get_physical_device_features(physical_device) };
+        let features = vk::PhysicalDeviceFeatures::default()
+            .sampler_anisotropy(true)
+            .fill_mode_non_solid(supported_features.fill_mode_non_solid == vk::TRUE)
+            .geometry_shader(supported_features.geometry_shader == vk::TRUE)
+            .tessellation_shader(supported_features.tessellation_shader == vk::TRUE);
+        let mut features2 = vk::PhysicalDeviceFeatures2::default()
+            .push_next(&mut vulkan13_features)
+            .features(features);
+
+        let device_extensions = if presentable {
+            vec![b"VK_KHR_swapchain\0".as_ptr().cast()]
+        } else {
+            Vec::new()
+        };
+        let device_create_info = vk::DeviceCreateInfo::default()
+            .push_next(&mut features2)
+            .enabled_extension_names(device_extensions.as_slice())
+            .queue_create_infos(queue_create_infos.as_slice());
+
+        let virtual_device =
+            unsafe { vk_instance.create_device(physical_device, &device_create_info, None) }?;
+        Ok(Self(Arc::new(WrappedDeviceInner {
+            allocator: unsafe {
+                vk_mem_alloc::create_allocator(
+                    &vk_instance,
+                    physical_device,
+                    &virtual_device,
+                    Some(&AllocatorCreateInfo::default()),
+                )?
+            },
+            graphics_queue: unsafe { virtual_device.get_device_queue(queue_families.graphics, 0) },
+            present_queue: unsafe { virtual_device.get_device_queue(queue_families.present, 0) },
+            transfer_queue: unsafe { virtual_device.get_device_queue(queue_families.transfer, 0) },
+            queue_families,
+            enabled_features: features,
+            debug_utils,
+            physical_device,
+            virtual_device,
+            vk_instance,
+        })))
+    }
+
+    /// This function records the layout transition of a color image
+    #[inline]
+    pub(crate) fn memory_barrier(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        image: vk::Image,
+        old_layout: vk::ImageLayout,
+        new_layout: vk::ImageLayout,
+    ) -> Result<()> {
+        self.image_barrier(
+            command_buffer,
+            image,
+            vk::ImageAspectFlags::COLOR,
+            old_layout,
+            new_layout,
+        )
+    }
+
+    /// This function records the layout transition of the specified aspects of the image. Depth images with a stencil
+    /// component have to transition both aspects, because the layouts of depth and stencil aren't separated.
+    pub(crate) fn image_barrier(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        image: vk::Image,
+        aspect_mask: vk::ImageAspectFlags,
+        old_layout: vk::ImageLayout,
+        new_layout: vk::ImageLayout,
+    ) -> Result<()> {
+        let (src_access_mask, dst_access_mask, src_stage_mask, dst_stage_mask) =
+            match (old_layout, new_layout) {
+                (vk::ImageLayout::UNDEFINED, vk::ImageLayout::TRANSFER_DST_OPTIMAL) => (
+                    vk::AccessFlags::empty(),
+                    vk::AccessFlags::TRANSFER_WRITE,
+                    vk::PipelineStageFlags::TOP_OF_PIPE,
+                    vk::PipelineStageFlags::TRANSFER,
+                ),
+                (vk::ImageLayout::UNDEFINED, vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL) => (
+                    vk::AccessFlags::empty(),
+                    vk::AccessFlags::empty(),
+                    vk::PipelineStageFlags::TOP_OF_PIPE,
+                    vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                ),
+                (vk::ImageLayout::UNDEFINED, vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL) => {
+                    (
+                        vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+                        vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ
+                            | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+                        vk::PipelineStageFlags::LATE_FRAGMENT_TESTS,
+                        vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS
+                            | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS,
+                    )
+                }
+                (vk::ImageLayout::UNDEFINED, vk::ImageLayout::GENERAL) => (
+                    vk::AccessFlags::empty(),
+                    vk::AccessFlags::SHADER_READ | vk::AccessFlags::SHADER_WRITE,
+                    vk::PipelineStageFlags::TOP_OF_PIPE,
+                    vk::PipelineStageFlags::COMPUTE_SHADER
+                        | vk::PipelineStageFlags::FRAGMENT_SHADER,
+                ),
+                (
+                    vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                ) => (
+                    vk::AccessFlags::TRANSFER_WRITE,
+                    vk::AccessFlags::SHADER_READ,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::PipelineStageFlags::FRAGMENT_SHADER,
+                ),
+                (vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL, vk::ImageLayout::PRESENT_SRC_KHR) => (
+                    vk::AccessFlags::empty(),
+                    vk::AccessFlags::empty(),
+                    vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                    vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                ),
+                (
+                    vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+                    vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                ) => (
+                    vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+                    vk::AccessFlags::TRANSFER_READ,
+                    vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                    vk::PipelineStageFlags::TRANSFER,
+                ),
+                (
+                    vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                    vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+                ) => (
+                    vk::AccessFlags::TRANSFER_READ,
+                    vk::AccessFlags::COLOR_ATTACHMENT_READ
+                        | vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                ),
+                _ => {
+                    return Err(Error::UnsupportedLayoutTransition {
+                        old: old_layout,
+                        new: new_layout,
+                    })
+                }
+            };
+
+        let memory_barrier = vk::ImageMemoryBarrier::default()
+            .src_access_mask(src_access_mask)
+            .dst_access_mask(dst_access_mask)
+            .new_layout(new_layout)
+            .old_layout(old_layout)
+            .image(image)
+            .subresource_range(
+                vk::ImageSubresourceRange::default()
+                    .aspect_mask(aspect_mask)
+                    .level_count(1)
+                    .layer_count(1),
+            );
+        unsafe {
+            self.virtual_device().cmd_pipeline_barrier(
+                command_buffer,
+                src_stage_mask,
+                dst_stage_mask,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                slice::from_ref(&memory_barrier),
+            );
+        }
+        Ok(())
+    }
+
+    /// This function records a global memory barrier, which makes the specified accesses of the source stages available
+    /// and visible to the specified accesses of the destination stages. It's used to synchronize compute dispatches
+    /// with the surrounding graphics work, which accesses the same buffers and storage images.
+    pub(crate) fn global_memory_barrier(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        (src_access_mask, src_stage_mask): (vk::AccessFlags, vk::PipelineStageFlags),
+        (dst_access_mask, dst_stage_mask): (vk::AccessFlags, vk::PipelineStageFlags),
+    ) {
+        let memory_barrier = vk::MemoryBarrier::default()
+            .src_access_mask(src_access_mask)
+            .dst_access_mask(dst_access_mask);
+        unsafe {
+            self.virtual_device().cmd_pipeline_barrier(
+                command_buffer,
+                src_stage_mask,
+                dst_stage_mask,
+                vk::DependencyFlags::empty(),
+                slice::from_ref(&memory_barrier),
+                &[],
+                &[],
+            );
+        }
+    }
+
+    /// This function releases the ownership of the uploaded image from the transfer queue family and transitions the
+    /// layout. If the transfer and graphics queues share the family, this is a normal barrier for the transition and
+    /// [WrappedDevice::acquire_image_ownership] records nothing.
+    pub(crate) fn release_image_ownership(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        image: vk::Image,
+        old_layout: vk::ImageLayout,
+        new_layout: vk::ImageLayout,
+    ) -> Result<()> {
+        if !self.0.queue_families.has_separate_transfer() {
+            return self.memory_barrier(command_buffer, image, old_layout, new_layout);
+        }
+
+        let image_memory_barrier = self
+            .ownership_image_barrier(image, old_layout, new_layout)
+            .src_access_mask(vk::AccessFlags::TRANSFER_WRITE);
+        unsafe {
+            self.virtual_device().cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                slice::from_ref(&image_memory_barrier),
+            );
+        }
+        Ok(())
+    }
+
+    /// This function acquires the ownership of the uploaded image in the graphics queue family. The layouts must be
+    /// the same as in the release by [WrappedDevice::release_image_ownership].
+    pub(crate) fn acquire_image_ownership(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        image: vk::Image,
+        old_layout: vk::ImageLayout,
+        new_layout: vk::ImageLayout,
+    ) {
+        if !self.0.queue_families.has_separate_transfer() {
+            return;
+        }
+
+        let image_memory_barrier = self
+            .ownership_image_barrier(image, old_layout, new_layout)
+            .dst_access_mask(vk::AccessFlags::SHADER_READ);
+        unsafe {
+            self.virtual_device().cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::TOP_OF_PIPE,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                slice::from_ref(&image_memory_barrier),
+            );
+        }
+    }
+
+    fn ownership_image_barrier(
+        &self,
+        image: vk::Image,
+        old_layout: vk::ImageLayout,
+        new_layout: vk::ImageLayout,
+    ) -> vk::ImageMemoryBarrier<'static> {
+        vk::ImageMemoryBarrier::default()
+            .src_queue_family_index(self.0.queue_families.transfer)
+            .dst_queue_family_index(self.0.queue_families.graphics)
+            .old_layout(old_layout)
+            .new_layout(new_layout)
+            .image(image)
+            .subresource_range(
+                vk::ImageSubresourceRange::default()
+                    .aspect_mask(vk::ImageAspectFlags::COLOR)
+                    .level_count(1)
+                    .layer_count(1),
+            )
+    }
+
+    /// This function releases the ownership of the uploaded buffer from the transfer queue family. If the transfer and
+    /// graphics queues share the family, this is a barrier, which makes the written data visible for the specified
+    /// access.
+    pub(crate) fn release_buffer_ownership(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        buffer: vk::Buffer,
+        dst_access_mask: vk::AccessFlags,
+        dst_stage_mask: vk::PipelineStageFlags,
+    ) {
+        let separate_transfer = self.0.queue_families.has_separate_transfer();
+        let buffer_memory_barrier = self
+            .ownership_buffer_barrier(buffer)
+            .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+            .dst_access_mask(if separate_transfer {
+                vk::AccessFlags::empty()
+            } else {
+                dst_access_mask
+            });
+        unsafe {
+            self.virtual_device().cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::TRANSFER,
+                if separate_transfer {
+                    vk::PipelineStageFlags::BOTTOM_OF_PIPE
+                } else {
+                    dst_stage_mask
+                },
+                vk::DependencyFlags::empty(),
+                &[],
+                slice::from_ref(&buffer_memory_barrier),
+                &[],
+            );
+        }
+    }
+
+    /// This function acquires the ownership of the uploaded buffer in the graphics queue family
+    pub(crate) fn acquire_buffer_ownership(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        buffer: vk::Buffer,
+        dst_access_mask: vk::AccessFlags,
+        dst_stage_mask: vk::PipelineStageFlags,
+    ) {
+        if !self.0.queue_families.has_separate_transfer() {
+            return;
+        }
+
+        let buffer_memory_barrier = self
+            .ownership_buffer_barrier(buffer)
+            .dst_access_mask(dst_access_mask);
+        unsafe {
+            self.virtual_device().cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::TOP_OF_PIPE,
+                dst_stage_mask,
+                vk::DependencyFlags::empty(),
+                &[],
+                slice::from_ref(&buffer_memory_barrier),
+                &[],
+            );
+        }
+    }
+
+    fn ownership_buffer_barrier(&self, buffer: vk::Buffer) -> vk::BufferMemoryBarrier<'static> {
+        let queue_families = self.0.queue_families;
+        let (src_queue_family_index, dst_queue_family_index) =
+            if queue_families.has_separate_transfer() {
+                (queue_families.transfer, queue_families.graphics)
+            } else {
+                (vk::QUEUE_FAMILY_IGNORED, vk::QUEUE_FAMILY_IGNORED)
+            };
+        vk::BufferMemoryBarrier::default()
+            .src_queue_family_index(src_queue_family_index)
+            .dst_queue_family_index(dst_queue_family_index)
+            .buffer(buffer)
+            .size(vk::WHOLE_SIZE)
+    }
+
+    /// This function assigns the specified name to the Vulkan object, so validation messages and debugging tools like
+    /// RenderDoc show the name instead of the handle. Without the debug utils extension, this does nothing.
+    pub fn set_object_name<T: vk::Handle>(&self, handle: T, name: &str) {
+        let Some(debug_utils) = self.0.debug_utils.as_ref() else {
+            return;
+        };
+        let Ok(object_name) = CString::new(name) else {
+            return;
+        };
+
+        let mut object_name_info = vk::DebugUtilsObjectNameInfoEXT::default();
+        object_name_info.object_type = T::TYPE;
+        object_name_info.object_handle = handle.as_raw();
+        object_name_info.p_object_name = object_name.as_ptr();
+        if let Err(error) = unsafe {
+            debug_utils
+                .set_debug_utils_object_name(self.0.virtual_device.handle(), &object_name_info)
+        } {
+            warn!("Unable to name object '{}' => {}", name, error);
+        }
+    }
+
+    #[inline]
+    pub fn queue_families(&self) -> QueueFamilies {
+        self.0.queue_families
+    }
+
+    /// This function returns the features, which are enabled on this device
+    #[inline]
+    pub fn enabled_features(&self) -> vk::PhysicalDeviceFeatures {
+        self.0.enabled_features
+    }
+
+    #[inline]
+    pub(crate) fn graphics_queue(&self) -> &vk::Queue {
+        &self.0.graphics_queue
+    }
+
+    #[inline]
+    pub(crate) fn present_queue(&self) -> &vk::Queue {
+        &self.0.present_queue
+    }
+
+    #[inline]
+    pub(crate) fn transfer_queue(&self) -> &vk::Queue {
+        &self.0.transfer_queue
+    }
+
+    #[inline]
+    pub(crate) fn allocator(&self) -> &Allocator {
+        &self.0.allocator
+    }
+
+    #[inline]
+    pub(crate) fn virtual_device(&self) -> &Device {
+        &self.0.virtual_device
+    }
+
+    #[inline]
+    pub(crate) fn physical_device(&self) -> PhysicalDevice {
+        self.0.physical_device
+    }
+}
@@ -0,0 +1,328 @@
+use crate::device::QueueFamilies;
+use crate::error::Error;
+use crate::Result;
+use ash::extensions::khr::Surface;
+use ash::{vk, Entry, Instance};
+use itertools::Itertools;
+use log::{info, warn};
+use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle};
+use std::ffi::CStr;
+use std::fmt::{Display, Formatter};
+use winit::window::Window;
+
+/// The name of the environment variable, which overrides the device selection by index or name
+pub const DEVICE_ENV_VAR: &str = "VESUVIUS_DEVICE";
+
+/// This enum describes, how the physical device of the application is selected
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub enum DeviceSelector {
+    /// The suitable device with the highest score is selected
+    #[default]
+    Auto,
+
+    /// The device with the specified index in the enumeration is selected
+    Index(usize),
+
+    /// The first device, which contains the specified name (case-insensitive), is selected
+    Name(String),
+}
+
+impl DeviceSelector {
+    /// This function reads the selector from the `VESUVIUS_DEVICE` environment variable. A number is interpreted as
+    /// index, every other value as name. If the variable isn't set, the automatic selection is used.
+    pub fn from_env() -> Self {
+        match std::env::var(DEVICE_ENV_VAR) {
+            Ok(value) if !value.trim().is_empty() => match value.trim().parse::<usize>() {
+                Ok(index) => Self::Index(index),
+                Err(_) => Self::Name(value.trim().to_string()),
+            },
+            _ => Self::Auto,
+        }
+    }
+}
+
+/// This structure describes a physical device, which was found while enumerating the devices. The missing
+/// requirements are empty, if the device can be used by the engine.
+#[derive(Clone, Debug)]
+pub struct DeviceCandidate {
+    pub index: usize,
+    pub name: String,
+    pub device_type: vk::PhysicalDeviceType,
+    pub api_version: u32,
+    pub local_heap_size: u64,
+    pub missing_requirements: Vec<String>,
+    pub(crate) physical_device: vk::PhysicalDevice,
+    pub queue_families: QueueFamilies,
+}
+
+impl Display for DeviceCandidate {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            formatter,
+            "#{} {} ({:?}, Vulkan {}.{}.{}, {} MiB local memory)",
+            self.index,
+            self.name,
+            self.device_type,
+            vk::api_version_major(self.api_version),
+            vk::api_version_minor(self.api_version),
+            vk::api_version_patch(self.api_version),
+            self.local_heap_size / (1024 * 1024)
+        )
+    }
+}
+
+impl DeviceCandidate {
+    #[inline]
+    pub fn is_suitable(&self) -> bool {
+        self.missing_requirements.is_empty()
+    }
+
+    /// This function returns the score of the device. Discrete GPUs are preferred over integrated GPUs and these are
+    /// preferred over CPU implementations. Devices of the same type are ordered by the size of the local memory.
+    pub fn score(&self) -> u64 {
+        let type_score = match self.device_type {
+            vk::PhysicalDeviceType::DISCRETE_GPU => 4,
+            vk::PhysicalDeviceType::INTEGRATED_GPU => 3,
+            vk::PhysicalDeviceType::VIRTUAL_GPU => 2,
+            vk::PhysicalDeviceType::CPU => 1,
+            _ => 0,
+        };
+        (type_score << 48) | (self.local_heap_size >> 20).min((1 << 48) - 1)
+    }
+}
+
+/// This function enumerates all physical devices and checks them against the requirements of the engine. If a window
+/// is specified, a temporary surface is created to check the presentation support.
+pub(crate) fn enumerate_candidates(
+    entry: &Entry,
+    instance: &Instance,
+    window: Option<&Window>,
+) -> Result<Vec<DeviceCandidate>> {
+    let surface_loader = Surface::new(entry, instance);
+    let surface = match window {
+        Some(window) => Some(unsafe {
+            ash_window::create_surface(
+                entry,
+                instance,
+                window.raw_display_handle(),
+                window.raw_window_handle(),
+                None,
+            )
+        }?),
+        None => None,
+    };
+
+    let candidates = unsafe { instance.enumerate_physical_devices() }.and_then(|devices| {
+        devices
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, physical_device)| {
+                let candidate = unsafe {
+                    check_device(instance, &surface_loader, surface, index, physical_device)
+                };
+
+                // A device, which can't be queried, is skipped, so the other devices can still be selected
+                candidate
+                    .map_err(|error| warn!("Unable to check device #{} => {}", index, error))
+                    .ok()
+            })
+            .collect::<Vec<_>>()
+    });
+
+    if let Some(surface) = surface {
+        unsafe { surface_loader.destroy_surface(surface, None) };
+    }
+    Ok(candidates?)
+}
+
+/// This function selects the device by the specified selector. The devices selected by index or name must also be
+/// suitable, the automatic selection takes the suitable device with the highest score.
+pub(crate) fn select_device(
+    candidates: &[DeviceCandidate],
+    selector: &DeviceSelector,
+) -> Result<DeviceCandidate> {
+    for candidate in candidates {
+        if candidate.is_suitable() {
+            info!("Found device {}", candidate);
+        } else {
+            info!(
+                "Found device {}, which is unsuitable => {}",
+                candidate,
+                candidate.missing_requirements.join(", ")
+            );
+        }
+    }
+
+    let selected = match selector {
+        DeviceSelector::Auto => candidates
+            .iter()
+            .filter(|candidate| candidate.is_suitable())
+            .sorted_by_key(|candidate| std::cmp::Reverse(candidate.score()))
+            .next(),
+        DeviceSelector::Index(index) => candidates
+            .iter()
+            .find(|candidate| candidate.index == *index),
+        DeviceSelector::Name(name) => {
+            let name = name.to_lowercase();
+            candidates
+                .iter()
+                .find(|candidate| candidate.name.to_lowercase().contains(&name))
+        }
+    };
+
+    match selected {
+        Some(candidate) if candidate.is_suitable() => Ok(candidate.clone()),
+        Some(candidate) => Err(Error::NoSuitableDevice(format!(
+            "The selected device '{}' is unsuitable => {}",
+            candidate.name,
+            candidate.missing_requirements.join(", ")
+        ))),
+        None if *selector == DeviceSelector::Auto => Err(Error::NoSuitableDevice(format!(
+            "None of the {} devices fulfills the requirements",
+            candidates.len()
+        ))),
+        None => Err(Error::NoSuitableDevice(format!(
+            "No device matches the selector {:?}",
+            selector
+        ))),
+    }
+}
+
+unsafe fn check_device(
+    instance: &Instance,
+    surface_loader: &Surface,
+    surface: Option<vk::SurfaceKHR>,
+    index: usize,
+    physical_device: vk::PhysicalDevice,
+) -> ash::prelude::VkResult<DeviceCandidate> {
+    let properties = instance.get_physical_device_properties(physical_device);
+    let mut missing_requirements = Vec::new();
+
+    // Vulkan 1.3 is required for dynamic rendering and synchronization2
+    if properties.api_version < vk::API_VERSION_1_3 {
+        missing_requirements.push("Vulkan 1.3".to_string());
+    }
+
+    // Check features
+    let mut vulkan13_features = vk::PhysicalDeviceVulkan13Features::default();
+    let mut features2 = vk::PhysicalDeviceFeatures2::default().push_next(&mut vulkan13_features);
+    instance.get_physical_device_features2(physical_device, &mut features2);
+    let features = features2.features;
+    if vulkan13_features.dynamic_rendering == vk::FALSE {
+        missing_requirements.push("Feature dynamicRendering".to_string());
+    }
+    if features.sampler_anisotropy == vk::FALSE {
+        missing_requirements.push("Feature samplerAnisotropy".to_string());
+    }
+
+    // Check extensions, the swapchain is only required when presenting into a window
+    if surface.is_some() {
+        let extensions = instance.enumerate_device_extension_properties(physical_device)?;
+        let swapchain_supported = extensions.iter().any(|extension| {
+            CStr::from_ptr(extension.extension_name.as_ptr()).to_bytes() == b"VK_KHR_swapchain"
+        });
+        if !swapchain_supported {
+            missing_requirements.push("Extension VK_KHR_swapchain".to_string());
+        }
+    }
+
+    // Find queue families, a device without graphics or present support is unsuitable
+    let queue_families = find_queue_families(instance, surface_loader, surface, physical_device)?;
+    if queue_families.is_none() {
+        missing_requirements.push("Queue families with graphics and present support".to_string());
+    }
+
+    let memory_properties = instance.get_physical_device_memory_properties(physical_device);
+    let local_heap_size = memory_properties.memory_heaps
+        [..memory_properties.memory_heap_count as usize]
+        .iter()
+        .filter(|heap| heap.flags.contains(vk::MemoryHeapFlags::DEVICE_LOCAL))
+        .map(|heap| heap.size)
+        .sum();
+
+    Ok(DeviceCandidate {
+        index,
+        name: CStr::from_ptr(properties.device_name.as_ptr())
+            .to_string_lossy()
+            .into_owned(),
+        device_type: properties.device_type,
+        api_version: properties.api_version,
+        local_heap_size,
+        missing_requirements,
+        physical_device,
+        queue_families: queue_families.unwrap_or_default(),
+    })
+}
+
+/// This function discovers the queue families of the device. The graphics family, which also supports presentation, is
+/// preferred. For the transfer family, a dedicated family without graphics and compute support is preferred, because
+/// these families are backed by the DMA engines of discrete GPUs. If no separate family exists, the graphics family
+/// is used for every operation.
+unsafe fn find_queue_families(
+    instance: &Instance,
+    surface_loader: &Surface,
+    surface: Option<vk::SurfaceKHR>,
+    physical_device: vk::PhysicalDevice,
+) -> ash::prelude::VkResult<Option<QueueFamilies>> {
+    let queue_family_properties =
+        instance.get_physical_device_queue_family_properties(physical_device);
+
+    let mut present_families = Vec::new();
+    for family_index in 0..queue_family_properties.len() as u32 {
+        let present_supported = match surface {
+            Some(surface) => surface_loader.get_physical_device_surface_support(
+                physical_device,
+                family_index,
+                surface,
+            )?,
+            None => true,
+        };
+        if present_supported {
+            present_families.push(family_index);
+        }
+    }
+
+    let families_with = |flags: vk::QueueFlags| {
+        queue_family_properties
+            .iter()
+            .enumerate()
+            .filter(move |(_, properties)| {
+                properties.queue_count > 0 && properties.queue_flags.contains(flags)
+            })
+            .map(|(family_index, properties)| (family_index as u32, properties.queue_flags))
+    };
+
+    let Some(graphics) = families_with(vk::QueueFlags::GRAPHICS)
+        .map(|(family_index, _)| family_index)
+        .find(|family_index| present_families.contains(family_index))
+        .or_else(|| {
+            families_with(vk::QueueFlags::GRAPHICS)
+                .map(|(family_index, _)| family_index)
+                .next()
+        })
+    else {
+        return Ok(None);
+    };
+    let Some(present) = present_families
+        .contains(&graphics)
+        .then_some(graphics)
+        .or_else(|| present_families.first().copied())
+    else {
+        return Ok(None);
+    };
+
+    let transfer = families_with(vk::QueueFlags::TRANSFER)
+        .find(|(_, flags)| !flags.intersects(vk::QueueFlags::GRAPHICS | vk::QueueFlags::COMPUTE))
+        .or_else(|| {
+            families_with(vk::QueueFlags::TRANSFER)
+                .find(|(_, flags)| !flags.contains(vk::QueueFlags::GRAPHICS))
+        })
+        .map(|(family_index, _)| family_index)
+        .unwrap_or(graphics);
+
+    Ok(Some(QueueFamilies {
+        graphics,
+        present,
+        transfer,
+    }))
+}
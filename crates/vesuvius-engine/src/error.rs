@@ -1,5 +1,7 @@
+use crate::render::pipeline::diagnostic::{self, Diagnostic};
 use ash::{vk, LoadingError};
 use std::io;
+use std::path::PathBuf;
 use std::string::FromUtf8Error;
 use thiserror::Error;
 
@@ -20,12 +22,118 @@ pub enum Error {
     #[error("Error while creating shader => Unable to create SPIR-V compiler")]
     CompilerCreation,
 
+    #[cfg(feature = "shaderc")]
     #[error("Error while creating shader => {0}")]
     ShaderCompiler(#[from] shaderc::Error),
 
+    #[error("Error while creating shader => The shader {0:?} has to be compiled, but the engine is built without the shaderc feature")]
+    ShaderCompilerUnavailable(PathBuf),
+
+    #[error("Error while loading pipelines => Found {} problems:{}", .0.len(), diagnostic::format_diagnostics(.0))]
+    Diagnostics(Vec<Diagnostic>),
+
+    #[error("Error while reflecting shader => {0}")]
+    Reflection(&'static str),
+
+    #[error("Error while watching assets => {0}")]
+    Watcher(#[from] notify::Error),
+
     #[error("Error while decoding image resource => {0}")]
     Image(#[from] image::ImageError),
 
     #[error("Error while reading from Json => {0}")]
     Json(#[from] serde_json::Error),
+
+    #[error("Error while capturing frame => The render target doesn't support transfer")]
+    CaptureUnsupported,
+
+    #[error("Error while capturing frame => The format {0:?} of the render target can't be converted into RGBA")]
+    CaptureFormatUnsupported(vk::Format),
+
+    #[error("Error while capturing frame => {size} bytes of pixels don't fill an image with {width}x{height} pixels")]
+    CaptureSizeMismatch { width: u32, height: u32, size: usize },
+
+    #[error("Error while selecting device => {0}")]
+    NoSuitableDevice(String),
+
+    #[error("Error while finding pipeline => No pipeline with the name '{0}' exists")]
+    PipelineNotFound(String),
+
+    #[error("Error while adding pipeline => A pipeline with the name '{0}' already exists")]
+    PipelineExists(String),
+
+    #[error(
+        "Error while finding pipeline => The pipeline '{pipeline}' has no variant '{variant}'"
+    )]
+    VariantNotFound { pipeline: String, variant: String },
+
+    #[error("Error while compiling pipeline => Pipeline '{pipeline}' requires {samples} samples, but the renderer uses {renderer_samples}")]
+    SampleCountMismatch {
+        pipeline: String,
+        samples: u32,
+        renderer_samples: u32,
+    },
+
+    #[error("Error while compiling pipeline => Pipeline '{pipeline}' requires the unsupported device feature {feature}")]
+    UnsupportedFeature {
+        pipeline: String,
+        feature: &'static str,
+    },
+
+    #[error("Error while compiling pipeline => The shaders of pipeline '{pipeline}' declare binding {binding} of set {set} with different types or counts")]
+    DescriptorBindingConflict {
+        pipeline: String,
+        set: u32,
+        binding: u32,
+    },
+
+    #[error("Error while allocating descriptor set => No set with the index {set} in pipeline '{pipeline}'")]
+    DescriptorSetNotFound { pipeline: String, set: usize },
+
+    #[error("Error while pushing constants => Pipeline '{0}' has no push constants")]
+    NoPushConstants(String),
+
+    #[error("Error while pushing constants => Data size ({size}) doesn't match the push constants of pipeline '{pipeline}' ({expected})")]
+    PushConstantSizeMismatch {
+        pipeline: String,
+        size: u32,
+        expected: u32,
+    },
+
+    #[error("Error while drawing instances => Pipeline '{0}' has no per-instance inputs")]
+    NoInstanceInputs(String),
+
+    #[error("Error while drawing instances => Instance size ({size}) doesn't match the per-instance inputs of pipeline '{pipeline}' ({expected})")]
+    InstanceSizeMismatch {
+        pipeline: String,
+        size: u32,
+        expected: u32,
+    },
+
+    #[error("Error while drawing text => Character '{0}' not found in font atlas")]
+    MissingGlyph(char),
+
+    #[error("Error while accessing buffer => Data size ({size}) is bigger than buffer size ({capacity})")]
+    BufferOverflow { size: u64, capacity: u64 },
+
+    #[error("Error while binding pipeline => The vertex layout {layout} doesn't match the vertex shader of pipeline '{pipeline}': {problems}")]
+    VertexLayoutMismatch {
+        pipeline: String,
+        layout: String,
+        problems: String,
+    },
+
+    #[error(
+        "Error while recording barrier => Unsupported layout transition from {old:?} to {new:?}"
+    )]
+    UnsupportedLayoutTransition {
+        old: vk::ImageLayout,
+        new: vk::ImageLayout,
+    },
+
+    #[error("Error while allocating memory => No suitable memory type found")]
+    NoSuitableMemoryType,
+
+    #[error("Error while loading asset {path:?} => {reason}")]
+    InvalidAsset { path: PathBuf, reason: String },
 }
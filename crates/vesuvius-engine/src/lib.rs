@@ -3,23 +3,31 @@
 
 extern crate core;
 
+// The derive macros refer to the engine by its name, which has to resolve inside of the engine as well
+extern crate self as vesuvius_engine;
+
+pub mod assets;
 #[cfg(feature = "debug_extensions")]
 pub mod debug;
 pub mod device;
 pub mod error;
 pub mod render;
 pub mod screen;
+pub mod validation;
 
-use ash::vk::{CommandBuffer, MemoryHeapFlags, PhysicalDevice};
+use ash::vk::CommandBuffer;
 use ash::{vk, Entry, Instance};
-use device::WrappedDevice;
+use device::selection::{DeviceCandidate, DeviceSelector};
+use device::{selection, WrappedDevice};
 use error::Error;
-use itertools::Itertools;
+use log::{info, warn};
 use raw_window_handle::HasRawDisplayHandle;
 use screen::Screen;
+use std::ffi::c_char;
 use std::mem::ManuallyDrop;
 use std::slice;
 use std::sync::Arc;
+use validation::DebugMessenger;
 use winit::window::Window;
 
 /// Reexport egui if debug extensions enabled
@@ -43,11 +51,15 @@ struct AppInner {
     /// Handle to the Vulkan instance
     instance: Instance,
 
+    /// The messenger, which forwards the messages of the validation layer into the log. This is none when the debug
+    /// utils extension isn't available.
+    debug_messenger: Option<DebugMessenger>,
+
     /// Reference to the main graphics device
     main_device: ManuallyDrop<WrappedDevice>,
 
-    /// The game window itself
-    window: Window,
+    /// The game window itself, this is none when the application runs headless
+    window: Option<Window>,
 
     /// The current screen (game state) of the application
     current_screen: Option<Box<dyn Screen>>,
@@ -60,6 +72,9 @@ impl Drop for AppInner {
     fn drop(&mut self) {
         unsafe {
             ManuallyDrop::drop(&mut self.main_device);
+            if let Some(debug_messenger) = self.debug_messenger.as_ref() {
+                debug_messenger.destroy();
+            }
             self.instance.destroy_instance(None);
         }
     }
@@ -71,52 +86,110 @@ impl Drop for AppInner {
 pub struct App(Arc<AppInner>);
 
 impl App {
-    /// This function creates a new instance of the engine application
+    /// This function creates a new instance of the engine application, which renders into the specified window. The
+    /// device is selected by the `VESUVIUS_DEVICE` environment variable or automatically.
+    #[inline]
     pub fn new(window: Window) -> Result<Self> {
+        Self::create(Some(window), DeviceSelector::from_env())
+    }
+
+    /// This function creates a new instance of the engine application without a window. Renderers of this application
+    /// can only draw into offscreen images (see [GameRenderer::headless](crate::render::GameRenderer::headless)), so
+    /// this works without a display server and on software Vulkan implementations like lavapipe.
+    #[inline]
+    pub fn headless() -> Result<Self> {
+        Self::create(None, DeviceSelector::from_env())
+    }
+
+    /// This function creates a new instance of the engine application with the device chosen by the specified
+    /// selector. If no window is specified, the application runs headless. When the selected device doesn't fulfill
+    /// the requirements of the engine, [Error::NoSuitableDevice] is returned.
+    pub fn create(window: Option<Window>, device_selector: DeviceSelector) -> Result<Self> {
         let entry = unsafe { Entry::load() }?;
 
         // Add validation layer if enabled
-        let mut layers = Vec::new();
-        if let Ok(value) = std::env::var("VALIDATION_LAYER") {
-            if value
-                .parse::<bool>()
-                .expect("Unable to wrap VALIDATE_LAYER env var into boolean")
-            {
-                layers.push(b"VK_LAYER_KHRONOS_validation\0".as_ptr().cast());
+        let mut layers: Vec<*const c_char> = Vec::new();
+        if validation::validation_layer_requested() {
+            if validation::is_layer_available(&entry, validation::VALIDATION_LAYER_NAME)? {
+                layers.push(validation::VALIDATION_LAYER_NAME.as_ptr());
+            } else {
+                warn!("Validation layer requested, but not installed");
             }
         }
 
-        // Create Vulkan instance
-        let extensions = ash_window::enumerate_required_extensions(window.raw_display_handle())?;
+        // Create Vulkan instance, surface extensions are only required when we present into a window. The debug
+        // utils are enabled if available, so messages are logged and objects are named.
+        let mut extensions: Vec<*const c_char> = match window.as_ref() {
+            Some(window) => {
+                ash_window::enumerate_required_extensions(window.raw_display_handle())?.to_vec()
+            }
+            None => Vec::new(),
+        };
+        let debug_utils_available =
+            validation::is_extension_available(&entry, validation::DEBUG_UTILS_EXTENSION_NAME)?;
+        if debug_utils_available {
+            extensions.push(validation::DEBUG_UTILS_EXTENSION_NAME.as_ptr());
+        }
         let application_info = vk::ApplicationInfo::default()
             .api_version(vk::API_VERSION_1_3)
             .engine_version(vk::make_api_version(0, 1, 0, 0));
         let instance_create_info = vk::InstanceCreateInfo::default()
             .application_info(&application_info)
-            .enabled_extension_names(extensions)
+            .enabled_extension_names(extensions.as_slice())
             .enabled_layer_names(layers.as_slice());
         let instance = unsafe { entry.create_instance(&instance_create_info, None) }?;
+        let debug_messenger = if debug_utils_available {
+            match DebugMessenger::new(&entry, &instance) {
+                Ok(debug_messenger) => Some(debug_messenger),
+                Err(error) => {
+                    warn!("Unable to create debug messenger => {}", error);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // Select device, the instance is destroyed if no device can be created
+        let device = selection::enumerate_candidates(&entry, &instance, window.as_ref())
+            .and_then(|candidates| selection::select_device(&candidates, &device_selector))
+            .and_then(|candidate| {
+                info!("Selected device {}", candidate);
+                WrappedDevice::new(
+                    instance.clone(),
+                    candidate.physical_device,
+                    candidate.queue_families,
+                    window.is_some(),
+                    debug_messenger
+                        .as_ref()
+                        .map(|debug_messenger| debug_messenger.debug_utils().clone()),
+                )
+            });
+        let device = match device {
+            Ok(device) => device,
+            Err(error) => {
+                if let Some(debug_messenger) = debug_messenger {
+                    debug_messenger.destroy();
+                }
+                unsafe { instance.destroy_instance(None) };
+                return Err(error);
+            }
+        };
 
-        // Create device and application
+        // Create application
         Ok(Self(Arc::new(AppInner {
-            main_device: ManuallyDrop::new(WrappedDevice::new(
-                instance.clone(),
-                unsafe { instance.enumerate_physical_devices() }?
-                    .into_iter()
-                    .sorted_by(|a, b| {
-                        local_heap_size_of(&instance, a).cmp(&local_heap_size_of(&instance, b))
-                    })
-                    .next()
-                    .unwrap(),
-            )?),
+            main_device: ManuallyDrop::new(device),
             entry,
             instance,
+            debug_messenger,
             window,
             current_screen: None,
         })))
     }
 
-    pub fn open_screen(&mut self, screen: Box<dyn Screen>) {
+    /// This function closes the current screen and opens the specified screen. If the initialization of the new screen
+    /// fails, the error is returned and the screen stays open, so the caller can replace it (e.g. by an error screen).
+    pub fn open_screen(&mut self, screen: Box<dyn Screen>) -> Result<()> {
         let immutable_clone = self.clone();
         let inner_application = unsafe { Arc::get_mut_unchecked(&mut self.0) };
         if let Some(previous_screen) = inner_application.current_screen.as_mut() {
@@ -128,17 +201,86 @@ impl App {
             .current_screen
             .as_mut()
             .unwrap()
-            .init(&immutable_clone);
+            .init(&immutable_clone)
+    }
+
+    /// This function records the operation into a temporary command buffer, submits it to the graphics queue and
+    /// waits until the GPU has finished it. If the operation fails, the command buffer is discarded.
+    #[inline]
+    pub fn upload_single_time_command_buffer<F: FnOnce(CommandBuffer) -> Result<()>>(
+        &self,
+        operation: F,
+    ) -> Result<()> {
+        let device = self.main_device();
+        self.submit_single_time_command_buffer(
+            device.queue_families().graphics,
+            *device.graphics_queue(),
+            None,
+            None,
+            operation,
+        )
     }
 
-    pub fn upload_single_time_command_buffer<F: FnOnce(CommandBuffer)>(
+    /// This function records the upload into a temporary command buffer of the transfer queue and the acquire
+    /// operation into a command buffer of the graphics queue, which waits for the upload. The upload has to release
+    /// the ownership of the resources and the acquire operation has to acquire them (see
+    /// [WrappedDevice::release_image_ownership] and [WrappedDevice::acquire_image_ownership]). On drivers without a
+    /// separate transfer queue family, both operations are recorded into a single command buffer of the graphics
+    /// queue.
+    pub fn upload_with_transfer_queue<
+        U: FnOnce(CommandBuffer) -> Result<()>,
+        A: FnOnce(CommandBuffer) -> Result<()>,
+    >(
         &self,
+        upload: U,
+        acquire: A,
+    ) -> Result<()> {
+        let device = self.main_device();
+        let queue_families = device.queue_families();
+        if !queue_families.has_separate_transfer() {
+            return self.upload_single_time_command_buffer(|command_buffer| {
+                upload(command_buffer)?;
+                acquire(command_buffer)
+            });
+        }
+
+        let vk_device = device.virtual_device();
+        let semaphore =
+            unsafe { vk_device.create_semaphore(&vk::SemaphoreCreateInfo::default(), None) }?;
+        let result = self
+            .submit_single_time_command_buffer(
+                queue_families.transfer,
+                *device.transfer_queue(),
+                None,
+                Some(semaphore),
+                upload,
+            )
+            .and_then(|_| {
+                self.submit_single_time_command_buffer(
+                    queue_families.graphics,
+                    *device.graphics_queue(),
+                    Some(semaphore),
+                    None,
+                    acquire,
+                )
+            });
+        unsafe { vk_device.destroy_semaphore(semaphore, None) };
+        result
+    }
+
+    fn submit_single_time_command_buffer<F: FnOnce(CommandBuffer) -> Result<()>>(
+        &self,
+        queue_family_index: u32,
+        queue: vk::Queue,
+        wait_semaphore: Option<vk::Semaphore>,
+        signal_semaphore: Option<vk::Semaphore>,
         operation: F,
     ) -> Result<()> {
         let device = self.main_device().virtual_device();
 
         // Allocate command buffer
-        let command_pool_create_info = vk::CommandPoolCreateInfo::default().queue_family_index(0);
+        let command_pool_create_info =
+            vk::CommandPoolCreateInfo::default().queue_family_index(queue_family_index);
         let command_pool = unsafe { device.create_command_pool(&command_pool_create_info, None) }?;
         let command_buffer_allocate_info = vk::CommandBufferAllocateInfo::default()
             .command_pool(command_pool)
@@ -149,30 +291,34 @@ impl App {
         // Create fence
         let submit_fence = unsafe { device.create_fence(&vk::FenceCreateInfo::default(), None) }?;
 
-        // Begin and perform operation
-        unsafe {
-            device.begin_command_buffer(command_buffer, &vk::CommandBufferBeginInfo::default())
-        }?;
-        operation(command_buffer);
-
-        // End, submit and free
+        // Begin, perform operation, end and submit
+        let wait_semaphores = wait_semaphore.as_slice();
+        let signal_semaphores = signal_semaphore.as_slice();
+        let wait_stages = [vk::PipelineStageFlags::ALL_COMMANDS];
+        let result = unsafe {
+            device
+                .begin_command_buffer(command_buffer, &vk::CommandBufferBeginInfo::default())
+                .map_err(Error::from)
+                .and_then(|_| operation(command_buffer))
+                .and_then(|_| Ok(device.end_command_buffer(command_buffer)?))
+                .and_then(|_| {
+                    let submit_info = vk::SubmitInfo::default()
+                        .command_buffers(slice::from_ref(&command_buffer))
+                        .wait_semaphores(wait_semaphores)
+                        .wait_dst_stage_mask(&wait_stages[..wait_semaphores.len()])
+                        .signal_semaphores(signal_semaphores);
+                    device.queue_submit(queue, slice::from_ref(&submit_info), submit_fence)?;
+                    Ok(device.wait_for_fences(slice::from_ref(&submit_fence), true, u64::MAX)?)
+                })
+        };
+
+        // Free, this also happens when the operation has failed
         unsafe {
-            device.end_command_buffer(command_buffer)?;
-
-            let submit_info =
-                vk::SubmitInfo::default().command_buffers(slice::from_ref(&command_buffer));
-            device.queue_submit(
-                *self.main_device().queue(),
-                slice::from_ref(&submit_info),
-                submit_fence,
-            )?;
-            device.wait_for_fences(slice::from_ref(&submit_fence), true, u64::MAX)?;
-
             device.destroy_fence(submit_fence, None);
             device.free_command_buffers(command_pool, slice::from_ref(&command_buffer));
             device.destroy_command_pool(command_pool, None);
         }
-        Ok(())
+        result
     }
 
     #[inline]
@@ -203,25 +349,35 @@ impl App {
         &self.0.entry
     }
 
+    /// This function panics with the first validation error of this application since the last check, if the panic on
+    /// validation errors is enabled. Every application has its own messenger, so errors of other applications (e.g.
+    /// tests running in parallel) aren't raised here.
+    #[inline]
+    pub(crate) fn check_validation_errors(&self) {
+        if let Some(debug_messenger) = self.0.debug_messenger.as_ref() {
+            debug_messenger.check_validation_errors();
+        }
+    }
+
+    /// This function enumerates all physical devices with their properties and the requirements, which they don't
+    /// fulfill. The devices are checked against the window of this application, if one exists.
+    pub fn enumerate_devices(&self) -> Result<Vec<DeviceCandidate>> {
+        selection::enumerate_candidates(&self.0.entry, &self.0.instance, self.window())
+    }
+
     #[inline]
     pub fn main_device(&self) -> &WrappedDevice {
         &self.0.main_device
     }
 
+    /// This function returns the game window or none, if the application runs headless
     #[inline]
-    pub fn window(&self) -> &Window {
-        &self.0.window
+    pub fn window(&self) -> Option<&Window> {
+        self.0.window.as_ref()
     }
-}
 
-#[inline]
-fn local_heap_size_of(instance: &Instance, physical_device: &PhysicalDevice) -> u64 {
-    unsafe { instance.get_physical_device_memory_properties(*physical_device) }
-        .memory_heaps
-        .iter()
-        .filter(|heap| {
-            (heap.flags & MemoryHeapFlags::DEVICE_LOCAL) == MemoryHeapFlags::DEVICE_LOCAL
-        })
-        .map(|heap| heap.size)
-        .sum()
+    #[inline]
+    pub fn is_headless(&self) -> bool {
+        self.0.window.is_none()
+    }
 }
@@ -0,0 +1,169 @@
+use crate::App;
+use crate::Result;
+use ash::vk;
+use log::warn;
+use vk_mem_alloc::{Allocation, AllocationCreateInfo, MemoryUsage};
+
+/// The supported depth formats in the order of preference. The Vulkan specification guarantees, that one of the 32-bit
+/// formats is supported, D16 is only a fallback for unusual drivers.
+const DEPTH_FORMATS: [vk::Format; 3] = [
+    vk::Format::D32_SFLOAT,
+    vk::Format::X8_D24_UNORM_PACK32,
+    vk::Format::D16_UNORM,
+];
+
+/// This structure represents an image, which is only used as attachment while rendering. These are the depth images
+/// and the multisampled color images, which are resolved into the images the renderer draws into.
+pub(crate) struct Attachment {
+    pub(crate) image: vk::Image,
+    pub(crate) image_view: vk::ImageView,
+    allocation: Allocation,
+}
+
+impl Attachment {
+    pub(crate) fn new(
+        application: &App,
+        format: vk::Format,
+        extent: vk::Extent2D,
+        samples: vk::SampleCountFlags,
+        usage: vk::ImageUsageFlags,
+        aspect_mask: vk::ImageAspectFlags,
+    ) -> Result<Self> {
+        let main_device = application.main_device();
+        let image_create_info = vk::ImageCreateInfo::default()
+            .image_type(vk::ImageType::TYPE_2D)
+            .extent(vk::Extent3D {
+                width: extent.width,
+                height: extent.height,
+                depth: 1,
+            })
+            .mip_levels(1)
+            .array_layers(1)
+            .format(format)
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .usage(usage)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .samples(samples);
+        let image_alloc_create_info = AllocationCreateInfo {
+            usage: MemoryUsage::AUTO_PREFER_DEVICE,
+            ..Default::default()
+        };
+        let (image, allocation, _) = unsafe {
+            vk_mem_alloc::create_image(
+                *main_device.allocator(),
+                &image_create_info,
+                &image_alloc_create_info,
+            )
+        }?;
+
+        let image_view_create_info = vk::ImageViewCreateInfo::default()
+            .image(image)
+            .view_type(vk::ImageViewType::TYPE_2D)
+            .format(format)
+            .subresource_range(
+                vk::ImageSubresourceRange::default()
+                    .aspect_mask(aspect_mask)
+                    .layer_count(1)
+                    .level_count(1),
+            );
+        let image_view = match unsafe {
+            main_device
+                .virtual_device()
+                .create_image_view(&image_view_create_info, None)
+        } {
+            Ok(image_view) => image_view,
+            Err(error) => {
+                unsafe { vk_mem_alloc::destroy_image(*main_device.allocator(), image, allocation) };
+                return Err(error.into());
+            }
+        };
+
+        Ok(Self {
+            image,
+            image_view,
+            allocation,
+        })
+    }
+
+    /// This function destroys the image and its view. The caller has to ensure, that the device is idle.
+    pub(crate) fn destroy(self, application: &App) {
+        let main_device = application.main_device();
+        unsafe {
+            main_device
+                .virtual_device()
+                .destroy_image_view(self.image_view, None);
+            vk_mem_alloc::destroy_image(*main_device.allocator(), self.image, self.allocation);
+        }
+    }
+}
+
+/// This function returns the first depth format of [DEPTH_FORMATS], which can be used as depth attachment
+pub(crate) fn choose_depth_format(application: &App) -> vk::Format {
+    DEPTH_FORMATS
+        .into_iter()
+        .find(|format| {
+            let format_properties = unsafe {
+                application
+                    .instance()
+                    .get_physical_device_format_properties(
+                        application.main_device().physical_device(),
+                        *format,
+                    )
+            };
+            format_properties
+                .optimal_tiling_features
+                .contains(vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT)
+        })
+        .unwrap_or(vk::Format::D16_UNORM)
+}
+
+/// This function returns the aspects of the depth format. Formats with a stencil component are transitioned and viewed
+/// with both aspects, because the layout `DEPTH_STENCIL_ATTACHMENT_OPTIMAL` covers depth and stencil.
+pub(crate) fn depth_aspect_mask(format: vk::Format) -> vk::ImageAspectFlags {
+    match format {
+        vk::Format::D16_UNORM_S8_UINT
+        | vk::Format::D24_UNORM_S8_UINT
+        | vk::Format::D32_SFLOAT_S8_UINT => {
+            vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL
+        }
+        _ => vk::ImageAspectFlags::DEPTH,
+    }
+}
+
+/// This function returns the highest sample count, which isn't higher than the requested sample count and is supported
+/// by the color and depth attachments of the device.
+pub(crate) fn choose_sample_count(
+    application: &App,
+    requested_samples: vk::SampleCountFlags,
+) -> vk::SampleCountFlags {
+    let limits = unsafe {
+        application
+            .instance()
+            .get_physical_device_properties(application.main_device().physical_device())
+    }
+    .limits;
+    let supported_samples =
+        limits.framebuffer_color_sample_counts & limits.framebuffer_depth_sample_counts;
+
+    let samples = [
+        vk::SampleCountFlags::TYPE_64,
+        vk::SampleCountFlags::TYPE_32,
+        vk::SampleCountFlags::TYPE_16,
+        vk::SampleCountFlags::TYPE_8,
+        vk::SampleCountFlags::TYPE_4,
+        vk::SampleCountFlags::TYPE_2,
+    ]
+    .into_iter()
+    .find(|samples| {
+        samples.as_raw() <= requested_samples.as_raw() && supported_samples.contains(*samples)
+    })
+    .unwrap_or(vk::SampleCountFlags::TYPE_1);
+    if samples != requested_samples {
+        warn!(
+            "{:?} samples are not supported, falling back to {:?} samples",
+            requested_samples, samples
+        );
+    }
+    samples
+}
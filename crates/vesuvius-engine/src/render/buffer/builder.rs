@@ -1,47 +1,26 @@
-use crate::render::buffer::format::{Topology, VertexFormat};
+use crate::render::buffer::format::Topology;
+use crate::render::buffer::layout::{VertexAttribute, VertexLayout, VertexTarget};
 use crate::render::image::Image;
 use crate::render::GameRenderer;
-use glam::{Vec2, Vec3};
+use std::any;
 use std::sync::Arc;
 
-/// This struct describes the data of a single vertex. The vertex contains the position and the color or uv coordinates.
-#[repr(C)]
-#[derive(Clone, Copy, PartialEq, Debug)]
-pub(crate) struct Vertex {
-    pub(crate) position: Vec2,
-    pub(crate) color: Option<Vec3>,
-    pub(crate) uv: Option<Vec2>,
-}
-
 /// This struct represents the buffer builder. The buffer builder allows the renderer to draw batched render calls when
-/// possible or non-batched when needed.
+/// possible or non-batched when needed. The vertices have the layout of the vertex type, which is checked against the
+/// inputs of the vertex shader when the pipeline is bound.
 #[derive(Clone)]
-pub struct BufferBuilder {
-    pub(crate) vertices: Vec<Vertex>,
-    pub(crate) vertex_format: VertexFormat,
-    pub(crate) topology: Topology,
-    current_vertex: Option<Vertex>,
-    pub(crate) image: Option<Image>,
-    pub(crate) pipeline: String,
-}
-
-impl PartialEq for BufferBuilder {
-    #[inline]
-    fn eq(&self, other: &Self) -> bool {
-        self.vertex_format == other.vertex_format
-            && self.topology == other.topology
-            && self.image == other.image
-            && self.pipeline == other.pipeline
-    }
+pub struct BufferBuilder<V: VertexLayout> {
+    vertices: Vec<V>,
+    topology: Topology,
+    image: Option<Image>,
+    pipeline: String,
 }
 
-impl BufferBuilder {
+impl<V: VertexLayout> BufferBuilder<V> {
     #[inline]
-    pub fn builder(vertex_format: VertexFormat, topology: Topology, pipeline: &str) -> Self {
+    pub fn builder(topology: Topology, pipeline: &str) -> Self {
         Self {
             vertices: vec![],
-            current_vertex: None,
-            vertex_format,
             topology,
             image: None,
             pipeline: pipeline.to_string(),
@@ -53,53 +32,58 @@ impl BufferBuilder {
         self
     }
 
-    pub fn begin(&mut self, x: f32, y: f32) -> &mut Self {
-        if let Some(vertex) = self.current_vertex.as_ref() {
-            panic!(
-                "Error while using buffer builder => The previous vertex ({:?}) has not end",
-                vertex
-            );
-        }
-
-        self.current_vertex = Some(Vertex {
-            position: Vec2::new(x, y),
-            color: None,
-            uv: None,
-        });
-        self
-    }
-
-    pub fn color(&mut self, red: f32, green: f32, blue: f32) -> &mut Self {
-        let Some(vertex) = self.current_vertex.as_mut() else {
-            panic!("Error while using buffer builder => No vertex building has begun, use position before this");
-        };
-
-        vertex.color = Some(Vec3::new(red, green, blue));
+    pub fn vertex(&mut self, vertex: V) -> &mut Self {
+        self.vertices.push(vertex);
         self
     }
 
-    pub fn uv(&mut self, u: f32, v: f32) -> &mut Self {
-        let Some(vertex) = self.current_vertex.as_mut() else {
-            panic!("Error while using buffer builder => No vertex building has begun, use position before this");
-        };
+    /// This function prepares the vertices for the render target of the renderer and queues them, so they're drawn by
+    /// [GameRenderer::queue_buffer_builder].
+    pub fn build(self, renderer: &mut GameRenderer) {
+        let target = VertexTarget::new(renderer.0.extent, renderer.0.format);
+        let mut vertices = self.vertices;
+        for vertex in vertices.iter_mut() {
+            vertex.prepare(&target);
+        }
 
-        vertex.uv = Some(Vec2::new(u, v));
-        self
+        unsafe { Arc::get_mut_unchecked(&mut renderer.0) }
+            .queued_buffer_builder
+            .push(QueuedVertices {
+                vertex_data: bytemuck::cast_slice(&vertices).to_vec(),
+                vertex_count: vertices.len(),
+                layout: any::type_name::<V>(),
+                attributes: V::ATTRIBUTES,
+                stride: V::stride(),
+                topology: self.topology,
+                image: self.image,
+                pipeline: self.pipeline,
+            });
     }
+}
 
-    pub fn end(&mut self) {
-        let Some(vertex) = self.current_vertex else {
-            panic!("Error while using buffer builder => No vertex is in building");
-        };
+/// This structure represents the prepared vertices of a built buffer builder. The vertices are stored as bytes, so
+/// buffer builders with different vertex layouts can be queued together.
+#[derive(Clone)]
+pub(crate) struct QueuedVertices {
+    pub(crate) vertex_data: Vec<u8>,
+    pub(crate) vertex_count: usize,
 
-        self.vertices.push(vertex);
-        self.current_vertex = None;
-    }
+    /// The name of the vertex type, which is used in errors
+    pub(crate) layout: &'static str,
+    pub(crate) attributes: &'static [VertexAttribute],
+    pub(crate) stride: u32,
+    pub(crate) topology: Topology,
+    pub(crate) image: Option<Image>,
+    pub(crate) pipeline: String,
+}
 
+impl PartialEq for QueuedVertices {
     #[inline]
-    pub fn build(self, renderer: &mut GameRenderer) {
-        unsafe { Arc::get_mut_unchecked(&mut renderer.0) }
-            .queued_buffer_builder
-            .push(self);
+    fn eq(&self, other: &Self) -> bool {
+        self.attributes == other.attributes
+            && self.stride == other.stride
+            && self.topology == other.topology
+            && self.image == other.image
+            && self.pipeline == other.pipeline
     }
 }
@@ -1,11 +1,13 @@
-use crate::render::buffer::builder::Vertex;
-use glam::{Vec2, Vec3};
-use std::mem;
+use crate::render::buffer::layout::{VertexAttribute, VertexLayout};
+use bytemuck::{Pod, Zeroable};
+use glam::{Vec2, Vec4};
+use serde::{Deserialize, Serialize};
 
-/// This enum represents the format of a single vertex in the buffer. The engine in Vesuvius
-/// implements a few vertex formats like the [VertexFormat::PositionColor] format. The renderer
-/// can use these formats to determine the size of the buffer.
-#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
+/// This enum names the vertex layouts of the engine, so a pipeline configuration can declare the layout of its
+/// vertices with `vertex_format`. The declared layout is checked against the vertex shader while compiling the
+/// pipeline and by the asset validator, other layouts are only checked when the pipeline is bound.
+#[derive(Serialize, Deserialize, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
+#[serde(rename_all = "snake_case")]
 pub enum VertexFormat {
     PositionColor,
     PositionTexCoord,
@@ -13,29 +15,88 @@ pub enum VertexFormat {
 }
 
 impl VertexFormat {
-    /// This function returns the evaluated size of the specified vertex format
-    pub const fn vertex_size(&self) -> usize {
+    /// This function returns the attributes of the vertex layout in the order of their locations
+    pub const fn attributes(&self) -> &'static [VertexAttribute] {
         match self {
-            Self::PositionColor => mem::size_of::<Vec2>() + mem::size_of::<Vec3>(),
-            Self::PositionTexCoord => mem::size_of::<Vec2>() * 2,
-            Self::PositionTexCoordColor => mem::size_of::<Vec2>() * 2 + mem::size_of::<Vec3>(),
+            Self::PositionColor => PositionColor::ATTRIBUTES,
+            Self::PositionTexCoord => PositionTexCoord::ATTRIBUTES,
+            Self::PositionTexCoordColor => PositionTexCoordColor::ATTRIBUTES,
         }
     }
 
-    /// This function converts the specified vertex into the raw byte structure
-    pub(crate) fn extend_raw_data(&self, raw_data: &mut Vec<u8>, vertex: Vertex) {
-        raw_data.extend(unsafe { mem::transmute::<Vec2, [u8; 8]>(vertex.position) });
+    /// This function returns the size of a single vertex of the vertex layout in bytes
+    pub fn stride(&self) -> u32 {
         match self {
-            Self::PositionColor => {
-                raw_data.extend(unsafe { mem::transmute::<Vec3, [u8; 12]>(vertex.color.unwrap()) });
-            }
-            Self::PositionTexCoord => {
-                raw_data.extend(unsafe { mem::transmute::<Vec2, [u8; 8]>(vertex.uv.unwrap()) });
-            }
-            Self::PositionTexCoordColor => {
-                raw_data.extend(unsafe { mem::transmute::<Vec3, [u8; 12]>(vertex.color.unwrap()) });
-                raw_data.extend(unsafe { mem::transmute::<Vec2, [u8; 8]>(vertex.uv.unwrap()) });
-            }
+            Self::PositionColor => PositionColor::stride(),
+            Self::PositionTexCoord => PositionTexCoord::stride(),
+            Self::PositionTexCoordColor => PositionTexCoordColor::stride(),
+        }
+    }
+}
+
+/// This structure represents a vertex with a position in pixels and a color. This is the vertex layout of the
+/// `position_color` pipeline.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Debug, Default, Pod, Zeroable, VertexLayout)]
+pub struct PositionColor {
+    #[vertex(position)]
+    pub position: Vec2,
+
+    /// The color is stored as array, because [Vec4] is aligned to 16 bytes and would leave padding after the position
+    #[vertex(color)]
+    pub color: [f32; 4],
+}
+
+impl PositionColor {
+    #[inline]
+    pub const fn new(x: f32, y: f32, color: Vec4) -> Self {
+        Self {
+            position: Vec2::new(x, y),
+            color: color.to_array(),
+        }
+    }
+}
+
+/// This structure represents a vertex with a position in pixels and a texture coordinate. This is the vertex layout of
+/// the `position_texcoord` pipeline.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Debug, Default, Pod, Zeroable, VertexLayout)]
+pub struct PositionTexCoord {
+    #[vertex(position)]
+    pub position: Vec2,
+    pub uv: Vec2,
+}
+
+impl PositionTexCoord {
+    #[inline]
+    pub const fn new(x: f32, y: f32, u: f32, v: f32) -> Self {
+        Self {
+            position: Vec2::new(x, y),
+            uv: Vec2::new(u, v),
+        }
+    }
+}
+
+/// This structure represents a vertex with a position in pixels, a color and a texture coordinate. This is the vertex
+/// layout of the `msdf_font` pipeline.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Debug, Default, Pod, Zeroable, VertexLayout)]
+pub struct PositionTexCoordColor {
+    #[vertex(position)]
+    pub position: Vec2,
+
+    #[vertex(color)]
+    pub color: [f32; 4],
+    pub uv: Vec2,
+}
+
+impl PositionTexCoordColor {
+    #[inline]
+    pub const fn new(x: f32, y: f32, u: f32, v: f32, color: Vec4) -> Self {
+        Self {
+            position: Vec2::new(x, y),
+            color: color.to_array(),
+            uv: Vec2::new(u, v),
         }
     }
 }
@@ -0,0 +1,87 @@
+use crate::render::buffer::layout::VertexLayout;
+use crate::render::image::Image;
+use bytemuck::{Pod, Zeroable};
+use glam::{Vec2, Vec4};
+
+/// This structure represents a corner of the unit quad, which is the shared geometry of the instances. The corner is
+/// the only per-vertex input of the pipelines drawn with an [InstanceBatch].
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Debug, Default, Pod, Zeroable, VertexLayout)]
+pub struct QuadVertex {
+    pub corner: Vec2,
+}
+
+/// This structure describes a single sprite drawn by the `sprite` pipeline. The sprite is a quad with the specified
+/// position and size in pixels, which shows the specified rectangle of the atlas image multiplied with the color.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Debug, Default, Pod, Zeroable)]
+pub struct SpriteInstance {
+    /// The position of the top left corner in pixels
+    pub position: Vec2,
+
+    /// The size of the sprite in pixels
+    pub scale: Vec2,
+
+    /// The linear color, which is multiplied with the color of the atlas image
+    pub color: Vec4,
+
+    /// The texture coordinates of the top left and the bottom right corner of the sprite in the atlas image
+    pub atlas_rect: Vec4,
+}
+
+/// This structure represents a batch of instances, which are drawn with a single draw call by
+/// [crate::render::GameRenderer::draw_instances]. Every instance is a quad, whose per-instance attributes are read
+/// from the instances of the batch. The pipeline has to declare the per-instance inputs with `instance_location`.
+#[derive(Clone)]
+pub struct InstanceBatch<T: Pod> {
+    pub(crate) instances: Vec<T>,
+    pub(crate) image: Option<Image>,
+    pub(crate) push_constants: Option<Vec<u8>>,
+    pub(crate) pipeline: String,
+}
+
+impl<T: Pod> InstanceBatch<T> {
+    pub fn new(pipeline: &str) -> Self {
+        Self {
+            instances: Vec::new(),
+            image: None,
+            push_constants: None,
+            pipeline: pipeline.to_string(),
+        }
+    }
+
+    /// This function sets the image, which is bound to the first binding of the first descriptor set
+    pub fn image(&mut self, image: &Image) -> &mut Self {
+        self.image = Some(image.clone());
+        self
+    }
+
+    /// This function sets the data, which is written into the push constants of the pipeline before drawing. The size
+    /// of the data has to match the size of the push constant range, the `sprite` pipeline expects the size of the
+    /// render target in pixels as `[f32; 2]`.
+    pub fn push_constants<P: Pod>(&mut self, data: &P) -> &mut Self {
+        self.push_constants = Some(bytemuck::bytes_of(data).to_vec());
+        self
+    }
+
+    pub fn push(&mut self, instance: T) -> &mut Self {
+        self.instances.push(instance);
+        self
+    }
+
+    /// This function removes all instances, so the batch can be filled again for the next frame
+    #[inline]
+    pub fn clear(&mut self) {
+        self.instances.clear();
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.instances.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+    }
+}
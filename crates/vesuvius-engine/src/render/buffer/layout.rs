@@ -0,0 +1,133 @@
+use crate::render::color;
+use ash::vk;
+use bytemuck::Pod;
+use glam::{IVec2, IVec3, IVec4, UVec2, UVec3, UVec4, Vec2, Vec3, Vec4};
+use std::mem;
+
+pub use vesuvius_derive::VertexLayout;
+
+/// This structure describes a single attribute of a vertex layout. The location of the attribute in the vertex shader
+/// is the index of the attribute in the layout.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct VertexAttribute {
+    pub name: &'static str,
+    pub format: vk::Format,
+
+    /// The offset of the attribute in the vertex in bytes
+    pub offset: u32,
+}
+
+/// This trait describes the memory layout of a vertex type, which is drawn by the buffer builder. The trait is
+/// implemented by `#[derive(VertexLayout)]` for `#[repr(C)]` structs, the layout is checked against the inputs of the
+/// vertex shader when the pipeline is bound.
+pub trait VertexLayout: Pod {
+    /// The attributes of the vertex in the order of their locations
+    const ATTRIBUTES: &'static [VertexAttribute];
+
+    /// This function converts the vertex for the render target, before it's written into the vertex buffer. The derive
+    /// macro converts the fields marked with `#[vertex(position)]` and `#[vertex(color)]`.
+    #[inline]
+    fn prepare(&mut self, _target: &VertexTarget) {}
+
+    /// This function returns the size of a single vertex in the vertex buffer
+    #[inline]
+    fn stride() -> u32 {
+        mem::size_of::<Self>() as u32
+    }
+}
+
+/// This trait maps the type of a vertex attribute to the format of the attribute in the vertex buffer
+pub trait AttributeFormat {
+    const FORMAT: vk::Format;
+}
+
+macro_rules! attribute_formats {
+    ($($ty:ty => $format:ident),* $(,)?) => {
+        $(
+            impl AttributeFormat for $ty {
+                const FORMAT: vk::Format = vk::Format::$format;
+            }
+        )*
+    };
+}
+
+attribute_formats! {
+    f32 => R32_SFLOAT,
+    [f32; 2] => R32G32_SFLOAT,
+    [f32; 3] => R32G32B32_SFLOAT,
+    [f32; 4] => R32G32B32A32_SFLOAT,
+    Vec2 => R32G32_SFLOAT,
+    Vec3 => R32G32B32_SFLOAT,
+    Vec4 => R32G32B32A32_SFLOAT,
+    u32 => R32_UINT,
+    [u32; 2] => R32G32_UINT,
+    [u32; 3] => R32G32B32_UINT,
+    [u32; 4] => R32G32B32A32_UINT,
+    UVec2 => R32G32_UINT,
+    UVec3 => R32G32B32_UINT,
+    UVec4 => R32G32B32A32_UINT,
+    i32 => R32_SINT,
+    [i32; 2] => R32G32_SINT,
+    [i32; 3] => R32G32B32_SINT,
+    [i32; 4] => R32G32B32A32_SINT,
+    IVec2 => R32G32_SINT,
+    IVec3 => R32G32B32_SINT,
+    IVec4 => R32G32B32A32_SINT,
+}
+
+/// This structure describes the render target, which the vertices of the buffer builders are prepared for
+pub struct VertexTarget {
+    size: Vec2,
+    srgb: bool,
+}
+
+impl VertexTarget {
+    pub(crate) fn new(extent: vk::Extent2D, format: vk::Format) -> Self {
+        Self {
+            size: Vec2::new(extent.width as f32, extent.height as f32),
+            srgb: color::is_srgb_format(format),
+        }
+    }
+
+    /// This function converts the position in pixels into normalized device coordinates
+    #[inline]
+    pub fn position(&self, position: Vec2) -> Vec2 {
+        ((position * 2.0) - self.size) / self.size
+    }
+
+    /// This function converts the sRGB encoded color into linear space, if the render target expects linear colors
+    #[inline]
+    pub fn color<C: VertexColor>(&self, color: C) -> C {
+        match self.srgb {
+            true => color.to_linear(),
+            false => color,
+        }
+    }
+}
+
+/// This trait is implemented by the vertex colors, which can be converted from sRGB into linear space
+pub trait VertexColor {
+    fn to_linear(self) -> Self;
+}
+
+impl VertexColor for Vec3 {
+    #[inline]
+    fn to_linear(self) -> Self {
+        color::srgb_to_linear(self)
+    }
+}
+
+impl VertexColor for Vec4 {
+    /// The alpha isn't encoded with the sRGB transfer function, so it's kept as it is
+    #[inline]
+    fn to_linear(self) -> Self {
+        color::srgb_to_linear(self.truncate()).extend(self.w)
+    }
+}
+
+impl VertexColor for [f32; 4] {
+    #[inline]
+    fn to_linear(self) -> Self {
+        Vec4::from_array(self).to_linear().to_array()
+    }
+}
@@ -1,12 +1,15 @@
+use crate::error::Error;
 use crate::App;
 use crate::Result;
 use ash::vk;
 
-use std::mem;
+use std::{mem, slice};
 use vk_mem_alloc::{Allocation, AllocationCreateFlags, AllocationCreateInfo, AllocationInfo};
 
 pub mod builder;
 pub mod format;
+pub mod instance;
+pub mod layout;
 
 /// This structure represents an allocated buffer with device memory. This struct contains a device, the buffer handle
 /// itself, the allocation handle and the info about the allocation and allows a simple write function to write
@@ -72,15 +75,99 @@ impl Buffer {
         })
     }
 
+    /// This function creates a buffer in device-local memory and uploads the specified data with a staging buffer. The
+    /// copy runs on the transfer queue, if the device has a separate transfer queue family. The memory of this buffer
+    /// isn't mapped, so it can't be written by [Buffer::write] after the creation.
+    pub fn new_device_local<T: Copy>(
+        app: App,
+        usage: vk::BufferUsageFlags,
+        data: &[T],
+    ) -> Result<Self> {
+        let size = mem::size_of_val(data) as vk::DeviceSize;
+        let staging_buffer = Self::new(
+            app.clone(),
+            vk::BufferUsageFlags::TRANSFER_SRC,
+            size,
+            Some(
+                AllocationCreateFlags::HOST_ACCESS_SEQUENTIAL_WRITE | AllocationCreateFlags::MAPPED,
+            ),
+        )?;
+        staging_buffer.write_ptr(data.as_ptr(), data.len())?;
+        staging_buffer.set_name("Staging Buffer");
+
+        // Create device-local buffer
+        let buffer_create_info = vk::BufferCreateInfo {
+            usage: usage | vk::BufferUsageFlags::TRANSFER_DST,
+            size,
+            ..Default::default()
+        };
+        let alloc_create_info = AllocationCreateInfo {
+            usage: vk_mem_alloc::MemoryUsage::AUTO_PREFER_DEVICE,
+            ..Default::default()
+        };
+        let (buffer, alloc, alloc_info) = unsafe {
+            vk_mem_alloc::create_buffer(
+                *app.main_device().allocator(),
+                &buffer_create_info,
+                &alloc_create_info,
+            )
+        }?;
+        let device_local_buffer = Self {
+            app: app.clone(),
+            buffer,
+            alloc,
+            alloc_info,
+            size,
+        };
+
+        // Copy the staging buffer and transfer the ownership to the graphics queue
+        let device = app.main_device();
+        let (dst_access_mask, dst_stage_mask) = access_of_usage(usage);
+        app.upload_with_transfer_queue(
+            |command_buffer| unsafe {
+                let buffer_copy = vk::BufferCopy::default().size(size);
+                device.virtual_device().cmd_copy_buffer(
+                    command_buffer,
+                    staging_buffer.buffer,
+                    buffer,
+                    slice::from_ref(&buffer_copy),
+                );
+                device.release_buffer_ownership(
+                    command_buffer,
+                    buffer,
+                    dst_access_mask,
+                    dst_stage_mask,
+                );
+                Ok(())
+            },
+            |command_buffer| {
+                device.acquire_buffer_ownership(
+                    command_buffer,
+                    buffer,
+                    dst_access_mask,
+                    dst_stage_mask,
+                );
+                Ok(())
+            },
+        )?;
+        Ok(device_local_buffer)
+    }
+
+    /// This function names the buffer by its purpose, so validation messages reference the buffer by name
+    #[inline]
+    pub fn set_name(&self, name: &str) {
+        self.app.main_device().set_object_name(self.buffer, name);
+    }
+
     /// This function allows to write arbitrary data into the buffer's memory. The input data can't be bigger than the
     /// size, specified in th allocation info.
     pub fn write<T>(&self, data: T) -> Result<()> {
         let input_size = mem::size_of::<T>() as u64;
         if self.size < input_size {
-            panic!(
-                "Error while writing buffer => Input Size ({}) is bigger than Buffer Size ({})",
-                input_size, self.size
-            );
+            return Err(Error::BufferOverflow {
+                size: input_size,
+                capacity: self.size,
+            });
         }
 
         unsafe {
@@ -89,18 +176,106 @@ impl Buffer {
         Ok(())
     }
 
-    pub(crate) fn write_ptr<T>(&self, data: *const T, count: usize) -> Result<()> {
-        let input_size = mem::size_of::<T>() * count;
-        if self.size < input_size as u64 {
-            panic!(
-                "Error while writing buffer => Input Size ({}) is bigger than Buffer Size ({})",
-                input_size, self.size
+    /// This function copies the specified count of elements from the buffer's memory into a vector. The memory is
+    /// invalidated before, so writes by the GPU are visible on the host.
+    pub(crate) fn read<T: Copy>(&self, count: usize) -> Result<Vec<T>> {
+        let output_size = mem::size_of::<T>() * count;
+        if self.size < output_size as u64 {
+            return Err(Error::BufferOverflow {
+                size: output_size as u64,
+                capacity: self.size,
+            });
+        }
+
+        unsafe {
+            vk_mem_alloc::invalidate_allocation(
+                *self.app.main_device().allocator(),
+                self.alloc,
+                0,
+                vk::WHOLE_SIZE,
+            )?;
+        }
+
+        let mut data = Vec::with_capacity(count);
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                self.alloc_info.mapped_data.cast::<T>(),
+                data.as_mut_ptr(),
+                count,
             );
+            data.set_len(count);
+        }
+        Ok(data)
+    }
+
+    #[inline]
+    pub(crate) fn write_ptr<T>(&self, data: *const T, count: usize) -> Result<()> {
+        self.write_ptr_at(0, data, count)
+    }
+
+    /// This function copies the specified count of elements into the buffer's memory at the specified offset in bytes
+    pub(crate) fn write_ptr_at<T>(
+        &self,
+        offset: vk::DeviceSize,
+        data: *const T,
+        count: usize,
+    ) -> Result<()> {
+        let input_size = offset + (mem::size_of::<T>() * count) as u64;
+        if self.size < input_size {
+            return Err(Error::BufferOverflow {
+                size: input_size,
+                capacity: self.size,
+            });
         }
 
         unsafe {
-            std::ptr::copy_nonoverlapping(data, self.alloc_info.mapped_data.cast(), count);
+            std::ptr::copy_nonoverlapping(
+                data.cast::<u8>(),
+                self.alloc_info
+                    .mapped_data
+                    .cast::<u8>()
+                    .add(offset as usize),
+                mem::size_of::<T>() * count,
+            );
         }
         Ok(())
     }
 }
+
+/// This function returns the access and stages, in which a buffer with the specified usage is read by the graphics
+/// queue
+fn access_of_usage(usage: vk::BufferUsageFlags) -> (vk::AccessFlags, vk::PipelineStageFlags) {
+    let mut access_mask = vk::AccessFlags::empty();
+    let mut stage_mask = vk::PipelineStageFlags::empty();
+    if usage.contains(vk::BufferUsageFlags::VERTEX_BUFFER) {
+        access_mask |= vk::AccessFlags::VERTEX_ATTRIBUTE_READ;
+        stage_mask |= vk::PipelineStageFlags::VERTEX_INPUT;
+    }
+    if usage.contains(vk::BufferUsageFlags::INDEX_BUFFER) {
+        access_mask |= vk::AccessFlags::INDEX_READ;
+        stage_mask |= vk::PipelineStageFlags::VERTEX_INPUT;
+    }
+    if usage.contains(vk::BufferUsageFlags::UNIFORM_BUFFER) {
+        access_mask |= vk::AccessFlags::UNIFORM_READ;
+        stage_mask |= vk::PipelineStageFlags::VERTEX_SHADER
+            | vk::PipelineStageFlags::FRAGMENT_SHADER
+            | vk::PipelineStageFlags::COMPUTE_SHADER;
+    }
+    if usage.contains(vk::BufferUsageFlags::STORAGE_BUFFER) {
+        // Storage buffers can also be written by compute shaders after the upload
+        access_mask |= vk::AccessFlags::SHADER_READ | vk::AccessFlags::SHADER_WRITE;
+        stage_mask |= vk::PipelineStageFlags::VERTEX_SHADER
+            | vk::PipelineStageFlags::FRAGMENT_SHADER
+            | vk::PipelineStageFlags::COMPUTE_SHADER;
+    }
+
+    // Fallback for other usages, e.g. transfer sources
+    if stage_mask.is_empty() {
+        (
+            vk::AccessFlags::MEMORY_READ,
+            vk::PipelineStageFlags::ALL_COMMANDS,
+        )
+    } else {
+        (access_mask, stage_mask)
+    }
+}
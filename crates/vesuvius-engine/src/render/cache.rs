@@ -0,0 +1,239 @@
+use crate::render::pipeline::config::Defines;
+use crate::render::pipeline::shader::ShaderKind;
+use crate::App;
+use crate::Result;
+use ash::vk;
+use log::{info, warn};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::{fs, io, process};
+
+/// The directory, which contains the compiled shaders and the pipeline cache
+const CACHE_DIRECTORY: &str = "cache";
+
+/// This version is part of every shader cache key. Increase it when the compile options of the shaders change, so the
+/// shaders compiled with the old options are ignored.
+const SHADER_CACHE_VERSION: u32 = 1;
+
+/// The versions of shaderc and the bundled glslang, which are written by the build script. Shaders compiled by another
+/// version of the compiler are ignored.
+#[cfg(feature = "shaderc")]
+const SHADER_COMPILER_VERSION: &str = env!("VESUVIUS_SHADER_COMPILER_VERSION");
+
+/// The magic number at the begin of every SPIR-V binary
+const SPIRV_MAGIC: u32 = 0x0723_0203;
+
+/// The count of temporary files written by this process, which makes the names of the temporary files unique
+static TEMPORARY_FILE_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// The magic bytes at the begin of the pipeline cache file
+const PIPELINE_CACHE_MAGIC: &[u8; 4] = b"VSPC";
+
+/// This structure implements the 64-bit FNV-1a hash. The hasher of the standard library isn't guaranteed to be stable
+/// between Rust versions, so it's not suitable for keys of persistent caches.
+struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
+/// This function returns the key of a shader in the shader cache. The key covers the path and source code, the kind and
+/// entry point of the shader, the defined macros and the version of the compiler. The path is part of the key, because
+/// includes are resolved relative to the shader. The included files aren't known before the compilation, so they are
+/// validated while loading the shader.
+pub(crate) fn shader_cache_key(
+    source_path: &Path,
+    source: &str,
+    kind: ShaderKind,
+    entry_point: &str,
+    defines: &Defines,
+) -> u64 {
+    let mut hasher = FnvHasher::default();
+    SHADER_CACHE_VERSION.hash(&mut hasher);
+    #[cfg(feature = "shaderc")]
+    SHADER_COMPILER_VERSION.hash(&mut hasher);
+    source_path.to_string_lossy().hash(&mut hasher);
+    kind.hash(&mut hasher);
+    entry_point.hash(&mut hasher);
+    defines.hash(&mut hasher);
+    source.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// This function returns the hash of the file content or none, if the file can't be read
+fn file_hash(path: &Path) -> Option<u64> {
+    let mut hasher = FnvHasher::default();
+    hasher.write(&fs::read(path).ok()?);
+    Some(hasher.finish())
+}
+
+fn shader_cache_path(key: u64, extension: &str) -> PathBuf {
+    Path::new(CACHE_DIRECTORY)
+        .join("shaders")
+        .join(format!("{:016x}.{}", key, extension))
+}
+
+/// This structure represents a compiled shader in the shader cache
+pub(crate) struct CachedShader {
+    pub(crate) ir_code: Vec<u8>,
+    pub(crate) dependencies: Vec<PathBuf>,
+}
+
+/// This function returns the SPIR-V code of the shader with the specified key, if it's in the shader cache. The shader
+/// is only returned, if none of the included files has changed since the compilation.
+pub(crate) fn load_shader(key: u64) -> Option<CachedShader> {
+    // Every line of the dependency file contains the hash and the path of an included file
+    let mut dependencies = Vec::new();
+    for line in fs::read_to_string(shader_cache_path(key, "deps"))
+        .ok()?
+        .lines()
+    {
+        let (hash, path) = line.split_once(' ')?;
+        let path = PathBuf::from(path);
+        if file_hash(&path) != u64::from_str_radix(hash, 16).ok() {
+            return None;
+        }
+        dependencies.push(path);
+    }
+
+    // SPIR-V consists of 32-bit words and starts with the magic number, everything else is a damaged cache entry
+    let ir_code = fs::read(shader_cache_path(key, "spv")).ok()?;
+    let valid = ir_code.len() % 4 == 0 && ir_code.starts_with(&SPIRV_MAGIC.to_le_bytes());
+    valid.then_some(CachedShader {
+        ir_code,
+        dependencies,
+    })
+}
+
+/// This function writes the SPIR-V code of the shader with the specified key and the hashes of the included files into
+/// the shader cache. The cache is only an optimization, so errors are logged instead of returned.
+pub(crate) fn store_shader(key: u64, ir_code: &[u8], dependencies: &[PathBuf]) {
+    let mut dependency_file = String::new();
+    for dependency in dependencies {
+        let Some(hash) = file_hash(dependency) else {
+            return;
+        };
+        dependency_file.push_str(&format!("{:016x} {}\n", hash, dependency.display()));
+    }
+
+    for (path, content) in [
+        (shader_cache_path(key, "deps"), dependency_file.as_bytes()),
+        (shader_cache_path(key, "spv"), ir_code),
+    ] {
+        if let Err(error) = write_file(&path, content) {
+            warn!("Unable to write shader cache {:?} => {}", path, error);
+            return;
+        }
+    }
+}
+
+fn pipeline_cache_path() -> PathBuf {
+    Path::new(CACHE_DIRECTORY).join("pipelines.bin")
+}
+
+/// This function returns the header of the pipeline cache file. The header identifies the device and the driver, which
+/// have written the pipeline cache, because the cache data is only valid for them.
+fn pipeline_cache_header(application: &App) -> Vec<u8> {
+    let properties = unsafe {
+        application
+            .instance()
+            .get_physical_device_properties(application.main_device().physical_device())
+    };
+
+    let mut header = PIPELINE_CACHE_MAGIC.to_vec();
+    for value in [
+        properties.vendor_id,
+        properties.device_id,
+        properties.driver_version,
+    ] {
+        header.extend_from_slice(&value.to_le_bytes());
+    }
+    header.extend_from_slice(&properties.pipeline_cache_uuid);
+    header
+}
+
+/// This function creates the pipeline cache and fills it with the data of the previous run. The data is only used, if
+/// it was written by the same device and driver. Otherwise an empty pipeline cache is created.
+pub(crate) fn create_pipeline_cache(application: &App) -> Result<vk::PipelineCache> {
+    let header = pipeline_cache_header(application);
+    let initial_data = match fs::read(pipeline_cache_path()) {
+        Ok(content) if content.starts_with(&header) => content[header.len()..].to_vec(),
+        Ok(_) => {
+            info!("Ignore pipeline cache, because it was written by another device or driver");
+            Vec::new()
+        }
+        Err(_) => Vec::new(),
+    };
+
+    let device = application.main_device().virtual_device();
+    let pipeline_cache_create_info =
+        vk::PipelineCacheCreateInfo::default().initial_data(&initial_data);
+    match unsafe { device.create_pipeline_cache(&pipeline_cache_create_info, None) } {
+        Ok(pipeline_cache) => Ok(pipeline_cache),
+
+        // Drivers can reject damaged cache data, so an empty pipeline cache is created instead
+        Err(error) if !initial_data.is_empty() => {
+            warn!(
+                "Ignore pipeline cache, because the driver rejected it => {}",
+                error
+            );
+            Ok(unsafe {
+                device.create_pipeline_cache(&vk::PipelineCacheCreateInfo::default(), None)
+            }?)
+        }
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// This function writes the data of the pipeline cache into the cache directory, so the next run can reuse it
+pub(crate) fn store_pipeline_cache(
+    application: &App,
+    pipeline_cache: vk::PipelineCache,
+) -> Result<()> {
+    let data = unsafe {
+        application
+            .main_device()
+            .virtual_device()
+            .get_pipeline_cache_data(pipeline_cache)
+    }?;
+
+    let mut content = pipeline_cache_header(application);
+    content.extend_from_slice(&data);
+    write_file(&pipeline_cache_path(), &content)?;
+    Ok(())
+}
+
+/// This function writes the content into a temporary file and renames it afterwards, so an interrupted write doesn't
+/// leave a damaged cache file. Every write uses its own temporary file, so renderers writing the same cache entry at
+/// the same time don't move each other's partially written files into place.
+fn write_file(path: &Path, content: &[u8]) -> io::Result<()> {
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+
+    let mut temporary_name = path.file_name().unwrap_or_default().to_os_string();
+    temporary_name.push(format!(
+        ".{}-{}.tmp",
+        process::id(),
+        TEMPORARY_FILE_COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+    let temporary_path = path.with_file_name(temporary_name);
+    fs::write(&temporary_path, content)?;
+    fs::rename(temporary_path, path)
+}
@@ -0,0 +1,144 @@
+use ash::vk;
+use glam::Vec3;
+
+/// This function converts a single sRGB encoded color component into linear space with the exact piecewise sRGB
+/// transfer function.
+#[inline]
+pub fn srgb_component_to_linear(component: f32) -> f32 {
+    if component <= 0.04045 {
+        component / 12.92
+    } else {
+        ((component + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// This function converts a sRGB encoded color into linear space. Colors picked by artists are sRGB encoded, but the
+/// blending and the sRGB render targets expect linear values.
+#[inline]
+pub fn srgb_to_linear(color: Vec3) -> Vec3 {
+    Vec3::new(
+        srgb_component_to_linear(color.x),
+        srgb_component_to_linear(color.y),
+        srgb_component_to_linear(color.z),
+    )
+}
+
+/// This function returns true, if the specified format encodes the values with the sRGB transfer function. The
+/// hardware converts linear values into sRGB when writing into an image of these formats.
+#[inline]
+pub(crate) fn is_srgb_format(format: vk::Format) -> bool {
+    matches!(
+        format,
+        vk::Format::B8G8R8A8_SRGB
+            | vk::Format::R8G8B8A8_SRGB
+            | vk::Format::A8B8G8R8_SRGB_PACK32
+            | vk::Format::R8G8B8_SRGB
+            | vk::Format::B8G8R8_SRGB
+    )
+}
+
+/// This function returns the size of a pixel in bytes, if the pixels of the specified format can be converted into 8-bit
+/// RGBA by [to_rgba8].
+#[inline]
+pub(crate) const fn rgba8_pixel_size(format: vk::Format) -> Option<usize> {
+    match format {
+        vk::Format::R8G8B8A8_UNORM
+        | vk::Format::R8G8B8A8_SRGB
+        | vk::Format::B8G8R8A8_UNORM
+        | vk::Format::B8G8R8A8_SRGB
+        | vk::Format::A2B10G10R10_UNORM_PACK32
+        | vk::Format::A2R10G10B10_UNORM_PACK32 => Some(4),
+        vk::Format::R16G16B16A16_SFLOAT => Some(8),
+        _ => None,
+    }
+}
+
+/// This function converts the pixels of the specified format into 8-bit RGBA. The values are only rescaled, so the
+/// pixels keep the encoding of the render target. None is returned for the formats, which aren't supported by
+/// [rgba8_pixel_size].
+pub(crate) fn to_rgba8(format: vk::Format, pixels: &[u8]) -> Option<Vec<u8>> {
+    let pixel_size = rgba8_pixel_size(format)?;
+    let mut rgba = Vec::with_capacity(pixels.len() / pixel_size * 4);
+    for pixel in pixels.chunks_exact(pixel_size) {
+        match format {
+            vk::Format::B8G8R8A8_UNORM | vk::Format::B8G8R8A8_SRGB => {
+                rgba.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]])
+            }
+            vk::Format::A2B10G10R10_UNORM_PACK32 | vk::Format::A2R10G10B10_UNORM_PACK32 => {
+                let value = u32::from_le_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]);
+                let component =
+                    |shift: u32| ((((value >> shift) & 0x3FF) * 255 + 511) / 1023) as u8;
+                let (red, blue) = match format {
+                    vk::Format::A2B10G10R10_UNORM_PACK32 => (component(0), component(20)),
+                    _ => (component(20), component(0)),
+                };
+                rgba.extend_from_slice(&[red, component(10), blue, ((value >> 30) * 85) as u8]);
+            }
+            vk::Format::R16G16B16A16_SFLOAT => rgba.extend(pixel.chunks_exact(2).map(|half| {
+                let value = half_to_f32(u16::from_le_bytes([half[0], half[1]]));
+                (value.clamp(0.0, 1.0) * 255.0).round() as u8
+            })),
+            _ => rgba.extend_from_slice(pixel),
+        }
+    }
+    Some(rgba)
+}
+
+/// This function converts a 16-bit float into a 32-bit float
+fn half_to_f32(half: u16) -> f32 {
+    let sign = if half & 0x8000 == 0 { 1.0 } else { -1.0 };
+    let exponent = ((half >> 10) & 0x1F) as i32;
+    let mantissa = (half & 0x3FF) as f32;
+    sign * match exponent {
+        0 => mantissa * 2.0f32.powi(-24),
+        0x1F if mantissa == 0.0 => f32::INFINITY,
+        0x1F => f32::NAN,
+        _ => (1.0 + mantissa / 1024.0) * 2.0f32.powi(exponent - 15),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bgra_to_rgba8() {
+        assert_eq!(
+            to_rgba8(vk::Format::B8G8R8A8_SRGB, &[1, 2, 3, 4, 5, 6, 7, 8]),
+            Some(vec![3, 2, 1, 4, 7, 6, 5, 8])
+        );
+    }
+
+    #[test]
+    fn packed_10_bit_to_rgba8() {
+        // Red is full, green is half and blue is zero with an opaque alpha
+        let pixel = (3u32 << 30) | (512 << 10) | 1023;
+        assert_eq!(
+            to_rgba8(vk::Format::A2B10G10R10_UNORM_PACK32, &pixel.to_le_bytes()),
+            Some(vec![255, 128, 0, 255])
+        );
+        assert_eq!(
+            to_rgba8(vk::Format::A2R10G10B10_UNORM_PACK32, &pixel.to_le_bytes()),
+            Some(vec![0, 128, 255, 255])
+        );
+    }
+
+    #[test]
+    fn half_float_to_rgba8() {
+        // 1.0, 0.5, -1.0 and 2.0 as 16-bit floats, the values are clamped into the range of the image
+        let pixel = [0x3C00u16, 0x3800, 0xBC00, 0x4000]
+            .iter()
+            .flat_map(|half| half.to_le_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            to_rgba8(vk::Format::R16G16B16A16_SFLOAT, &pixel),
+            Some(vec![255, 128, 0, 255])
+        );
+    }
+
+    #[test]
+    fn unsupported_format() {
+        assert_eq!(rgba8_pixel_size(vk::Format::R32G32B32A32_SFLOAT), None);
+        assert_eq!(to_rgba8(vk::Format::R32G32B32A32_SFLOAT, &[0; 16]), None);
+    }
+}
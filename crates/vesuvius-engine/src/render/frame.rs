@@ -0,0 +1,88 @@
+use crate::render::buffer::Buffer;
+use crate::render::pipeline::DescriptorSet;
+use crate::Result;
+use ash::{vk, Device};
+use std::slice;
+
+/// This structure represents the resources of a single frame in flight. Every frame owns its own command pool and
+/// buffer, the semaphore for the image acquisition and a fence, which signals when the GPU has finished the
+/// frame. Resources used by the frame are stored in the frame's caches and retired when the fence signals.
+pub(crate) struct Frame {
+    pub(crate) command_pool: vk::CommandPool,
+    pub(crate) command_buffer: vk::CommandBuffer,
+
+    /// This semaphore is signaled by the swapchain when the acquired image is ready to be rendered into
+    pub(crate) acquire_semaphore: vk::Semaphore,
+
+    /// This field is true, when the acquire semaphore is signaled but not waited on by a submission yet
+    pub(crate) acquire_pending: bool,
+
+    /// This fence is signaled when the GPU has finished all commands of this frame
+    pub(crate) in_flight_fence: vk::Fence,
+
+    /// Buffers and descriptor sets used by the frame, these are retired after the fence has signaled
+    pub(crate) buffer_cache: Vec<Buffer>,
+    pub(crate) descriptor_set_cache: Vec<DescriptorSet>,
+
+    /// The buffer with the instances drawn in this frame, which is reused by the next submissions of this frame and
+    /// replaced by a larger buffer, if the instances don't fit. The offset is the end of the instances written so far.
+    pub(crate) instance_buffer: Option<Buffer>,
+    pub(crate) instance_offset: vk::DeviceSize,
+}
+
+impl Frame {
+    pub(crate) fn new(device: &Device, queue_family_index: u32) -> Result<Self> {
+        let command_pool_create_info = vk::CommandPoolCreateInfo::default()
+            .flags(vk::CommandPoolCreateFlags::RESET_COMMAND_BUFFER) // Reset at begin
+            .queue_family_index(queue_family_index);
+        let command_pool = unsafe { device.create_command_pool(&command_pool_create_info, None) }?;
+
+        let command_buffer_alloc_info = vk::CommandBufferAllocateInfo::default()
+            .command_pool(command_pool)
+            .command_buffer_count(1);
+        let command_buffer =
+            unsafe { device.allocate_command_buffers(&command_buffer_alloc_info) }?[0];
+
+        // The fence is created signaled, so the first wait on this frame doesn't block forever
+        let fence_create_info =
+            vk::FenceCreateInfo::default().flags(vk::FenceCreateFlags::SIGNALED);
+        Ok(Self {
+            command_pool,
+            command_buffer,
+            acquire_semaphore: unsafe {
+                device.create_semaphore(&vk::SemaphoreCreateInfo::default(), None)
+            }?,
+            in_flight_fence: unsafe { device.create_fence(&fence_create_info, None) }?,
+            acquire_pending: false,
+            buffer_cache: Vec::new(),
+            descriptor_set_cache: Vec::new(),
+            instance_buffer: None,
+            instance_offset: 0,
+        })
+    }
+
+    /// This function waits until the GPU has finished the previous submission of this frame and retires all resources,
+    /// which were used by that submission.
+    pub(crate) fn wait_and_retire(&mut self, device: &Device) -> Result<()> {
+        unsafe {
+            device.wait_for_fences(slice::from_ref(&self.in_flight_fence), true, u64::MAX)?;
+        }
+        self.buffer_cache.clear();
+        self.descriptor_set_cache.clear();
+        self.instance_offset = 0;
+        Ok(())
+    }
+
+    /// This function destroys all Vulkan handles of this frame. The caller has to ensure, that the device is idle.
+    pub(crate) fn destroy(&mut self, device: &Device) {
+        self.buffer_cache.clear();
+        self.descriptor_set_cache.clear();
+        self.instance_buffer = None;
+        unsafe {
+            device.destroy_fence(self.in_flight_fence, None);
+            device.destroy_semaphore(self.acquire_semaphore, None);
+            device.free_command_buffers(self.command_pool, slice::from_ref(&self.command_buffer));
+            device.destroy_command_pool(self.command_pool, None);
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::error::Error;
 use crate::render::buffer::Buffer;
 use crate::{App, Result};
 use ash::vk;
@@ -13,6 +14,9 @@ pub struct ImageInner {
     image_alloc: Allocation,
     pub(crate) image_view: vk::ImageView,
     pub(crate) sampler: vk::Sampler,
+
+    /// The layout of the image, while it's accessed by shaders
+    pub(crate) layout: vk::ImageLayout,
 }
 
 impl Drop for ImageInner {
@@ -27,6 +31,27 @@ impl Drop for ImageInner {
     }
 }
 
+/// This enum describes how the color values of an image are encoded. Color textures painted by artists are sRGB
+/// encoded, data textures like normal maps or distance fields store linear values and must not be converted.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ColorSpace {
+    #[default]
+    Srgb,
+    Linear,
+}
+
+impl ColorSpace {
+    /// This function returns the image format for the color space. Sampling a sRGB image converts the values into
+    /// linear space.
+    #[inline]
+    pub(crate) fn format(self) -> vk::Format {
+        match self {
+            ColorSpace::Srgb => vk::Format::R8G8B8A8_SRGB,
+            ColorSpace::Linear => vk::Format::R8G8B8A8_UNORM,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Image(pub(crate) Arc<ImageInner>);
 
@@ -37,11 +62,21 @@ impl PartialEq for Image {
 }
 
 impl Image {
+    /// This function loads the image from the specified file as sRGB encoded color texture
+    #[inline]
     pub fn from_file<P: AsRef<Path>>(app: &App, path: P) -> Result<Self> {
-        info!(
-            "Loading resource '{}' as image",
-            path.as_ref().file_name().unwrap().to_str().unwrap()
-        );
+        Self::from_file_with_color_space(app, path, ColorSpace::Srgb)
+    }
+
+    /// This function loads the image from the specified file. The color space specifies, whether the pixels are
+    /// converted from sRGB into linear space when sampled.
+    pub fn from_file_with_color_space<P: AsRef<Path>>(
+        app: &App,
+        path: P,
+        color_space: ColorSpace,
+    ) -> Result<Self> {
+        let path_name = path.as_ref().to_string_lossy().into_owned();
+        info!("Loading resource '{}' as image", path_name);
         let device = app.main_device();
         let vk_device = device.virtual_device();
 
@@ -60,7 +95,7 @@ impl Image {
             })
             .mip_levels(1)
             .array_layers(1)
-            .format(vk::Format::R8G8B8A8_UNORM)
+            .format(color_space.format())
             .tiling(vk::ImageTiling::OPTIMAL)
             .initial_layout(vk::ImageLayout::UNDEFINED)
             .usage(vk::ImageUsageFlags::SAMPLED | vk::ImageUsageFlags::TRANSFER_DST)
@@ -75,6 +110,7 @@ impl Image {
         let (image, image_alloc, image_alloc_info) = unsafe {
             vk_mem_alloc::create_image(allocator, &image_create_info, &image_alloc_create_info)
         }?;
+        device.set_object_name(image, &path_name);
 
         debug!("Initialize and write staging buffer");
         let staging_buffer = Buffer::new(
@@ -86,53 +122,69 @@ impl Image {
             ),
         )?;
         staging_buffer.write_ptr(pixels.as_ptr(), pixels.len())?;
+        staging_buffer.set_name(&format!("{} (Staging Buffer)", path_name));
 
-        // Command Buffer move memory to image
+        // Command Buffer move memory to image, the upload runs on the transfer queue if available
         debug!("Use staging buffer to upload pixel data into resource image");
-        app.upload_single_time_command_buffer(|command_buffer| unsafe {
-            device.memory_barrier(
-                command_buffer,
-                image,
-                vk::ImageLayout::UNDEFINED,
-                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
-            );
+        let upload_result = app.upload_with_transfer_queue(
+            |command_buffer| unsafe {
+                device.memory_barrier(
+                    command_buffer,
+                    image,
+                    vk::ImageLayout::UNDEFINED,
+                    vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                )?;
 
-            let buffer_image_copy = vk::BufferImageCopy::default()
-                .image_extent(vk::Extent3D {
-                    width,
-                    height,
-                    depth: 1,
-                })
-                .image_subresource(
-                    vk::ImageSubresourceLayers::default()
-                        .aspect_mask(vk::ImageAspectFlags::COLOR)
-                        .mip_level(0)
-                        .base_array_layer(0)
-                        .layer_count(1),
-                );
+                let buffer_image_copy = vk::BufferImageCopy::default()
+                    .image_extent(vk::Extent3D {
+                        width,
+                        height,
+                        depth: 1,
+                    })
+                    .image_subresource(
+                        vk::ImageSubresourceLayers::default()
+                            .aspect_mask(vk::ImageAspectFlags::COLOR)
+                            .mip_level(0)
+                            .base_array_layer(0)
+                            .layer_count(1),
+                    );
 
-            vk_device.cmd_copy_buffer_to_image(
-                command_buffer,
-                staging_buffer.buffer,
-                image,
-                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
-                slice::from_ref(&buffer_image_copy),
-            );
+                vk_device.cmd_copy_buffer_to_image(
+                    command_buffer,
+                    staging_buffer.buffer,
+                    image,
+                    vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    slice::from_ref(&buffer_image_copy),
+                );
 
-            device.memory_barrier(
-                command_buffer,
-                image,
-                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
-                vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
-            );
-        })?;
+                device.release_image_ownership(
+                    command_buffer,
+                    image,
+                    vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                )
+            },
+            |command_buffer| {
+                device.acquire_image_ownership(
+                    command_buffer,
+                    image,
+                    vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                );
+                Ok(())
+            },
+        );
+        if let Err(error) = upload_result {
+            unsafe { vk_mem_alloc::destroy_image(allocator, image, image_alloc) };
+            return Err(error);
+        }
 
         // Create image view
         debug!("Create image view and sampler by resource");
         let image_view_create_info = vk::ImageViewCreateInfo::default()
             .image(image)
             .view_type(vk::ImageViewType::TYPE_2D)
-            .format(vk::Format::R8G8B8A8_UNORM)
+            .format(color_space.format())
             .subresource_range(
                 vk::ImageSubresourceRange::default()
                     .aspect_mask(vk::ImageAspectFlags::COLOR)
@@ -140,6 +192,7 @@ impl Image {
                     .level_count(1),
             );
         let image_view = unsafe { vk_device.create_image_view(&image_view_create_info, None) }?;
+        device.set_object_name(image_view, &format!("{} (View)", path_name));
 
         // Create sampler
         let sampler_create_info = vk::SamplerCreateInfo::default()
@@ -160,6 +213,112 @@ impl Image {
             image_view,
             sampler,
             image_alloc,
+            layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+        })))
+    }
+
+    /// This function creates an empty storage image with the specified size and format. Storage images stay in the
+    /// general layout, so compute shaders can write them and all shaders can sample them afterwards. The content of the
+    /// image is undefined until it's written by a shader.
+    pub fn storage(
+        app: &App,
+        width: u32,
+        height: u32,
+        format: vk::Format,
+        name: &str,
+    ) -> Result<Self> {
+        info!(
+            "Create storage image '{}' with {}x{} pixels",
+            name, width, height
+        );
+        let device = app.main_device();
+        let vk_device = device.virtual_device();
+
+        let image_create_info = vk::ImageCreateInfo::default()
+            .image_type(vk::ImageType::TYPE_2D)
+            .extent(vk::Extent3D {
+                width,
+                height,
+                depth: 1,
+            })
+            .mip_levels(1)
+            .array_layers(1)
+            .format(format)
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .usage(vk::ImageUsageFlags::STORAGE | vk::ImageUsageFlags::SAMPLED)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .samples(vk::SampleCountFlags::TYPE_1);
+        let image_alloc_create_info = AllocationCreateInfo {
+            usage: MemoryUsage::AUTO_PREFER_DEVICE,
+            ..Default::default()
+        };
+        let allocator = *device.allocator();
+        let (image, image_alloc, _) = unsafe {
+            vk_mem_alloc::create_image(allocator, &image_create_info, &image_alloc_create_info)
+        }?;
+        device.set_object_name(image, name);
+
+        // The image is transitioned once, all later accesses use the general layout
+        let transition_result = app.upload_single_time_command_buffer(|command_buffer| {
+            device.memory_barrier(
+                command_buffer,
+                image,
+                vk::ImageLayout::UNDEFINED,
+                vk::ImageLayout::GENERAL,
+            )
+        });
+        if let Err(error) = transition_result {
+            unsafe { vk_mem_alloc::destroy_image(allocator, image, image_alloc) };
+            return Err(error);
+        }
+
+        let image_view_create_info = vk::ImageViewCreateInfo::default()
+            .image(image)
+            .view_type(vk::ImageViewType::TYPE_2D)
+            .format(format)
+            .subresource_range(
+                vk::ImageSubresourceRange::default()
+                    .aspect_mask(vk::ImageAspectFlags::COLOR)
+                    .layer_count(1)
+                    .level_count(1),
+            );
+        let image_view = match unsafe { vk_device.create_image_view(&image_view_create_info, None) }
+        {
+            Ok(image_view) => image_view,
+            Err(error) => {
+                unsafe { vk_mem_alloc::destroy_image(allocator, image, image_alloc) };
+                return Err(error.into());
+            }
+        };
+        device.set_object_name(image_view, &format!("{} (View)", name));
+
+        // The sampler is used, when the image is sampled by shaders after it was written
+        let sampler_create_info = vk::SamplerCreateInfo::default()
+            .mag_filter(vk::Filter::LINEAR)
+            .min_filter(vk::Filter::LINEAR)
+            .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .address_mode_w(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .mipmap_mode(vk::SamplerMipmapMode::LINEAR);
+        let sampler = match unsafe { vk_device.create_sampler(&sampler_create_info, None) } {
+            Ok(sampler) => sampler,
+            Err(error) => {
+                unsafe {
+                    vk_device.destroy_image_view(image_view, None);
+                    vk_mem_alloc::destroy_image(allocator, image, image_alloc);
+                }
+                return Err(error.into());
+            }
+        };
+
+        Ok(Self(Arc::new(ImageInner {
+            app: app.clone(),
+            image,
+            image_view,
+            sampler,
+            image_alloc,
+            layout: vk::ImageLayout::GENERAL,
         })))
     }
 }
@@ -168,7 +327,7 @@ pub fn get_memory_type_index(
     app: &App,
     type_filter: Option<u32>,
     properties: vk::MemoryPropertyFlags,
-) -> u32 {
+) -> Result<u32> {
     let memory_properties = unsafe {
         app.instance()
             .get_physical_device_memory_properties(app.main_device().physical_device())
@@ -179,8 +338,8 @@ pub fn get_memory_type_index(
             .unwrap_or(true)
             && !(memory_properties.memory_types[i].property_flags & properties).is_empty()
         {
-            return i as u32;
+            return Ok(i as u32);
         }
     }
-    panic!("No support ig... ._.")
+    Err(Error::NoSuitableMemoryType)
 }
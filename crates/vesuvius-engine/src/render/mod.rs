@@ -1,51 +1,520 @@
+mod attachment;
 pub mod buffer;
+mod cache;
+pub mod color;
+mod frame;
 pub mod image;
 pub mod pipeline;
+pub mod swapchain;
 pub mod text;
+mod watcher;
 
+use crate::render::attachment::Attachment;
 use crate::render::buffer::Buffer;
-use crate::render::pipeline::config::PipelineConfiguration;
+use crate::render::frame::Frame;
+use crate::render::pipeline::compute::ComputePipeline;
+use crate::render::pipeline::config::{self, PipelineConfiguration, PipelineKind};
+use crate::render::pipeline::diagnostic::Diagnostic;
+use crate::render::pipeline::shader::ShaderModule;
+use crate::render::swapchain::SwapchainConfig;
+use crate::render::watcher::{self, AssetWatcher};
+use ::image::{ImageFormat, RgbaImage};
 use ash::extensions::khr::{Surface, Swapchain};
 use ash::vk;
+use bytemuck::Pod;
+use log::{error, info, warn};
 use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle};
+use std::cell::Cell;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::{fs, mem, slice};
-use glam::vec2;
+use std::{any, fs, mem, slice};
+use glam::{vec2, Vec3};
+use vk_mem_alloc::{Allocation, AllocationCreateInfo, MemoryUsage};
 
-use crate::render::buffer::builder::BufferBuilder;
-use crate::render::pipeline::{DescriptorSet, RenderPipeline, WriteDescriptorSet};
+use crate::error::Error;
+use crate::render::buffer::builder::QueuedVertices;
+use crate::render::buffer::format::Topology;
+use crate::render::buffer::instance::{InstanceBatch, QuadVertex};
+use crate::render::buffer::layout::{VertexAttribute, VertexLayout};
+use crate::render::pipeline::{
+    AttachmentFormats, DescriptorSet, RenderPipeline, WriteDescriptorSet, INSTANCE_BINDING,
+    VERTEX_BINDING,
+};
 use crate::App;
 use crate::Result;
 
+/// The default count of frames, which can be recorded by the CPU while the GPU is still working on previous frames
+pub const DEFAULT_FRAMES_IN_FLIGHT: usize = 2;
+
+/// The format of the offscreen images, if the preferred format of the swapchain config is not supported. The sRGB
+/// variant is guaranteed to support color attachments by the Vulkan specification.
+const FALLBACK_COLOR_FORMAT: vk::Format = vk::Format::R8G8B8A8_SRGB;
+
+/// The directory, which contains the configuration files of the render pipelines
+pub(crate) const PIPELINES_DIRECTORY: &str = "assets/pipelines";
+
+/// The alignment of the instances in the instance buffer of a frame, which fits every vertex attribute format
+const INSTANCE_ALIGNMENT: vk::DeviceSize = 16;
+
 struct GameRendererInner {
     application: App,
 
-    // Surface
+    // Surface, this is none when the renderer draws into offscreen images
     surface_loader: Surface,
-    surface: vk::SurfaceKHR,
+    surface: Option<vk::SurfaceKHR>,
 
     // Images
     images: Vec<vk::Image>,
     image_views: Vec<vk::ImageView>,
+    offscreen_allocations: Vec<Allocation>,
+    extent: vk::Extent2D,
+    format: vk::Format,
     current_image_index: u32,
 
+    // Depth images and multisampled color images, one of each per image
+    depth_format: vk::Format,
+    samples: vk::SampleCountFlags,
+    depth_attachments: Vec<Attachment>,
+    multisample_attachments: Vec<Attachment>,
+
+    /// This field is true, when the images can be copied back into host memory by [GameRenderer::capture_frame]
+    capturable: bool,
+
     // Swapchain
     swapchain_loader: Swapchain,
     swapchain: Option<vk::SwapchainKHR>,
+    swapchain_config: SwapchainConfig,
+    swapchain_config_changed: bool,
+
+    /// The semaphores signaled by the queue submission and waited on by the presentation, one per swapchain image. The
+    /// presentation engine may still wait on the semaphore of an image, until that image is acquired again, so these
+    /// can't be reused by the next frame in flight.
+    present_semaphores: Vec<vk::Semaphore>,
 
-    // Command Pool and Buffer
-    command_pool: vk::CommandPool,
-    command_buffer: vk::CommandBuffer,
+    /// This field is true, when the acquire reported a suboptimal swapchain. The swapchain is recreated after the
+    /// presentation of the acquired image.
+    swapchain_suboptimal: bool,
 
-    // Semaphores
-    submit_semaphore: vk::Semaphore,
-    present_semaphore: vk::Semaphore,
+    // Frames in flight
+    frames: Vec<Frame>,
+    current_frame: usize,
+
+    /// This field is true, while the dynamic rendering into the current image is active. Compute dispatches suspend
+    /// the rendering, because barriers aren't allowed inside of it.
+    rendering: Cell<bool>,
 
     // Other things
     pipelines: Vec<RenderPipeline>,
+    compute_pipelines: Vec<ComputePipeline>,
+    pipeline_cache: vk::PipelineCache,
     descriptor_pool: vk::DescriptorPool,
-    queued_buffer_builder: Vec<BufferBuilder>,
-    buffer_cache: Vec<Buffer>,
+    queued_buffer_builder: Vec<QueuedVertices>,
+
+    /// The unit quad, which is the shared geometry of the instances drawn by [GameRenderer::draw_instances]
+    quad_vertex_buffer: Buffer,
+    quad_index_buffer: Buffer,
+
+    /// The watcher over the pipeline configurations and shaders, this is none if hot reload is disabled
+    asset_watcher: Option<AssetWatcher>,
+}
+
+impl GameRendererInner {
+    #[inline]
+    fn frame(&self) -> &Frame {
+        &self.frames[self.current_frame]
+    }
+
+    #[inline]
+    fn command_buffer(&self) -> vk::CommandBuffer {
+        self.frame().command_buffer
+    }
+
+    /// This function returns the formats of the attachments, the pipelines are compiled for
+    #[inline]
+    fn attachment_formats(&self) -> AttachmentFormats {
+        AttachmentFormats {
+            color_format: self.format,
+            depth_format: self.depth_format,
+            samples: self.samples,
+        }
+    }
+
+    /// This function destroys the images, the renderer draws into. These are the swapchain images or the offscreen
+    /// images when running headless. The caller has to ensure, that the device is idle.
+    fn destroy_render_targets(&mut self) {
+        for attachment in self
+            .depth_attachments
+            .drain(..)
+            .chain(self.multisample_attachments.drain(..))
+        {
+            attachment.destroy(&self.application);
+        }
+
+        let device = self.application.main_device();
+        for image_view in self.image_views.drain(..) {
+            unsafe { device.virtual_device().destroy_image_view(image_view, None) };
+        }
+
+        // Swapchain images are owned by the swapchain, so only offscreen images are destroyed here
+        for (image, allocation) in self
+            .images
+            .drain(..)
+            .zip(self.offscreen_allocations.drain(..))
+        {
+            unsafe { vk_mem_alloc::destroy_image(*device.allocator(), image, allocation) };
+        }
+        self.images.clear();
+
+        if let Some(swapchain) = self.swapchain.take() {
+            unsafe { self.swapchain_loader.destroy_swapchain(swapchain, None) };
+        }
+        for semaphore in self.present_semaphores.drain(..) {
+            unsafe { device.virtual_device().destroy_semaphore(semaphore, None) };
+        }
+    }
+
+    /// This function creates the swapchain for the surface and the image views for the swapchain images
+    fn create_swapchain(&mut self, surface: vk::SurfaceKHR) -> Result<()> {
+        let surface_capabilities = unsafe {
+            self.surface_loader
+                .get_physical_device_surface_capabilities(
+                    self.application.main_device().physical_device(),
+                    surface,
+                )
+        }?;
+
+        let physical_device = self.application.main_device().physical_device();
+        let surface_formats = unsafe {
+            self.surface_loader
+                .get_physical_device_surface_formats(physical_device, surface)
+        }?;
+        let present_modes = unsafe {
+            self.surface_loader
+                .get_physical_device_surface_present_modes(physical_device, surface)
+        }?;
+
+        // Negotiate the swapchain config against the capabilities of the surface
+        let config = &self.swapchain_config;
+        let surface_format = config.choose_surface_format(&surface_formats);
+        let present_mode = config.choose_present_mode(&present_modes);
+        let window_size = self
+            .application
+            .window()
+            .map(|window| window.inner_size())
+            .unwrap_or_default();
+        let extent =
+            swapchain::choose_extent(&surface_capabilities, window_size.width, window_size.height);
+        info!(
+            "Create swapchain with {:?} ({:?}), {:?} and {}x{}",
+            surface_format.format,
+            surface_format.color_space,
+            present_mode,
+            extent.width,
+            extent.height
+        );
+
+        // The images are only copyable into host memory, if the surface supports transfer operations
+        let mut image_usage = vk::ImageUsageFlags::COLOR_ATTACHMENT;
+        self.capturable = surface_capabilities
+            .supported_usage_flags
+            .contains(vk::ImageUsageFlags::TRANSFER_SRC);
+        if self.capturable {
+            image_usage |= vk::ImageUsageFlags::TRANSFER_SRC;
+        }
+
+        // The images are shared concurrently, if the graphics queue can't present
+        let queue_families = self.application.main_device().queue_families();
+        let queue_family_indices = [queue_families.graphics, queue_families.present];
+        let (sharing_mode, queue_family_indices) =
+            if queue_families.graphics == queue_families.present {
+                (vk::SharingMode::EXCLUSIVE, &[][..])
+            } else {
+                (vk::SharingMode::CONCURRENT, &queue_family_indices[..])
+            };
+
+        let swapchain_create_info = vk::SwapchainCreateInfoKHR::default()
+            .surface(surface)
+            .min_image_count(config.choose_image_count(&surface_capabilities))
+            .image_format(surface_format.format)
+            .image_color_space(surface_format.color_space)
+            .image_extent(extent)
+            .image_array_layers(1)
+            .image_usage(image_usage)
+            .image_sharing_mode(sharing_mode)
+            .queue_family_indices(queue_family_indices)
+            .pre_transform(surface_capabilities.current_transform)
+            .composite_alpha(swapchain::choose_composite_alpha(&surface_capabilities))
+            .present_mode(present_mode)
+            .clipped(true);
+        let swapchain = unsafe {
+            self.swapchain_loader
+                .create_swapchain(&swapchain_create_info, None)
+        }?;
+
+        self.images = unsafe { self.swapchain_loader.get_swapchain_images(swapchain) }?;
+        self.swapchain = Some(swapchain);
+        self.format = surface_format.format;
+        self.extent = extent;
+
+        // Create one present semaphore per swapchain image, the number of images can change with every swapchain
+        let device = self.application.main_device().virtual_device();
+        self.present_semaphores = self
+            .images
+            .iter()
+            .map(|_| unsafe { device.create_semaphore(&vk::SemaphoreCreateInfo::default(), None) })
+            .collect::<ash::prelude::VkResult<_>>()?;
+        Ok(())
+    }
+
+    /// This function creates one offscreen color image per frame in flight. These images replace the swapchain
+    /// images, when the renderer runs headless.
+    fn create_offscreen_images(&mut self) -> Result<()> {
+        let allocator = *self.application.main_device().allocator();
+
+        // Use the preferred format of the swapchain config, if the device can render into it
+        let format_properties = unsafe {
+            self.application
+                .instance()
+                .get_physical_device_format_properties(
+                    self.application.main_device().physical_device(),
+                    self.swapchain_config.preferred_format,
+                )
+        };
+        self.format = if format_properties.optimal_tiling_features.contains(
+            vk::FormatFeatureFlags::COLOR_ATTACHMENT | vk::FormatFeatureFlags::TRANSFER_SRC,
+        ) {
+            self.swapchain_config.preferred_format
+        } else {
+            FALLBACK_COLOR_FORMAT
+        };
+
+        for _ in 0..self.frames.len() {
+            let image_create_info = vk::ImageCreateInfo::default()
+                .image_type(vk::ImageType::TYPE_2D)
+                .extent(vk::Extent3D {
+                    width: self.extent.width,
+                    height: self.extent.height,
+                    depth: 1,
+                })
+                .mip_levels(1)
+                .array_layers(1)
+                .format(self.format)
+                .tiling(vk::ImageTiling::OPTIMAL)
+                .initial_layout(vk::ImageLayout::UNDEFINED)
+                .usage(vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSFER_SRC)
+                .sharing_mode(vk::SharingMode::EXCLUSIVE)
+                .samples(vk::SampleCountFlags::TYPE_1);
+            let image_alloc_create_info = AllocationCreateInfo {
+                usage: MemoryUsage::AUTO_PREFER_DEVICE,
+                ..Default::default()
+            };
+            let (image, allocation, _) = unsafe {
+                vk_mem_alloc::create_image(allocator, &image_create_info, &image_alloc_create_info)
+            }?;
+            self.images.push(image);
+            self.offscreen_allocations.push(allocation);
+        }
+        self.capturable = true;
+        Ok(())
+    }
+
+    /// This function creates a depth image for every image, the renderer draws into. If multisampling is enabled, a
+    /// multisampled color image is created for every image too, which is resolved into the image at the end of the
+    /// rendering.
+    fn create_attachments(&mut self) -> Result<()> {
+        for index in 0..self.images.len() {
+            let depth_attachment = Attachment::new(
+                &self.application,
+                self.depth_format,
+                self.extent,
+                self.samples,
+                vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
+                attachment::depth_aspect_mask(self.depth_format),
+            )?;
+            self.application
+                .main_device()
+                .set_object_name(depth_attachment.image, &format!("Depth Image {}", index));
+            self.depth_attachments.push(depth_attachment);
+
+            if self.samples != vk::SampleCountFlags::TYPE_1 {
+                let multisample_attachment = Attachment::new(
+                    &self.application,
+                    self.format,
+                    self.extent,
+                    self.samples,
+                    vk::ImageUsageFlags::COLOR_ATTACHMENT,
+                    vk::ImageAspectFlags::COLOR,
+                )?;
+                self.application.main_device().set_object_name(
+                    multisample_attachment.image,
+                    &format!("Multisample Image {}", index),
+                );
+                self.multisample_attachments.push(multisample_attachment);
+            }
+        }
+        Ok(())
+    }
+
+    /// This function creates the pipeline and its variants of the specified configuration file and compiles them. An
+    /// existing pipeline with the same name is only replaced, if the compilation was successful. The caller has to
+    /// ensure, that the GPU doesn't use the replaced pipeline anymore. The problems of all variants are returned as
+    /// diagnostics.
+    fn load_pipeline(&mut self, config_file: &Path) -> Result<()> {
+        let pipeline_config = PipelineConfiguration::from_file(config_file)?;
+
+        // The pipeline itself and every variant is compiled as own pipeline
+        let mut diagnostics = Vec::new();
+        for variant in pipeline_config.variant_names() {
+            let result = match pipeline_config.kind {
+                PipelineKind::Graphics => self.load_render_pipeline(&pipeline_config, variant),
+                PipelineKind::Compute => self.load_compute_pipeline(&pipeline_config, variant),
+            };
+            if let Err(error) = result {
+                diagnostics.extend(Diagnostic::from_error(
+                    &pipeline_config.variant_name(variant),
+                    config_file,
+                    error,
+                ));
+            }
+        }
+
+        match diagnostics.is_empty() {
+            true => Ok(()),
+            false => Err(Error::Diagnostics(diagnostics)),
+        }
+    }
+
+    /// This function creates and compiles the render pipeline of the variant and replaces the existing pipeline with
+    /// the same name. A compute pipeline with the name is removed, if the kind of the configuration has changed. The
+    /// pipelines derived from the replaced pipeline by [RenderPipeline::with_specialization] are rebuilt from the new
+    /// pipeline with their constants, so they don't keep the old configuration.
+    fn load_render_pipeline(
+        &mut self,
+        pipeline_config: &PipelineConfiguration,
+        variant: Option<&str>,
+    ) -> Result<()> {
+        let mut pipeline = RenderPipeline::new(
+            self.application.clone(),
+            self.pipeline_cache,
+            pipeline_config,
+            variant,
+        )?;
+        pipeline.compile(self.attachment_formats())?;
+
+        let mut derived_pipelines = Vec::new();
+        for derived_pipeline in self.pipelines.iter() {
+            let Some((base_name, constants)) = derived_pipeline.derived_from.as_ref() else {
+                continue;
+            };
+            if *base_name == pipeline.name {
+                let mut rebuilt_pipeline =
+                    pipeline.derive(derived_pipeline.name.clone(), constants.clone());
+                rebuilt_pipeline.compile(self.attachment_formats())?;
+                derived_pipelines.push(rebuilt_pipeline);
+            }
+        }
+        for rebuilt_pipeline in derived_pipelines {
+            if let Some(derived_pipeline) = self
+                .pipelines
+                .iter_mut()
+                .find(|derived_pipeline| derived_pipeline.name == rebuilt_pipeline.name)
+            {
+                *derived_pipeline = rebuilt_pipeline;
+            }
+        }
+
+        self.compute_pipelines
+            .retain(|compute_pipeline| compute_pipeline.name != pipeline.name);
+        match self
+            .pipelines
+            .iter_mut()
+            .find(|existing_pipeline| existing_pipeline.name == pipeline.name)
+        {
+            Some(existing_pipeline) => *existing_pipeline = pipeline,
+            None => self.pipelines.push(pipeline),
+        }
+        Ok(())
+    }
+
+    /// This function creates and compiles the compute pipeline of the variant and replaces the existing pipeline with
+    /// the same name. A render pipeline with the name is removed, if the kind of the configuration has changed.
+    fn load_compute_pipeline(
+        &mut self,
+        pipeline_config: &PipelineConfiguration,
+        variant: Option<&str>,
+    ) -> Result<()> {
+        let mut pipeline = ComputePipeline::new(
+            self.application.clone(),
+            self.pipeline_cache,
+            pipeline_config,
+            variant,
+        )?;
+        pipeline.compile()?;
+
+        self.pipelines
+            .retain(|render_pipeline| render_pipeline.name != pipeline.name);
+        match self
+            .compute_pipelines
+            .iter_mut()
+            .find(|existing_pipeline| existing_pipeline.name == pipeline.name)
+        {
+            Some(existing_pipeline) => *existing_pipeline = pipeline,
+            None => self.compute_pipelines.push(pipeline),
+        }
+        Ok(())
+    }
+
+    /// This function removes the pipeline and its variants, which were loaded from the specified configuration file.
+    /// This is used when the configuration file was deleted or renamed, so the pipelines aren't drawn by their old
+    /// names anymore. The caller has to ensure, that the GPU doesn't use the removed pipelines anymore.
+    fn remove_pipelines(&mut self, config_file: &Path) {
+        let config_file = watcher::normalize_path(config_file);
+        let is_removed = |path: &Path| watcher::normalize_path(path) == config_file;
+        self.pipelines
+            .retain(|pipeline| !is_removed(&pipeline.config_path));
+        self.compute_pipelines
+            .retain(|pipeline| !is_removed(&pipeline.config_path));
+    }
+
+    /// This function watches the directories of all shader sources and included files, if hot reload is enabled
+    fn watch_shader_sources(&mut self) -> Result<()> {
+        if let Some(asset_watcher) = self.asset_watcher.as_mut() {
+            let shader_modules = self
+                .pipelines
+                .iter()
+                .flat_map(|pipeline| pipeline.shader_modules.iter())
+                .chain(
+                    self.compute_pipelines
+                        .iter()
+                        .flat_map(|pipeline| pipeline.shader_modules.iter()),
+                );
+            for path in shader_modules.flat_map(|shader_module| shader_module.source_files()) {
+                asset_watcher.watch_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// This function creates the image views for the images, the renderer draws into
+    fn create_image_views(&mut self) -> Result<()> {
+        let device = self.application.main_device().virtual_device();
+        for image in self.images.iter() {
+            let image_view_create_info = vk::ImageViewCreateInfo::default()
+                .image(*image)
+                .view_type(vk::ImageViewType::TYPE_2D)
+                .format(self.format)
+                .components(vk::ComponentMapping::default())
+                .subresource_range(
+                    vk::ImageSubresourceRange::default()
+                        .aspect_mask(vk::ImageAspectFlags::COLOR)
+                        .layer_count(1)
+                        .level_count(1),
+                );
+            self.image_views
+                .push(unsafe { device.create_image_view(&image_view_create_info, None) }?);
+        }
+        Ok(())
+    }
 }
 
 impl Drop for GameRendererInner {
@@ -53,20 +522,26 @@ impl Drop for GameRendererInner {
         let device = self.application.main_device().virtual_device();
         let surface_loader = Surface::new(self.application.entry(), self.application.instance());
         unsafe {
-            device.destroy_descriptor_pool(self.descriptor_pool, None);
-            device.destroy_semaphore(self.submit_semaphore, None);
-            device.destroy_semaphore(self.present_semaphore, None);
-            for image_view in self.image_views.iter() {
-                device.destroy_image_view(*image_view, None);
+            // Wait for all frames in flight before destroying their resources
+            device
+                .device_wait_idle()
+                .expect("Unable to wait for device idle");
+            for frame in self.frames.iter_mut() {
+                frame.destroy(device);
             }
 
-            if let Some(swapchain) = self.swapchain.as_ref() {
-                self.swapchain_loader.destroy_swapchain(*swapchain, None);
-            }
+            device.destroy_descriptor_pool(self.descriptor_pool, None);
+        }
 
-            surface_loader.destroy_surface(self.surface, None);
-            device.free_command_buffers(self.command_pool, slice::from_ref(&self.command_buffer));
-            device.destroy_command_pool(self.command_pool, None);
+        // Write the pipeline cache, so the next run doesn't have to compile the pipelines again
+        if let Err(error) = cache::store_pipeline_cache(&self.application, self.pipeline_cache) {
+            warn!("Unable to write pipeline cache => {}", error);
+        }
+        unsafe { device.destroy_pipeline_cache(self.pipeline_cache, None) };
+
+        self.destroy_render_targets();
+        if let Some(surface) = self.surface {
+            unsafe { surface_loader.destroy_surface(surface, None) };
         }
     }
 }
@@ -75,40 +550,75 @@ impl Drop for GameRendererInner {
 pub struct GameRenderer(Arc<GameRendererInner>);
 
 impl GameRenderer {
+    /// This function creates a new game renderer with [DEFAULT_FRAMES_IN_FLIGHT] frames in flight
+    #[inline]
     pub fn new(application: App) -> Result<Self> {
-        let device = application.main_device().virtual_device();
-        let window = application.window();
-        let surface = unsafe {
-            ash_window::create_surface(
-                application.entry(),
-                application.instance(),
-                window.raw_display_handle(),
-                window.raw_window_handle(),
-                None,
-            )
-        }?;
+        Self::with_frames_in_flight(application, DEFAULT_FRAMES_IN_FLIGHT)
+    }
 
-        // Command Pool and Command Buffer
-        let command_pool_create_info = vk::CommandPoolCreateInfo::default()
-            .flags(vk::CommandPoolCreateFlags::RESET_COMMAND_BUFFER) // Reset at begin
-            .queue_family_index(0);
-        let command_pool = unsafe { device.create_command_pool(&command_pool_create_info, None) }?;
+    /// This function creates a new game renderer, which allows the CPU to record the specified count of frames while
+    /// the GPU is still working on the previous frames. If the application runs headless, the renderer draws into
+    /// offscreen images with the size of 800x600.
+    pub fn with_frames_in_flight(application: App, frames_in_flight: usize) -> Result<Self> {
+        let surface = match application.window() {
+            Some(window) => Some(unsafe {
+                ash_window::create_surface(
+                    application.entry(),
+                    application.instance(),
+                    window.raw_display_handle(),
+                    window.raw_window_handle(),
+                    None,
+                )
+            }?),
+            None => None,
+        };
+        Self::create(application, surface, frames_in_flight, 800, 600)
+    }
 
-        let command_buffer_alloc_info = vk::CommandBufferAllocateInfo::default()
-            .command_pool(command_pool)
-            .command_buffer_count(1);
-        let command_buffer =
-            unsafe { device.allocate_command_buffers(&command_buffer_alloc_info) }?[0];
+    /// This function creates a new game renderer, which draws into offscreen images with the specified size instead
+    /// of a window. The renderer doesn't need a surface, so this also works on headless or software Vulkan
+    /// implementations.
+    #[inline]
+    pub fn headless(application: App, width: u32, height: u32) -> Result<Self> {
+        Self::create(application, None, DEFAULT_FRAMES_IN_FLIGHT, width, height)
+    }
 
-        // Create descriptor pool
+    fn create(
+        application: App,
+        surface: Option<vk::SurfaceKHR>,
+        frames_in_flight: usize,
+        width: u32,
+        height: u32,
+    ) -> Result<Self> {
+        let device = application.main_device().virtual_device();
+        let queue_family_index = application.main_device().queue_families().graphics;
+
+        // Command pools, command buffers and synchronization primitives of every frame in flight
+        let frames = (0..frames_in_flight.max(1))
+            .map(|_| Frame::new(device, queue_family_index))
+            .collect::<Result<Vec<_>>>()?;
+        for (index, frame) in frames.iter().enumerate() {
+            let main_device = application.main_device();
+            main_device.set_object_name(frame.command_buffer, &format!("Frame {}", index));
+            main_device.set_object_name(frame.in_flight_fence, &format!("Frame {} (Fence)", index));
+        }
+
+        // Create descriptor pool, descriptor sets are kept alive until their frame has finished, so the pool has to
+        // serve the sets of all frames in flight
         // TODO
         let descriptor_pool_sizes = [
             vk::DescriptorPoolSize::default()
-                .descriptor_count(1)
+                .descriptor_count(1024)
                 .ty(vk::DescriptorType::UNIFORM_BUFFER),
             vk::DescriptorPoolSize::default()
-                .descriptor_count(1)
+                .descriptor_count(1024)
                 .ty(vk::DescriptorType::COMBINED_IMAGE_SAMPLER),
+            vk::DescriptorPoolSize::default()
+                .descriptor_count(256)
+                .ty(vk::DescriptorType::STORAGE_BUFFER),
+            vk::DescriptorPoolSize::default()
+                .descriptor_count(256)
+                .ty(vk::DescriptorType::STORAGE_IMAGE),
         ];
         let descriptor_pool_create_info = vk::DescriptorPoolCreateInfo::default()
             .pool_sizes(&descriptor_pool_sizes)
@@ -117,211 +627,411 @@ impl GameRenderer {
         let descriptor_pool =
             unsafe { device.create_descriptor_pool(&descriptor_pool_create_info, None) }?;
 
+        // The pipeline cache contains the pipelines compiled by the driver in previous runs
+        let pipeline_cache = cache::create_pipeline_cache(&application)?;
+
+        // The corners of the unit quad are scaled and moved by the per-instance attributes
+        let quad_vertex_buffer = Buffer::new_device_local(
+            application.clone(),
+            vk::BufferUsageFlags::VERTEX_BUFFER,
+            &[
+                vec2(0.0, 0.0),
+                vec2(1.0, 0.0),
+                vec2(1.0, 1.0),
+                vec2(0.0, 1.0),
+            ]
+            .map(|corner| QuadVertex { corner }),
+        )?;
+        let quad_index_buffer = Buffer::new_device_local(
+            application.clone(),
+            vk::BufferUsageFlags::INDEX_BUFFER,
+            Topology::Quad.indices(0).as_slice(),
+        )?;
+        quad_vertex_buffer.set_name("Quad (Vertex Buffer)");
+        quad_index_buffer.set_name("Quad (Index Buffer)");
+
         // Create swapchain loader and return game renderer to caller
         let swapchain_loader = Swapchain::new(application.instance(), device);
         let surface_loader = Surface::new(application.entry(), application.instance());
         Ok(Self(Arc::new(GameRendererInner {
-            submit_semaphore: unsafe {
-                device.create_semaphore(&vk::SemaphoreCreateInfo::default(), None)
-            }?,
-            present_semaphore: unsafe {
-                device.create_semaphore(&vk::SemaphoreCreateInfo::default(), None)
-            }?,
             surface_loader,
             swapchain_loader,
             swapchain: None,
+            swapchain_config: SwapchainConfig::default(),
+            swapchain_config_changed: false,
+            present_semaphores: Vec::new(),
+            swapchain_suboptimal: false,
             images: Vec::new(),
             image_views: Vec::new(),
-            command_pool,
-            command_buffer,
+            offscreen_allocations: Vec::new(),
+            extent: vk::Extent2D { width, height },
+            format: SwapchainConfig::default().preferred_format,
+            depth_format: attachment::choose_depth_format(&application),
+            samples: vk::SampleCountFlags::TYPE_1,
+            depth_attachments: Vec::new(),
+            multisample_attachments: Vec::new(),
+            capturable: false,
+            frames,
+            current_frame: 0,
             current_image_index: 0,
             application,
             surface,
+            rendering: Cell::new(false),
             pipelines: Vec::new(),
+            compute_pipelines: Vec::new(),
+            pipeline_cache,
             descriptor_pool,
             queued_buffer_builder: Vec::new(),
-            buffer_cache: Vec::new(),
+            quad_vertex_buffer,
+            quad_index_buffer,
+            asset_watcher: None,
         })))
     }
 
     pub fn reload(&mut self, recompile_pipelines: bool) -> Result<()> {
         let inner = unsafe { Arc::get_mut_unchecked(&mut self.0) };
 
-        // Create swapchain and images
+        // Create swapchain or offscreen images
         let device = inner.application.main_device().virtual_device();
         unsafe { device.device_wait_idle() }?;
 
-        for image_view in &inner.image_views {
-            unsafe { device.destroy_image_view(*image_view, None) };
+        inner.destroy_render_targets();
+        match inner.surface {
+            Some(surface) => inner.create_swapchain(surface)?,
+            None => inner.create_offscreen_images()?,
         }
+        inner.create_image_views()?;
+        inner.samples =
+            attachment::choose_sample_count(&inner.application, inner.swapchain_config.samples);
+        inner.create_attachments()?;
+        inner.swapchain_config_changed = false;
+        inner.swapchain_suboptimal = false;
 
-        if let Some(swapchain) = inner.swapchain.as_ref() {
-            unsafe { inner.swapchain_loader.destroy_swapchain(*swapchain, None) };
-        }
-        let surface_capabilities = unsafe {
+        // (Re)compile pipelines. The problems of all pipelines are collected, so every broken pipeline is reported at
+        // once.
+        let mut diagnostics = Vec::new();
+        if recompile_pipelines {
+            // The pipelines of deleted or renamed configurations aren't loaded again
             inner
-                .surface_loader
-                .get_physical_device_surface_capabilities(
-                    inner.application.main_device().physical_device(),
-                    inner.surface,
-                )
-        }?;
-        let swapchain_create_info = vk::SwapchainCreateInfoKHR::default()
-            .surface(inner.surface)
-            .min_image_count(2)
-            .image_format(vk::Format::B8G8R8A8_UNORM)
-            .image_color_space(vk::ColorSpaceKHR::SRGB_NONLINEAR)
-            .image_extent(surface_capabilities.current_extent)
-            .image_array_layers(1)
-            .image_usage(vk::ImageUsageFlags::COLOR_ATTACHMENT)
-            .image_sharing_mode(vk::SharingMode::EXCLUSIVE)
-            .pre_transform(vk::SurfaceTransformFlagsKHR::IDENTITY)
-            .composite_alpha(vk::CompositeAlphaFlagsKHR::OPAQUE)
-            .present_mode(vk::PresentModeKHR::FIFO);
-        let swapchain = unsafe {
+                .pipelines
+                .retain(|pipeline| pipeline.config_path.is_file());
             inner
-                .swapchain_loader
-                .create_swapchain(&swapchain_create_info, None)
-        }?;
+                .compute_pipelines
+                .retain(|pipeline| pipeline.config_path.is_file());
 
-        let images = unsafe { inner.swapchain_loader.get_swapchain_images(swapchain) }?;
-        let image_views = images
-            .iter()
-            .map(|image| {
-                let image_view_create_info = vk::ImageViewCreateInfo::default()
-                    .image(*image)
-                    .view_type(vk::ImageViewType::TYPE_2D)
-                    .format(vk::Format::B8G8R8A8_UNORM)
-                    .components(vk::ComponentMapping::default())
-                    .subresource_range(
-                        vk::ImageSubresourceRange::default()
-                            .aspect_mask(vk::ImageAspectFlags::COLOR)
-                            .layer_count(1)
-                            .level_count(1),
-                    );
-                unsafe { device.create_image_view(&image_view_create_info, None) }.unwrap()
-            })
-            .collect::<Vec<_>>();
-
-        inner.swapchain = Some(swapchain);
-        inner.images = images;
-        inner.image_views = image_views;
-
-        // (Re)compile pipelines
-        if recompile_pipelines {
-            for pipeline_configurations in
-                fs::read_dir("assets/pipelines").expect("Unable to find pipeline configs")
-            {
+            for pipeline_configurations in fs::read_dir(PIPELINES_DIRECTORY)? {
                 // Filter invalid configuration files
-                let config_file = pipeline_configurations.unwrap().path();
-                if !config_file
-                    .file_name()
-                    .unwrap()
-                    .to_str()
-                    .unwrap()
-                    .ends_with(".json")
-                {
+                let config_file = pipeline_configurations?.path();
+                if !config::is_pipeline_configuration(&config_file) {
                     continue;
                 }
 
-                // Create pipeline or recompile it
-                let file_content = String::from_utf8(fs::read(&config_file)?)?;
-                let pipeline_config: PipelineConfiguration = serde_json::from_str(&file_content)
-                    .expect("Unable to read pipeline configuration");
-                match inner
-                    .pipelines
-                    .iter_mut()
-                    .find(|pipeline| pipeline.name == pipeline_config.name)
-                {
-                    Some(pipeline) => pipeline.compile()?, // TODO: Reload only if changes are detected
-                    None => {
-                        let mut pipeline =
-                            RenderPipeline::new(inner.application.clone(), pipeline_config)?;
-                        pipeline.compile()?;
-                        inner.pipelines.push(pipeline);
-                    }
+                if let Err(error) = inner.load_pipeline(&config_file) {
+                    diagnostics.extend(Diagnostic::from_error(
+                        &config::config_file_name(&config_file),
+                        &config_file,
+                        error,
+                    ));
                 }
             }
         }
 
+        // The pipelines, which weren't compiled for the current attachments, have to be recompiled. These are all
+        // pipelines, if the format of an attachment has changed and the pipelines weren't reloaded from their
+        // configuration.
+        let attachment_formats = inner.attachment_formats();
+        for pipeline in inner
+            .pipelines
+            .iter_mut()
+            .filter(|pipeline| pipeline.attachment_formats != Some(attachment_formats))
+        {
+            if let Err(error) = pipeline.compile(attachment_formats) {
+                diagnostics.extend(Diagnostic::from_error(
+                    &pipeline.name,
+                    &pipeline.config_path,
+                    error,
+                ));
+            }
+        }
+
+        match diagnostics.is_empty() {
+            true => Ok(()),
+            false => Err(Error::Diagnostics(diagnostics)),
+        }
+    }
+
+    /// This function enables the hot reload of the render pipelines. The pipeline configurations and the sources of
+    /// all shaders are watched, and changed pipelines are recompiled at the begin of the next frame. If the
+    /// recompilation fails, the error is logged and the old pipeline stays in use.
+    pub fn enable_hot_reload(&mut self) -> Result<()> {
+        let inner = unsafe { Arc::get_mut_unchecked(&mut self.0) };
+        let mut asset_watcher = AssetWatcher::new()?;
+        asset_watcher.watch_directory(Path::new(PIPELINES_DIRECTORY))?;
+        inner.asset_watcher = Some(asset_watcher);
+        inner.watch_shader_sources()?;
+        info!("Enabled hot reload of render pipelines");
         Ok(())
     }
 
-    pub fn begin(&mut self) -> Result<()> {
+    /// This function recompiles all pipelines, whose configuration or shaders have changed since the last call. The
+    /// device is only waited for, if at least one pipeline is affected.
+    fn reload_changed_pipelines(&mut self) -> Result<()> {
         let inner = unsafe { Arc::get_mut_unchecked(&mut self.0) };
-        inner.current_image_index = unsafe {
-            inner.swapchain_loader.acquire_next_image(
-                inner.swapchain.unwrap(),
-                u64::MAX,
-                inner.submit_semaphore,
-                vk::Fence::null(),
-            )
-        }?
-        .0;
+        let changed_paths = match inner.asset_watcher.as_mut() {
+            Some(asset_watcher) => asset_watcher.changed_paths(),
+            None => return Ok(()),
+        };
+
+        // Changed configurations replace the pipeline, changed shaders only recompile the pipeline. Renaming a
+        // configuration reports the old and the new path, so the pipeline of the old path is removed and the new
+        // path is loaded like a new configuration.
+        let pipelines_directory = fs::canonicalize(PIPELINES_DIRECTORY)?;
+        let (changed_configurations, removed_configurations): (Vec<PathBuf>, Vec<PathBuf>) =
+            changed_paths
+                .iter()
+                .filter(|path| path.parent() == Some(pipelines_directory.as_path()))
+                .filter(|path| config::is_pipeline_configuration(path))
+                .cloned()
+                .partition(|path| path.is_file());
+        let is_changed = |shader_modules: &[ShaderModule]| {
+            shader_modules
+                .iter()
+                .flat_map(|shader_module| shader_module.source_files())
+                .any(|path| changed_paths.contains(&watcher::normalize_path(path)))
+        };
+        let changed_pipelines = inner
+            .pipelines
+            .iter()
+            .enumerate()
+            .filter(|(_, pipeline)| is_changed(&pipeline.shader_modules))
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        let changed_compute_pipelines = inner
+            .compute_pipelines
+            .iter()
+            .enumerate()
+            .filter(|(_, pipeline)| is_changed(&pipeline.shader_modules))
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        if changed_configurations.is_empty()
+            && removed_configurations.is_empty()
+            && changed_pipelines.is_empty()
+            && changed_compute_pipelines.is_empty()
+        {
+            return Ok(());
+        }
+
+        // The old pipelines are destroyed after a successful compilation, so no frame in flight may use them
+        let device = inner.application.main_device().virtual_device();
+        unsafe { device.device_wait_idle() }?;
+        let attachment_formats = inner.attachment_formats();
+        for index in changed_pipelines {
+            let pipeline = &mut inner.pipelines[index];
+            info!("Recompile render pipeline '{}'", pipeline.name);
+            if let Err(error) = pipeline.compile(attachment_formats) {
+                error!(
+                    "Unable to recompile render pipeline '{}' => {}",
+                    pipeline.name, error
+                );
+            }
+        }
+
+        for index in changed_compute_pipelines {
+            let pipeline = &mut inner.compute_pipelines[index];
+            info!("Recompile compute pipeline '{}'", pipeline.name);
+            if let Err(error) = pipeline.compile() {
+                error!(
+                    "Unable to recompile compute pipeline '{}' => {}",
+                    pipeline.name, error
+                );
+            }
+        }
+
+        for config_file in removed_configurations {
+            info!(
+                "Remove pipelines of deleted configuration {:?}",
+                config_file
+            );
+            inner.remove_pipelines(&config_file);
+        }
+
+        for config_file in changed_configurations {
+            info!("Reload render pipeline from {:?}", config_file);
+            if let Err(error) = inner.load_pipeline(&config_file) {
+                error!(
+                    "Unable to reload render pipeline from {:?} => {}",
+                    config_file, error
+                );
+            }
+        }
+
+        // Reloaded configurations can reference shaders in directories, which aren't watched yet
+        inner.watch_shader_sources()
+    }
+
+    pub fn begin(&mut self) -> Result<()> {
+        if self.0.swapchain_config_changed || self.0.swapchain_suboptimal {
+            self.reload(false)?;
+        }
+        self.reload_changed_pipelines()?;
+
+        // Wait until the GPU has finished the last submission of this frame, so we can reuse its resources
+        {
+            let inner = unsafe { Arc::get_mut_unchecked(&mut self.0) };
+            let device = inner.application.main_device().virtual_device();
+            inner.frames[inner.current_frame].wait_and_retire(device)?;
+        }
 
+        // Offscreen images are bound to the frames in flight, so we only acquire an image when presenting. An out of
+        // date swapchain doesn't match the surface anymore, so it's recreated and the acquire is retried.
+        let (image_index, suboptimal) = loop {
+            let Some(swapchain) = self.0.swapchain else {
+                break (self.0.current_frame as u32, false);
+            };
+            match unsafe {
+                self.0.swapchain_loader.acquire_next_image(
+                    swapchain,
+                    u64::MAX,
+                    self.0.frame().acquire_semaphore,
+                    vk::Fence::null(),
+                )
+            } {
+                Ok(result) => break result,
+                Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => self.reload(false)?,
+                Err(error) => return Err(error.into()),
+            }
+        };
+
+        let inner = unsafe { Arc::get_mut_unchecked(&mut self.0) };
         let device = inner.application.main_device().virtual_device();
+        inner.current_image_index = image_index;
+
+        // A suboptimal image is still presentable, so the swapchain is recreated after the presentation of this frame
+        inner.swapchain_suboptimal |= suboptimal;
+        let frame = &mut inner.frames[inner.current_frame];
+        frame.acquire_pending = inner.swapchain.is_some();
+
         unsafe {
+            device.reset_fences(slice::from_ref(&frame.in_flight_fence))?;
             device.reset_command_pool(
-                inner.command_pool,
+                frame.command_pool,
                 vk::CommandPoolResetFlags::RELEASE_RESOURCES,
             )?;
             device.reset_command_buffer(
-                inner.command_buffer,
+                frame.command_buffer,
                 vk::CommandBufferResetFlags::RELEASE_RESOURCES,
             )?;
             device.begin_command_buffer(
-                inner.command_buffer,
+                frame.command_buffer,
                 &vk::CommandBufferBeginInfo::default(),
             )?;
         };
 
-        inner.application.main_device().memory_barrier(
-            inner.command_buffer,
-            inner.images[inner.current_image_index as usize],
+        // The content of the attachments is cleared, so the previous layout can be discarded
+        let image_index = inner.current_image_index as usize;
+        let main_device = inner.application.main_device();
+        main_device.memory_barrier(
+            frame.command_buffer,
+            inner.images[image_index],
             vk::ImageLayout::UNDEFINED,
             vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
-        );
+        )?;
+        main_device.image_barrier(
+            frame.command_buffer,
+            inner.depth_attachments[image_index].image,
+            attachment::depth_aspect_mask(inner.depth_format),
+            vk::ImageLayout::UNDEFINED,
+            vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL,
+        )?;
+        if let Some(multisample_attachment) = inner.multisample_attachments.get(image_index) {
+            main_device.memory_barrier(
+                frame.command_buffer,
+                multisample_attachment.image,
+                vk::ImageLayout::UNDEFINED,
+                vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+            )?;
+        }
         Ok(())
     }
 
+    /// This function begins the rendering and clears the current image with the specified sRGB encoded color. The
+    /// color is converted into linear space, if the render target is a sRGB image.
     pub fn clear_color(&self, red: f32, green: f32, blue: f32, alpha: f32) {
+        let mut clear_color = Vec3::new(red, green, blue);
+        if color::is_srgb_format(self.0.format) {
+            clear_color = color::srgb_to_linear(clear_color);
+        }
+        self.begin_rendering(
+            vk::AttachmentLoadOp::CLEAR,
+            [clear_color.x, clear_color.y, clear_color.z, alpha],
+        );
+    }
+
+    /// This function begins the dynamic rendering into the current image with the specified load operation. The load
+    /// operation is also used for the depth image, which is cleared to the far plane. If multisampling is enabled, the
+    /// renderer draws into the multisampled image and resolves it into the current image.
+    fn begin_rendering(&self, load_op: vk::AttachmentLoadOp, clear_color: [f32; 4]) {
         let inner = &self.0;
+        let image_index = inner.current_image_index as usize;
 
-        let rendering_attachment_info = vk::RenderingAttachmentInfo::default()
-            .image_view(inner.image_views[inner.current_image_index as usize])
+        let mut rendering_attachment_info = vk::RenderingAttachmentInfo::default()
+            .image_view(inner.image_views[image_index])
             .image_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
-            .load_op(vk::AttachmentLoadOp::CLEAR)
+            .load_op(load_op)
             .store_op(vk::AttachmentStoreOp::STORE)
             .clear_value(vk::ClearValue {
                 color: vk::ClearColorValue {
-                    float32: [red, green, blue, alpha],
+                    float32: clear_color,
+                },
+            });
+        if let Some(multisample_attachment) = inner.multisample_attachments.get(image_index) {
+            rendering_attachment_info = rendering_attachment_info
+                .image_view(multisample_attachment.image_view)
+                .resolve_mode(vk::ResolveModeFlags::AVERAGE)
+                .resolve_image_view(inner.image_views[image_index])
+                .resolve_image_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
+        }
+
+        let depth_attachment_info = vk::RenderingAttachmentInfo::default()
+            .image_view(inner.depth_attachments[image_index].image_view)
+            .image_layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL)
+            .load_op(load_op)
+            .store_op(vk::AttachmentStoreOp::STORE)
+            .clear_value(vk::ClearValue {
+                depth_stencil: vk::ClearDepthStencilValue {
+                    depth: 1.0,
+                    stencil: 0,
                 },
             });
 
-        let window_size = inner.application.window().inner_size();
         let rendering_info = vk::RenderingInfo::default()
             .layer_count(1)
             .render_area(vk::Rect2D {
                 offset: vk::Offset2D::default(),
-                extent: vk::Extent2D {
-                    width: window_size.width,
-                    height: window_size.height,
-                },
+                extent: inner.extent,
             })
-            .color_attachments(slice::from_ref(&rendering_attachment_info));
+            .color_attachments(slice::from_ref(&rendering_attachment_info))
+            .depth_attachment(&depth_attachment_info);
         unsafe {
             inner
                 .application
                 .main_device()
                 .virtual_device()
-                .cmd_begin_rendering(inner.command_buffer, &rendering_info);
+                .cmd_begin_rendering(inner.command_buffer(), &rendering_info);
         }
+        inner.rendering.set(true);
     }
 
+    /// This function draws all queued buffer builders. The queue is emptied also if an error occurs, so a broken buffer
+    /// builder isn't drawn again in the next frame.
     pub fn queue_buffer_builder(&mut self) -> Result<()> {
+        // Dequeue buffer builders
+        let queued_buffer_builder =
+            mem::take(&mut unsafe { Arc::get_mut_unchecked(&mut self.0) }.queued_buffer_builder);
+
         // Create groups of equal buffer builders
         let mut grouped_buffer_builders = Vec::new();
-        for buffer_builder in self.0.queued_buffer_builder.iter() {
+        for buffer_builder in queued_buffer_builder.iter() {
             // Push first buffer into grouped buffer builders list
             if grouped_buffer_builders.is_empty() {
                 grouped_buffer_builders.push(vec![buffer_builder.clone()]);
@@ -349,31 +1059,34 @@ impl GameRenderer {
         let mut grouped_buffers = Vec::new();
         for buffer_builder_group in grouped_buffer_builders {
             let (mut vertices, mut indices) = (Vec::new(), Vec::new());
-            let (vertex_format, topology, image, pipeline) = {
+            let (layout, attributes, stride, topology, image, pipeline) = {
                 let buffer_builder = buffer_builder_group.get(0).unwrap();
                 (
-                    buffer_builder.vertex_format,
+                    buffer_builder.layout,
+                    buffer_builder.attributes,
+                    buffer_builder.stride,
                     buffer_builder.topology,
                     buffer_builder.image.clone(),
                     buffer_builder.pipeline.clone(),
                 )
             };
 
-            // Fill buffer data
+            // Fill buffer data, the vertices are already prepared for the render target. The indices of every buffer
+            // builder start after the vertices of the previous buffer builders in the group.
             let mut vertex_count = 0;
-            let window_size = self.0.application.window().inner_size();
-            let window_size = vec2(window_size.width as f32, window_size.height as f32);
-
             for buffer_builder in buffer_builder_group {
-                for i in 0..=(buffer_builder.vertices.len() / buffer_builder.topology.vertex_count()) {
-                    indices.extend(topology.indices((i * buffer_builder.topology.vertex_count()) as u16));
+                let primitive_vertex_count = buffer_builder.topology.vertex_count();
+                for primitive in 0..(buffer_builder.vertex_count / primitive_vertex_count) {
+                    let first_vertex = vertex_count + primitive * primitive_vertex_count;
+                    indices.extend(topology.indices(first_vertex as u16));
                 }
+                vertices.extend_from_slice(&buffer_builder.vertex_data);
+                vertex_count += buffer_builder.vertex_count;
+            }
 
-                for mut vertex in buffer_builder.vertices.clone() {
-                    vertex.position = ((vertex.position * 2.0) - window_size) / window_size;
-                    vertex_format.extend_raw_data(&mut vertices, vertex);
-                    vertex_count += 1;
-                }
+            // Empty buffers can't be created, so groups without a complete primitive are skipped
+            if indices.is_empty() {
+                continue;
             }
 
             // Create vertex and index buffer
@@ -381,7 +1094,7 @@ impl GameRenderer {
                 Buffer::new(
                     app.clone(),
                     vk::BufferUsageFlags::VERTEX_BUFFER,
-                    (vertex_format.vertex_size() * vertex_count) as vk::DeviceSize,
+                    vertices.len() as vk::DeviceSize,
                     None,
                 )?,
                 Buffer::new(
@@ -393,121 +1106,334 @@ impl GameRenderer {
             );
 
             // Write buffer and push
+            vertex_buffer.set_name(&format!("{} (Vertex Buffer)", pipeline));
+            index_buffer.set_name(&format!("{} (Index Buffer)", pipeline));
             vertex_buffer.write_ptr(vertices.as_ptr(), vertices.len())?;
             index_buffer.write_ptr(indices.as_ptr(), indices.len())?;
-            grouped_buffers.push((vertex_buffer, index_buffer, image, pipeline));
+            grouped_buffers.push((
+                vertex_buffer,
+                index_buffer,
+                image,
+                pipeline,
+                layout,
+                attributes,
+                stride,
+            ));
         }
 
         // Bind and draw
-        for (vertex_buffer, index_buffer, image, pipeline) in grouped_buffers {
-            if let Some(image) = image {
-                let descriptor_set = DescriptorSet::allocate(&self, &pipeline, 0).unwrap();
-                image.write_to_set(&descriptor_set, 0);
-                self.bind_pipeline(
-                    self.find_pipeline(&pipeline).unwrap(),
-                    slice::from_ref(&descriptor_set),
-                );
-            } else {
-                self.bind_pipeline(self.find_pipeline(&pipeline).unwrap(), &[]);
-            }
+        for (vertex_buffer, index_buffer, image, pipeline, layout, attributes, stride) in
+            grouped_buffers
+        {
+            let render_pipeline = self
+                .find_pipeline(&pipeline)
+                .ok_or_else(|| Error::PipelineNotFound(pipeline.clone()))?;
+            let descriptor_set = match image {
+                Some(image) => {
+                    let descriptor_set = DescriptorSet::allocate(self, &pipeline, 0)?;
+                    image.write_to_set(&descriptor_set, 0);
+                    Some(descriptor_set)
+                }
+                None => None,
+            };
+            self.bind_pipeline_with_attributes(
+                render_pipeline,
+                layout,
+                attributes,
+                stride,
+                descriptor_set.as_slice(),
+            )?;
 
             self.bind_vertex_buffer(&vertex_buffer);
             self.draw_indexed(&index_buffer);
 
-            let buffer_cache = &mut unsafe { Arc::get_mut_unchecked(&mut self.0) }.buffer_cache;
-            buffer_cache.push(vertex_buffer);
-            buffer_cache.push(index_buffer);
+            // Keep the resources alive until the GPU has finished this frame
+            let inner = unsafe { Arc::get_mut_unchecked(&mut self.0) };
+            let frame = &mut inner.frames[inner.current_frame];
+            frame.buffer_cache.push(vertex_buffer);
+            frame.buffer_cache.push(index_buffer);
+            frame.descriptor_set_cache.extend(descriptor_set);
         }
-
-        // Dequeue already used buffer builder
-        unsafe { Arc::get_mut_unchecked(&mut self.0) }
-            .queued_buffer_builder
-            .clear();
         Ok(())
     }
 
     pub fn end(&mut self) -> Result<()> {
+        // Raise validation errors of this frame, if enabled
+        self.0.application.check_validation_errors();
+
         // Memory barrier
         let device = &self.0.application.main_device().virtual_device();
-        unsafe { device.cmd_end_rendering(self.0.command_buffer) };
+        let frame = self.0.frame();
+        if self.0.rendering.replace(false) {
+            unsafe { device.cmd_end_rendering(frame.command_buffer) };
+        }
+
+        // Offscreen images stay in the color attachment layout, because they are never presented
+        let Some(swapchain) = self.0.swapchain else {
+            unsafe { device.end_command_buffer(frame.command_buffer) }?;
+            let submit_info =
+                vk::SubmitInfo::default().command_buffers(slice::from_ref(&frame.command_buffer));
+            unsafe {
+                device.queue_submit(
+                    *self.0.application.main_device().graphics_queue(),
+                    slice::from_ref(&submit_info),
+                    frame.in_flight_fence,
+                )
+            }?;
+            return self.advance_frame();
+        };
+
         self.0.application.main_device().memory_barrier(
-            self.0.command_buffer,
+            frame.command_buffer,
             self.0.images[self.0.current_image_index as usize],
             vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
             vk::ImageLayout::PRESENT_SRC_KHR,
-        );
+        )?;
 
         // Move command buffer into executable state
-        unsafe { device.end_command_buffer(self.0.command_buffer) }?;
+        unsafe { device.end_command_buffer(frame.command_buffer) }?;
 
-        // Submit and present queued commands
+        // Submit and present queued commands, the fence signals when the frame's resources can be retired. The acquire
+        // semaphore was already waited on, if the frame was captured.
+        let present_semaphore = &self.0.present_semaphores[self.0.current_image_index as usize];
+        let wait_semaphores: &[vk::Semaphore] = if frame.acquire_pending {
+            slice::from_ref(&frame.acquire_semaphore)
+        } else {
+            &[]
+        };
+        let wait_stages: &[vk::PipelineStageFlags] = if frame.acquire_pending {
+            &[vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT]
+        } else {
+            &[]
+        };
         let submit_info = vk::SubmitInfo::default()
-            .wait_semaphores(slice::from_ref(&self.0.submit_semaphore))
-            .wait_dst_stage_mask(slice::from_ref(
-                &vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
-            ))
-            .command_buffers(slice::from_ref(&self.0.command_buffer))
-            .signal_semaphores(slice::from_ref(&self.0.present_semaphore));
+            .wait_semaphores(wait_semaphores)
+            .wait_dst_stage_mask(wait_stages)
+            .command_buffers(slice::from_ref(&frame.command_buffer))
+            .signal_semaphores(slice::from_ref(present_semaphore));
         unsafe {
             device.queue_submit(
-                *self.0.application.main_device().queue(),
+                *self.0.application.main_device().graphics_queue(),
                 slice::from_ref(&submit_info),
-                vk::Fence::null(),
+                frame.in_flight_fence,
             )
         }?;
 
         let present_info = vk::PresentInfoKHR::default()
             .image_indices(slice::from_ref(&self.0.current_image_index))
-            .wait_semaphores(slice::from_ref(&self.0.present_semaphore))
-            .swapchains(slice::from_ref(self.0.swapchain.as_ref().unwrap()));
+            .wait_semaphores(slice::from_ref(present_semaphore))
+            .swapchains(slice::from_ref(&swapchain));
 
         match unsafe {
-            self.0
-                .swapchain_loader
-                .queue_present(*self.0.application.main_device().queue(), &present_info)
+            self.0.swapchain_loader.queue_present(
+                *self.0.application.main_device().present_queue(),
+                &present_info,
+            )
         } {
-            Ok(_) => Ok(()),
-            Err(error) => {
-                if error == vk::Result::ERROR_OUT_OF_DATE_KHR {
+            // The swapchain doesn't match the surface anymore, so it's recreated before the next frame
+            Ok(suboptimal) => {
+                if suboptimal || self.0.swapchain_suboptimal {
                     self.reload(false)?;
-                    return Ok(());
                 }
-                Err(error)
             }
+            Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => self.reload(false)?,
+            Err(error) => return Err(error.into()),
         }
-        .unwrap();
+        self.advance_frame()
+    }
 
-        // Wait for finish operations
-        unsafe { device.device_wait_idle() }?;
+    /// This function copies the current image into a host-visible buffer and returns the pixels as RGBA image. It has
+    /// to be called after all draw calls of the frame and before [GameRenderer::end]. The already recorded commands
+    /// are submitted and the CPU waits for the GPU, so this should only be used for screenshots and tests. The pixels of
+    /// 8-bit, 10-bit and 16-bit float render targets are converted into RGBA, other formats return an error.
+    pub fn capture_frame(&mut self) -> Result<RgbaImage> {
+        let inner = unsafe { Arc::get_mut_unchecked(&mut self.0) };
+        if !inner.capturable {
+            return Err(Error::CaptureUnsupported);
+        }
+        let pixel_size = color::rgba8_pixel_size(inner.format)
+            .ok_or(Error::CaptureFormatUnsupported(inner.format))?;
 
-        // Clear buffer cache
-        unsafe { Arc::get_mut_unchecked(&mut self.0) }
-            .buffer_cache
-            .clear();
+        let main_device = inner.application.main_device();
+        let device = main_device.virtual_device();
+        let image = inner.images[inner.current_image_index as usize];
+        let extent = inner.extent;
+        let frame = &mut inner.frames[inner.current_frame];
+
+        // Copy image into the readback buffer
+        let readback_buffer = Buffer::new(
+            inner.application.clone(),
+            vk::BufferUsageFlags::TRANSFER_DST,
+            (extent.width * extent.height) as vk::DeviceSize * pixel_size as vk::DeviceSize,
+            None,
+        )?;
+        readback_buffer.set_name("Frame Capture Readback Buffer");
+        if inner.rendering.replace(false) {
+            unsafe { device.cmd_end_rendering(frame.command_buffer) };
+        }
+        main_device.memory_barrier(
+            frame.command_buffer,
+            image,
+            vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        )?;
+
+        let buffer_image_copy = vk::BufferImageCopy::default()
+            .image_extent(vk::Extent3D {
+                width: extent.width,
+                height: extent.height,
+                depth: 1,
+            })
+            .image_subresource(
+                vk::ImageSubresourceLayers::default()
+                    .aspect_mask(vk::ImageAspectFlags::COLOR)
+                    .layer_count(1),
+            );
+        unsafe {
+            device.cmd_copy_image_to_buffer(
+                frame.command_buffer,
+                image,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                readback_buffer.buffer,
+                slice::from_ref(&buffer_image_copy),
+            );
+        }
+
+        // The copied pixels have to be visible to the host, before they are read from the mapped buffer
+        main_device.global_memory_barrier(
+            frame.command_buffer,
+            (
+                vk::AccessFlags::TRANSFER_WRITE,
+                vk::PipelineStageFlags::TRANSFER,
+            ),
+            (vk::AccessFlags::HOST_READ, vk::PipelineStageFlags::HOST),
+        );
+        main_device.memory_barrier(
+            frame.command_buffer,
+            image,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+        )?;
+
+        // Submit the recorded commands and wait for them
+        let wait_semaphores: &[vk::Semaphore] = if frame.acquire_pending {
+            slice::from_ref(&frame.acquire_semaphore)
+        } else {
+            &[]
+        };
+        let wait_stages: &[vk::PipelineStageFlags] = if frame.acquire_pending {
+            &[vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT]
+        } else {
+            &[]
+        };
+        let submit_info = vk::SubmitInfo::default()
+            .wait_semaphores(wait_semaphores)
+            .wait_dst_stage_mask(wait_stages)
+            .command_buffers(slice::from_ref(&frame.command_buffer));
+        unsafe {
+            device.end_command_buffer(frame.command_buffer)?;
+            device.queue_submit(
+                *main_device.graphics_queue(),
+                slice::from_ref(&submit_info),
+                frame.in_flight_fence,
+            )?;
+            device.wait_for_fences(slice::from_ref(&frame.in_flight_fence), true, u64::MAX)?;
+            device.reset_fences(slice::from_ref(&frame.in_flight_fence))?;
+        }
+        frame.acquire_pending = false;
+
+        // Continue recording the frame, the rendering loads the already drawn content
+        unsafe {
+            device.reset_command_buffer(
+                frame.command_buffer,
+                vk::CommandBufferResetFlags::RELEASE_RESOURCES,
+            )?;
+            device.begin_command_buffer(
+                frame.command_buffer,
+                &vk::CommandBufferBeginInfo::default(),
+            )?;
+        }
+        self.begin_rendering(vk::AttachmentLoadOp::LOAD, [0.0; 4]);
+
+        // Convert pixels into RGBA, the format was already checked before the copy
+        let pixels = readback_buffer.read::<u8>((extent.width * extent.height) as usize * pixel_size)?;
+        let pixels = color::to_rgba8(self.0.format, &pixels)
+            .ok_or(Error::CaptureFormatUnsupported(self.0.format))?;
+        self.0.application.check_validation_errors();
+        let size = pixels.len();
+        RgbaImage::from_raw(extent.width, extent.height, pixels).ok_or(Error::CaptureSizeMismatch {
+            width: extent.width,
+            height: extent.height,
+            size,
+        })
+    }
+
+    /// This function captures the current image with [GameRenderer::capture_frame] and saves it as PNG file
+    pub fn capture_frame_to_file<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        self.capture_frame()?
+            .save_with_format(path, ImageFormat::Png)?;
+        info!("Captured frame into '{}'", path.display());
+        Ok(())
+    }
+
+    /// This function moves the renderer to the next frame in flight
+    #[inline]
+    fn advance_frame(&mut self) -> Result<()> {
+        let inner = unsafe { Arc::get_mut_unchecked(&mut self.0) };
+        inner.current_frame = (inner.current_frame + 1) % inner.frames.len();
         Ok(())
     }
 
-    pub fn bind_pipeline(&self, pipeline: &RenderPipeline, descriptor_sets: &[DescriptorSet]) {
+    /// This function binds the pipeline with the descriptor sets for drawing vertices with the layout `V`. The layout
+    /// is checked against the per-vertex inputs of the vertex shader before, so a mismatching layout fails with an
+    /// error instead of drawing garbage.
+    #[inline]
+    pub fn bind_pipeline<V: VertexLayout>(
+        &self,
+        pipeline: &RenderPipeline,
+        descriptor_sets: &[DescriptorSet],
+    ) -> Result<()> {
+        self.bind_pipeline_with_attributes(
+            pipeline,
+            any::type_name::<V>(),
+            V::ATTRIBUTES,
+            V::stride(),
+            descriptor_sets,
+        )
+    }
+
+    fn bind_pipeline_with_attributes(
+        &self,
+        pipeline: &RenderPipeline,
+        layout: &str,
+        attributes: &[VertexAttribute],
+        stride: u32,
+        descriptor_sets: &[DescriptorSet],
+    ) -> Result<()> {
+        pipeline.validate_attributes(layout, attributes, stride)?;
+
         let inner = &self.0;
         let device = inner.application.main_device().virtual_device();
-        let window_size = inner.application.window().inner_size();
+        let extent = inner.extent;
         unsafe {
             device.cmd_bind_pipeline(
-                inner.command_buffer,
+                inner.command_buffer(),
                 vk::PipelineBindPoint::GRAPHICS,
                 pipeline.vulkan_pipeline.unwrap(),
             );
 
             let viewport = vk::Viewport::default()
-                .width(window_size.width as f32)
-                .height(window_size.height as f32);
-            device.cmd_set_viewport(inner.command_buffer, 0, slice::from_ref(&viewport));
+                .width(extent.width as f32)
+                .height(extent.height as f32);
+            device.cmd_set_viewport(inner.command_buffer(), 0, slice::from_ref(&viewport));
 
-            let scissor = vk::Rect2D::default().extent(vk::Extent2D {
-                width: window_size.width,
-                height: window_size.height,
-            });
-            device.cmd_set_scissor(inner.command_buffer, 0, slice::from_ref(&scissor));
+            let scissor = vk::Rect2D::default().extent(extent);
+            device.cmd_set_scissor(inner.command_buffer(), 0, slice::from_ref(&scissor));
         }
 
         if !descriptor_sets.is_empty() {
@@ -517,7 +1443,7 @@ impl GameRenderer {
                 .collect::<Vec<_>>();
             unsafe {
                 device.cmd_bind_descriptor_sets(
-                    inner.command_buffer,
+                    inner.command_buffer(),
                     vk::PipelineBindPoint::GRAPHICS,
                     pipeline.vulkan_pipeline_layout.unwrap(),
                     0,
@@ -526,9 +1452,290 @@ impl GameRenderer {
                 );
             }
         }
+        Ok(())
+    }
+
+    /// This function writes the specified data into the push constants of the pipeline, which has to be bound. The data
+    /// is written at the begin of the push constant range, so its size has to match the size of the range.
+    pub fn push_constants<T: Pod>(&self, pipeline: &RenderPipeline, data: &T) -> Result<()> {
+        self.record_push_constants(
+            &pipeline.name,
+            pipeline.vulkan_pipeline_layout.unwrap(),
+            pipeline.push_constant_range,
+            bytemuck::bytes_of(data),
+        )
+    }
+
+    /// This function writes the specified data into the push constants of the compute pipeline, which are used by the
+    /// next [GameRenderer::dispatch] of the pipeline. The size of the data has to match the size of the range.
+    pub fn push_compute_constants<T: Pod>(
+        &self,
+        pipeline: &ComputePipeline,
+        data: &T,
+    ) -> Result<()> {
+        self.record_push_constants(
+            &pipeline.name,
+            pipeline.vulkan_pipeline_layout.unwrap(),
+            pipeline.push_constant_range,
+            bytemuck::bytes_of(data),
+        )
+    }
+
+    fn record_push_constants(
+        &self,
+        pipeline: &str,
+        layout: vk::PipelineLayout,
+        push_constant_range: Option<vk::PushConstantRange>,
+        bytes: &[u8],
+    ) -> Result<()> {
+        let push_constant_range =
+            push_constant_range.ok_or_else(|| Error::NoPushConstants(pipeline.to_string()))?;
+        if bytes.len() as u32 != push_constant_range.size {
+            return Err(Error::PushConstantSizeMismatch {
+                pipeline: pipeline.to_string(),
+                size: bytes.len() as u32,
+                expected: push_constant_range.size,
+            });
+        }
+
+        let inner = &self.0;
+        unsafe {
+            inner
+                .application
+                .main_device()
+                .virtual_device()
+                .cmd_push_constants(
+                    inner.command_buffer(),
+                    layout,
+                    push_constant_range.stage_flags,
+                    push_constant_range.offset,
+                    bytes,
+                );
+        }
+        Ok(())
+    }
+
+    /// This function dispatches the compute pipeline with the specified count of work groups. If the renderer is
+    /// rendering, the rendering is suspended for the dispatch and resumed afterwards with the already drawn content.
+    /// The barriers around the dispatch order it after the previous draw calls and dispatches and make its writes to
+    /// buffers and storage images visible to the following ones. The bound render pipeline stays bound.
+    pub fn dispatch(
+        &self,
+        pipeline: &ComputePipeline,
+        descriptor_sets: &[DescriptorSet],
+        group_count_x: u32,
+        group_count_y: u32,
+        group_count_z: u32,
+    ) {
+        let inner = &self.0;
+        let main_device = inner.application.main_device();
+        let device = main_device.virtual_device();
+        let command_buffer = inner.command_buffer();
+
+        // Barriers aren't allowed inside of the rendering
+        let rendering = inner.rendering.replace(false);
+        if rendering {
+            unsafe { device.cmd_end_rendering(command_buffer) };
+        }
+
+        // The dispatch is ordered after the shaders and the attachment writes of the previous draw calls. The geometry
+        // and tessellation stages are only allowed in barriers, if their features are enabled.
+        let mut shader_stages = vk::PipelineStageFlags::VERTEX_SHADER
+            | vk::PipelineStageFlags::FRAGMENT_SHADER
+            | vk::PipelineStageFlags::COMPUTE_SHADER;
+        let enabled_features = main_device.enabled_features();
+        if enabled_features.geometry_shader == vk::TRUE {
+            shader_stages |= vk::PipelineStageFlags::GEOMETRY_SHADER;
+        }
+        if enabled_features.tessellation_shader == vk::TRUE {
+            shader_stages |= vk::PipelineStageFlags::TESSELLATION_CONTROL_SHADER
+                | vk::PipelineStageFlags::TESSELLATION_EVALUATION_SHADER;
+        }
+        let attachment_stages = vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+            | vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS
+            | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS;
+        let attachment_writes = vk::AccessFlags::COLOR_ATTACHMENT_WRITE
+            | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE;
+        main_device.global_memory_barrier(
+            command_buffer,
+            (
+                vk::AccessFlags::SHADER_WRITE | attachment_writes,
+                shader_stages | vk::PipelineStageFlags::VERTEX_INPUT | attachment_stages,
+            ),
+            (
+                vk::AccessFlags::SHADER_READ | vk::AccessFlags::SHADER_WRITE,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+            ),
+        );
+
+        unsafe {
+            device.cmd_bind_pipeline(
+                command_buffer,
+                vk::PipelineBindPoint::COMPUTE,
+                pipeline.vulkan_pipeline.unwrap(),
+            );
+            if !descriptor_sets.is_empty() {
+                let raw_descriptor_sets = descriptor_sets
+                    .iter()
+                    .map(|value| value.vk_descriptor_set)
+                    .collect::<Vec<_>>();
+                device.cmd_bind_descriptor_sets(
+                    command_buffer,
+                    vk::PipelineBindPoint::COMPUTE,
+                    pipeline.vulkan_pipeline_layout.unwrap(),
+                    0,
+                    raw_descriptor_sets.as_slice(),
+                    &[],
+                );
+            }
+            device.cmd_dispatch(command_buffer, group_count_x, group_count_y, group_count_z);
+        }
+
+        // The results of the dispatch can be used as vertices, indices, indirect commands or in shaders
+        main_device.global_memory_barrier(
+            command_buffer,
+            (
+                vk::AccessFlags::SHADER_WRITE,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+            ),
+            (
+                vk::AccessFlags::VERTEX_ATTRIBUTE_READ
+                    | vk::AccessFlags::INDEX_READ
+                    | vk::AccessFlags::INDIRECT_COMMAND_READ
+                    | vk::AccessFlags::UNIFORM_READ
+                    | vk::AccessFlags::SHADER_READ
+                    | vk::AccessFlags::SHADER_WRITE,
+                shader_stages
+                    | vk::PipelineStageFlags::VERTEX_INPUT
+                    | vk::PipelineStageFlags::DRAW_INDIRECT,
+            ),
+        );
+
+        // The resumed rendering loads the attachments, so it has to wait for the attachment writes before the suspend
+        if rendering {
+            main_device.global_memory_barrier(
+                command_buffer,
+                (attachment_writes, attachment_stages),
+                (
+                    vk::AccessFlags::COLOR_ATTACHMENT_READ
+                        | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ
+                        | attachment_writes,
+                    attachment_stages,
+                ),
+            );
+            self.begin_rendering(vk::AttachmentLoadOp::LOAD, [0.0; 4]);
+        }
+    }
+
+    /// This function draws the instances of the batch with the pipeline of the batch. Every instance is a unit quad,
+    /// which is transformed by the per-instance attributes in the vertex shader. The size of the instances has to match
+    /// the per-instance inputs of the pipeline, the push constants of the batch are pushed after binding the pipeline.
+    ///
+    /// The instances are written into the instance buffer of the current frame, which grows as needed and is reused by
+    /// the following frames, so a batch can be drawn multiple times per frame.
+    pub fn draw_instances<T: Pod>(&mut self, batch: &InstanceBatch<T>) -> Result<()> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+
+        let pipeline_index = self
+            .0
+            .pipelines
+            .iter()
+            .position(|pipeline| pipeline.name == batch.pipeline)
+            .ok_or_else(|| Error::PipelineNotFound(batch.pipeline.clone()))?;
+        let instance_stride = self.0.pipelines[pipeline_index]
+            .instance_stride
+            .ok_or_else(|| Error::NoInstanceInputs(batch.pipeline.clone()))?;
+        if mem::size_of::<T>() as u32 != instance_stride {
+            return Err(Error::InstanceSizeMismatch {
+                pipeline: batch.pipeline.clone(),
+                size: mem::size_of::<T>() as u32,
+                expected: instance_stride,
+            });
+        }
+
+        let descriptor_set = match batch.image.as_ref() {
+            Some(image) => {
+                let descriptor_set = DescriptorSet::allocate(self, &batch.pipeline, 0)?;
+                image.write_to_set(&descriptor_set, 0);
+                Some(descriptor_set)
+            }
+            None => None,
+        };
+        let (instance_buffer, instance_offset) = self.write_instances(&batch.instances)?;
+
+        let render_pipeline = &self.0.pipelines[pipeline_index];
+        self.bind_pipeline::<QuadVertex>(render_pipeline, descriptor_set.as_slice())?;
+        if let Some(push_constants) = batch.push_constants.as_deref() {
+            self.record_push_constants(
+                &render_pipeline.name,
+                render_pipeline.vulkan_pipeline_layout.unwrap(),
+                render_pipeline.push_constant_range,
+                push_constants,
+            )?;
+        }
+        self.bind_buffer_to_binding(VERTEX_BINDING, self.0.quad_vertex_buffer.buffer, 0);
+        self.bind_buffer_to_binding(INSTANCE_BINDING, instance_buffer, instance_offset);
+        self.draw_indexed_instanced(&self.0.quad_index_buffer, batch.len() as u32);
+
+        // Keep the descriptor set alive until the GPU has finished this frame
+        let inner = unsafe { Arc::get_mut_unchecked(&mut self.0) };
+        inner.frames[inner.current_frame]
+            .descriptor_set_cache
+            .extend(descriptor_set);
+        Ok(())
+    }
+
+    /// This function writes the instances behind the instances already written in the current frame and returns the
+    /// instance buffer with the offset of the instances. If the instances don't fit, the buffer is replaced by a buffer
+    /// with at least twice the size. The old buffer is kept alive until the frame is finished, because the instances
+    /// drawn before still read from it.
+    fn write_instances<T: Pod>(&mut self, instances: &[T]) -> Result<(vk::Buffer, vk::DeviceSize)> {
+        let inner = unsafe { Arc::get_mut_unchecked(&mut self.0) };
+        let frame = &mut inner.frames[inner.current_frame];
+
+        // Vertex attributes have to be aligned to the size of their components
+        let size = mem::size_of_val(instances) as vk::DeviceSize;
+        let offset = frame.instance_offset.next_multiple_of(INSTANCE_ALIGNMENT);
+        let capacity = frame
+            .instance_buffer
+            .as_ref()
+            .map_or(0, |instance_buffer| instance_buffer.size);
+        let offset = match offset + size <= capacity {
+            true => offset,
+            false => {
+                let instance_buffer = Buffer::new(
+                    inner.application.clone(),
+                    vk::BufferUsageFlags::VERTEX_BUFFER,
+                    size.max(capacity * 2),
+                    None,
+                )?;
+                instance_buffer
+                    .set_name(&format!("Frame #{} (Instance Buffer)", inner.current_frame));
+                frame
+                    .buffer_cache
+                    .extend(frame.instance_buffer.replace(instance_buffer));
+                0
+            }
+        };
+
+        let instance_buffer = frame.instance_buffer.as_ref().unwrap();
+        instance_buffer.write_ptr_at(offset, instances.as_ptr(), instances.len())?;
+        frame.instance_offset = offset + size;
+        Ok((instance_buffer.buffer, offset))
     }
 
     pub fn bind_vertex_buffer(&self, buffer: &Buffer) {
+        self.bind_buffer_to_binding(VERTEX_BINDING, buffer.buffer, 0);
+    }
+
+    /// This function binds the buffer with the per-instance attributes of pipelines with an instance location
+    pub fn bind_instance_buffer(&self, buffer: &Buffer) {
+        self.bind_buffer_to_binding(INSTANCE_BINDING, buffer.buffer, 0);
+    }
+
+    fn bind_buffer_to_binding(&self, binding: u32, buffer: vk::Buffer, offset: vk::DeviceSize) {
         let inner = &self.0;
         unsafe {
             inner
@@ -536,10 +1743,10 @@ impl GameRenderer {
                 .main_device()
                 .virtual_device()
                 .cmd_bind_vertex_buffers(
-                    inner.command_buffer,
-                    0,
-                    slice::from_ref(&buffer.buffer),
-                    slice::from_ref(&vk::DeviceSize::from(0u32)),
+                    inner.command_buffer(),
+                    binding,
+                    slice::from_ref(&buffer),
+                    slice::from_ref(&offset),
                 );
         }
     }
@@ -548,30 +1755,83 @@ impl GameRenderer {
         let inner = &self.0;
         unsafe {
             inner.application.main_device().virtual_device().cmd_draw(
-                inner.command_buffer,
+                inner.command_buffer(),
                 vertices,
-                4,
                 1,
                 0,
+                0,
             );
         }
     }
 
+    #[inline]
     pub fn draw_indexed(&self, index_buffer: &Buffer) {
+        self.draw_indexed_instanced(index_buffer, 1);
+    }
+
+    /// This function draws the indices of the buffer for the specified count of instances. The per-instance attributes
+    /// are read from the buffer bound by [GameRenderer::bind_instance_buffer].
+    pub fn draw_indexed_instanced(&self, index_buffer: &Buffer, instances: u32) {
         let inner = &self.0;
         let device = inner.application.main_device().virtual_device();
-        let indices = (index_buffer.alloc_info.size / mem::size_of::<u16>() as u64) as u32;
+        let indices = (index_buffer.size / mem::size_of::<u16>() as u64) as u32;
         unsafe {
             device.cmd_bind_index_buffer(
-                inner.command_buffer,
+                inner.command_buffer(),
                 index_buffer.buffer,
                 vk::DeviceSize::from(0u32),
                 vk::IndexType::UINT16,
             );
-            device.cmd_draw_indexed(inner.command_buffer, indices, 1, 0, 0, 0);
+            device.cmd_draw_indexed(inner.command_buffer(), indices, instances, 0, 0, 0);
         }
     }
 
+    /// This function returns the size of the images, the renderer draws into
+    #[inline]
+    pub fn extent(&self) -> vk::Extent2D {
+        self.0.extent
+    }
+
+    /// This function returns the format of the images, the renderer draws into
+    #[inline]
+    pub fn format(&self) -> vk::Format {
+        self.0.format
+    }
+
+    #[inline]
+    pub fn swapchain_config(&self) -> SwapchainConfig {
+        self.0.swapchain_config
+    }
+
+    /// This function replaces the swapchain config. The swapchain is recreated with the new config at the begin of the
+    /// next frame, so this function can also be called while rendering.
+    pub fn set_swapchain_config(&mut self, config: SwapchainConfig) {
+        let inner = unsafe { Arc::get_mut_unchecked(&mut self.0) };
+        if inner.swapchain_config != config {
+            inner.swapchain_config = config;
+            inner.swapchain_config_changed = true;
+        }
+    }
+
+    /// This function compiles the specified pipeline and adds it to the renderer, so it can be drawn by its name like
+    /// the pipelines of the configurations. This is used for the variants created with
+    /// [RenderPipeline::with_specialization]. If the renderer already contains a render or compute pipeline with the
+    /// name, an error is returned, because the existing pipeline may still be used by the frames in flight and the
+    /// lookup by the name would be ambiguous. The pipeline is rebuilt, when the configuration of the pipeline it was
+    /// derived from is reloaded.
+    pub fn add_pipeline(&mut self, mut pipeline: RenderPipeline) -> Result<()> {
+        if self.find_pipeline(&pipeline.name).is_some()
+            || self.find_compute_pipeline(&pipeline.name).is_some()
+        {
+            return Err(Error::PipelineExists(pipeline.name));
+        }
+
+        let inner = unsafe { Arc::get_mut_unchecked(&mut self.0) };
+        pipeline.compile(inner.attachment_formats())?;
+        inner.pipelines.push(pipeline);
+        Ok(())
+    }
+
     #[inline]
     pub fn find_pipeline(&self, pipeline_name: &str) -> Option<&RenderPipeline> {
         self.0
@@ -579,4 +1839,12 @@ impl GameRenderer {
             .iter()
             .find(|pipeline| pipeline.name == pipeline_name)
     }
+
+    #[inline]
+    pub fn find_compute_pipeline(&self, pipeline_name: &str) -> Option<&ComputePipeline> {
+        self.0
+            .compute_pipelines
+            .iter()
+            .find(|pipeline| pipeline.name == pipeline_name)
+    }
 }
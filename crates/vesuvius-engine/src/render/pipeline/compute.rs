@@ -0,0 +1,153 @@
+use crate::render::pipeline::config::{PipelineConfiguration, PipelineKind};
+use crate::render::pipeline::shader::{ShaderKind, ShaderModule};
+use crate::render::pipeline::specialization::{self, Specialization};
+use crate::render::pipeline::{self, BindingTypes};
+use crate::App;
+use crate::Result;
+use ash::vk;
+use log::info;
+use std::path::PathBuf;
+use std::{mem, slice};
+
+/// This structure represents a compute pipeline, which is created from a pipeline configuration with the kind
+/// `compute`. Like the render pipeline, the compute pipeline is re-compilable, when the source code or the
+/// configuration file changes. The pipeline is dispatched with [crate::render::GameRenderer::dispatch].
+pub struct ComputePipeline {
+    pub(crate) shader_modules: Vec<ShaderModule>,
+    application: App,
+    pipeline_cache: vk::PipelineCache,
+    specialization: Specialization,
+    pub(crate) config_path: PathBuf,
+    pub(crate) vulkan_pipeline_layout: Option<vk::PipelineLayout>,
+    pub(crate) push_constant_range: Option<vk::PushConstantRange>,
+    pub(crate) descriptor_set_layouts: Option<Vec<(vk::DescriptorSetLayout, BindingTypes)>>,
+    pub(crate) vulkan_pipeline: Option<vk::Pipeline>,
+    pub(crate) name: String,
+}
+
+impl Drop for ComputePipeline {
+    fn drop(&mut self) {
+        pipeline::destroy_handles(
+            &self.application,
+            self.descriptor_set_layouts.as_deref(),
+            self.vulkan_pipeline_layout,
+            self.vulkan_pipeline,
+        );
+    }
+}
+
+impl ComputePipeline {
+    /// This function creates the compute pipeline or the specified variant of the compute pipeline from the
+    /// configuration. The shader is compiled with the defines of the pipeline and the variant.
+    pub(crate) fn new(
+        application: App,
+        pipeline_cache: vk::PipelineCache,
+        pipeline_config: &PipelineConfiguration,
+        variant: Option<&str>,
+    ) -> Result<Self> {
+        let (name, defines) = pipeline_config.variant(variant)?;
+        let shader_modules =
+            pipeline::create_shader_modules(&application, pipeline_config, &name, &defines)?;
+        info!("Internally created '{}' compute pipeline", name);
+
+        Ok(Self {
+            application,
+            pipeline_cache,
+            specialization: pipeline_config.specialization.clone(),
+            config_path: pipeline_config.path.clone(),
+            shader_modules,
+            descriptor_set_layouts: None,
+            vulkan_pipeline_layout: None,
+            push_constant_range: None,
+            vulkan_pipeline: None,
+            name,
+        })
+    }
+
+    /// This function compiles the shader and (re)creates the pipeline. Like [pipeline::RenderPipeline::compile], the
+    /// new pipeline is built next to the old one, so the old pipeline stays usable, if the compilation fails. On
+    /// success the handles of the old pipeline are destroyed, so the caller has to ensure, that the GPU doesn't use
+    /// them anymore.
+    pub fn compile(&mut self) -> Result<()> {
+        let mut pipeline = Self {
+            application: self.application.clone(),
+            pipeline_cache: self.pipeline_cache,
+            specialization: self.specialization.clone(),
+            config_path: self.config_path.clone(),
+            shader_modules: pipeline::uncompiled_shader_modules(&self.shader_modules),
+            descriptor_set_layouts: None,
+            vulkan_pipeline_layout: None,
+            push_constant_range: None,
+            vulkan_pipeline: None,
+            name: self.name.clone(),
+        };
+
+        // The partially created pipeline destroys its handles, when it's dropped because of an error
+        pipeline.create_handles()?;
+        mem::swap(self, &mut pipeline);
+        Ok(())
+    }
+
+    /// This function compiles the shader and creates the handles of this pipeline. Every handle is stored directly
+    /// after its creation, so the handles are destroyed by [Drop] if a later step fails.
+    fn create_handles(&mut self) -> Result<()> {
+        let reflections = pipeline::compile_shaders(&self.name, &mut self.shader_modules)?;
+        pipeline::validate_interface(
+            &self.name,
+            &self.config_path,
+            PipelineKind::Compute,
+            None,
+            None,
+            &reflections,
+        )?;
+        let specialization_data = specialization::specialize(
+            &self.name,
+            &self.config_path,
+            &self.specialization,
+            &reflections,
+        )?;
+
+        let descriptor_set_layouts = self.descriptor_set_layouts.insert(Vec::new());
+        let (layout, push_constant_range) = pipeline::create_pipeline_layout(
+            &self.application,
+            &self.name,
+            &reflections,
+            descriptor_set_layouts,
+        )?;
+        self.vulkan_pipeline_layout = Some(layout);
+        self.push_constant_range = push_constant_range;
+
+        // The existence of exactly one compute shader is validated before
+        let (compute_shader, specialization_data) = self
+            .shader_modules
+            .iter()
+            .zip(specialization_data.iter())
+            .find(|(shader_module, _)| shader_module.kind == ShaderKind::Compute)
+            .unwrap();
+        let specialization_info = specialization_data.info();
+        let compute_pipeline_create_info = vk::ComputePipelineCreateInfo::default()
+            .stage(
+                vk::PipelineShaderStageCreateInfo::from(compute_shader)
+                    .specialization_info(&specialization_info),
+            )
+            .base_pipeline_handle(vk::Pipeline::null())
+            .layout(layout);
+
+        let device = self.application.main_device().virtual_device();
+        let pipeline = unsafe {
+            device.create_compute_pipelines(
+                self.pipeline_cache,
+                slice::from_ref(&compute_pipeline_create_info),
+                None,
+            )
+        }
+        .map_err(|(_, error)| error)?[0];
+        self.vulkan_pipeline = Some(pipeline);
+
+        // Name the pipeline, so validation messages reference the pipeline by name
+        self.application
+            .main_device()
+            .set_object_name(pipeline, &self.name);
+        Ok(())
+    }
+}
@@ -1,14 +1,376 @@
+use crate::error::Error;
+use crate::render::buffer::format::VertexFormat;
+use crate::render::pipeline::diagnostic::Diagnostic;
 use crate::render::pipeline::shader::ShaderKind;
+use crate::render::pipeline::specialization::Specialization;
+use crate::Result;
+use ash::vk;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::{fs, iter};
 
+/// This structure represents a shader of the pipeline. The resource is a GLSL source, a HLSL source with the extension
+/// `.hlsl` or a precompiled SPIR-V binary with the extension `.spv`.
 #[derive(Serialize, Deserialize)]
 pub(crate) struct ShaderConfiguration {
     pub(crate) resource: String,
     pub(crate) kind: ShaderKind,
+
+    /// The name of the function, which is executed by the shader stage. This is `main` by default.
+    #[serde(default)]
+    pub(crate) entry_point: Option<String>,
 }
 
+/// The preprocessor macros of a pipeline by their name. An empty value defines the macro without a value.
+pub(crate) type Defines = BTreeMap<String, String>;
+
 #[derive(Serialize, Deserialize)]
 pub(crate) struct PipelineConfiguration {
     pub(crate) name: String,
     pub(crate) shader: Vec<ShaderConfiguration>,
+
+    /// The kind of the pipeline. Compute pipelines consist of a single compute shader and ignore the fixed-function
+    /// state and the vertex format.
+    #[serde(default)]
+    pub(crate) kind: PipelineKind,
+
+    /// The path of the configuration file, which is set after reading the configuration
+    #[serde(skip)]
+    pub(crate) path: PathBuf,
+
+    /// The vertex format of the buffers drawn with the pipeline. If this is specified, the inputs of the vertex shader
+    /// are validated against the attributes of the vertex format while compiling the pipeline.
+    #[serde(default)]
+    pub(crate) vertex_format: Option<VertexFormat>,
+
+    /// The first location of the per-instance inputs of the vertex shader. The inputs at this and all following
+    /// locations are read from the instance buffer, which is bound in addition to the vertex buffer.
+    #[serde(default)]
+    pub(crate) instance_location: Option<u32>,
+
+    /// The preprocessor macros, which are defined in all shaders of the pipeline
+    #[serde(default)]
+    pub(crate) defines: Defines,
+
+    /// The variants of the pipeline by their name. Every variant is compiled as own pipeline with the name
+    /// `<name>_<variant>` and the defines of the variant in addition to the defines of the pipeline.
+    #[serde(default)]
+    pub(crate) variants: BTreeMap<String, Defines>,
+
+    /// The values of the specialization constants by the name or the constant ID of the constant. The values are
+    /// applied to the constants of every shader of the pipeline, which declares a constant with this name.
+    #[serde(default)]
+    pub(crate) specialization: Specialization,
+
+    #[serde(flatten)]
+    pub(crate) state: PipelineState,
+}
+
+impl PipelineConfiguration {
+    /// This function reads the configuration from the specified file. If the file isn't valid, the error is returned as
+    /// diagnostic with the location in the file.
+    pub(crate) fn from_file(config_file: &Path) -> Result<Self> {
+        let file_content = String::from_utf8(fs::read(config_file)?)?;
+        let mut pipeline_config: Self = serde_json::from_str(&file_content).map_err(|error| {
+            Error::Diagnostics(vec![Diagnostic::from_json_error(
+                &config_file_name(config_file),
+                config_file,
+                &error,
+            )])
+        })?;
+        pipeline_config.path = config_file.to_path_buf();
+        Ok(pipeline_config)
+    }
+
+    /// This function returns the pipeline itself (none) and the names of all variants. Every one of them is compiled
+    /// as own pipeline.
+    pub(crate) fn variant_names(&self) -> impl Iterator<Item = Option<&str>> {
+        iter::once(None).chain(self.variants.keys().map(|variant| Some(variant.as_str())))
+    }
+
+    /// This function returns the name and the defines of the specified variant or of the pipeline itself, if no
+    /// variant is specified.
+    pub(crate) fn variant(&self, variant: Option<&str>) -> Result<(String, Defines)> {
+        let Some(variant) = variant else {
+            return Ok((self.name.clone(), self.defines.clone()));
+        };
+
+        let variant_defines = self
+            .variants
+            .get(variant)
+            .ok_or_else(|| Error::VariantNotFound {
+                pipeline: self.name.clone(),
+                variant: variant.to_string(),
+            })?;
+        let mut defines = self.defines.clone();
+        defines.extend(variant_defines.clone());
+        Ok((self.variant_name(Some(variant)), defines))
+    }
+
+    /// This function returns the name of the pipeline, which is created for the specified variant
+    pub(crate) fn variant_name(&self, variant: Option<&str>) -> String {
+        match variant {
+            Some(variant) => format!("{}_{}", self.name, variant),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// This function returns true, if the specified file is a pipeline configuration
+pub(crate) fn is_pipeline_configuration(path: &Path) -> bool {
+    path.extension().and_then(|extension| extension.to_str()) == Some("json")
+}
+
+/// This function returns the name of the configuration file without extension. It's used as pipeline name in
+/// diagnostics, when the name can't be read from the configuration.
+pub(crate) fn config_file_name(config_file: &Path) -> String {
+    config_file
+        .file_stem()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned())
+}
+
+/// This enum represents the kind of a pipeline, which decides about the shaders of the pipeline and how it's used by
+/// the renderer.
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum PipelineKind {
+    /// The pipeline draws into the attachments of the renderer with a vertex and a fragment shader
+    #[default]
+    Graphics,
+
+    /// The pipeline is dispatched with a single compute shader outside of the rendering
+    Compute,
+}
+
+/// This structure represents the fixed-function state of a render pipeline. All fields are optional in the
+/// configuration file, the defaults are the state of the 2D renderer.
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub(crate) struct PipelineState {
+    #[serde(default)]
+    pub(crate) blend: BlendMode,
+
+    #[serde(default)]
+    pub(crate) topology: Topology,
+
+    #[serde(default)]
+    pub(crate) cull_mode: CullMode,
+
+    #[serde(default)]
+    pub(crate) front_face: FrontFace,
+
+    #[serde(default)]
+    pub(crate) polygon_mode: PolygonMode,
+
+    #[serde(default)]
+    pub(crate) depth: DepthConfiguration,
+
+    /// The count of control points per patch. This is required by pipelines with tessellation shaders, which use the
+    /// topology `patch_list`.
+    #[serde(default)]
+    pub(crate) patch_control_points: Option<u32>,
+
+    /// The sample count of the pipeline. If this isn't specified, the pipeline uses the sample count of the renderer.
+    /// Otherwise the compilation fails, when the sample count of the renderer is different.
+    #[serde(default)]
+    pub(crate) samples: Option<u32>,
+}
+
+/// This enum represents the blending of the fragment color with the color in the color attachment
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum BlendMode {
+    /// The color is blended by the alpha of the fragment
+    #[default]
+    Alpha,
+
+    /// The color multiplied with the alpha of the fragment is added to the color in the attachment
+    Additive,
+
+    /// The color is already multiplied with the alpha of the fragment
+    Premultiplied,
+
+    /// The color replaces the color in the attachment
+    None,
+}
+
+impl BlendMode {
+    pub(crate) fn attachment_state(self) -> vk::PipelineColorBlendAttachmentState {
+        let attachment_state = vk::PipelineColorBlendAttachmentState::default()
+            .color_write_mask(vk::ColorComponentFlags::RGBA)
+            .color_blend_op(vk::BlendOp::ADD)
+            .alpha_blend_op(vk::BlendOp::ADD);
+        let (src_color_blend_factor, dst_color_blend_factor, src_alpha_blend_factor) = match self {
+            Self::Alpha => (
+                vk::BlendFactor::SRC_ALPHA,
+                vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+                vk::BlendFactor::ONE,
+            ),
+            Self::Additive => (
+                vk::BlendFactor::SRC_ALPHA,
+                vk::BlendFactor::ONE,
+                vk::BlendFactor::ONE,
+            ),
+            Self::Premultiplied => (
+                vk::BlendFactor::ONE,
+                vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+                vk::BlendFactor::ONE,
+            ),
+            Self::None => return attachment_state.blend_enable(false),
+        };
+
+        attachment_state
+            .src_color_blend_factor(src_color_blend_factor)
+            .dst_color_blend_factor(dst_color_blend_factor)
+            .src_alpha_blend_factor(src_alpha_blend_factor)
+            .dst_alpha_blend_factor(vk::BlendFactor::ZERO)
+            .blend_enable(true)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Topology {
+    PointList,
+    LineList,
+    LineStrip,
+    #[default]
+    TriangleList,
+    TriangleStrip,
+    TriangleFan,
+
+    /// The vertices are grouped into patches, which are processed by the tessellation shaders
+    PatchList,
+}
+
+impl From<Topology> for vk::PrimitiveTopology {
+    #[inline]
+    fn from(value: Topology) -> Self {
+        match value {
+            Topology::PointList => Self::POINT_LIST,
+            Topology::LineList => Self::LINE_LIST,
+            Topology::LineStrip => Self::LINE_STRIP,
+            Topology::TriangleList => Self::TRIANGLE_LIST,
+            Topology::TriangleStrip => Self::TRIANGLE_STRIP,
+            Topology::TriangleFan => Self::TRIANGLE_FAN,
+            Topology::PatchList => Self::PATCH_LIST,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum CullMode {
+    #[default]
+    None,
+    Front,
+    Back,
+    FrontAndBack,
+}
+
+impl From<CullMode> for vk::CullModeFlags {
+    #[inline]
+    fn from(value: CullMode) -> Self {
+        match value {
+            CullMode::None => Self::NONE,
+            CullMode::Front => Self::FRONT,
+            CullMode::Back => Self::BACK,
+            CullMode::FrontAndBack => Self::FRONT_AND_BACK,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum FrontFace {
+    #[default]
+    Clockwise,
+    CounterClockwise,
+}
+
+impl From<FrontFace> for vk::FrontFace {
+    #[inline]
+    fn from(value: FrontFace) -> Self {
+        match value {
+            FrontFace::Clockwise => Self::CLOCKWISE,
+            FrontFace::CounterClockwise => Self::COUNTER_CLOCKWISE,
+        }
+    }
+}
+
+/// This enum represents the rasterization of the polygons. Other modes than fill require the fillModeNonSolid feature
+/// of the device.
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum PolygonMode {
+    #[default]
+    Fill,
+    Line,
+    Point,
+}
+
+impl From<PolygonMode> for vk::PolygonMode {
+    #[inline]
+    fn from(value: PolygonMode) -> Self {
+        match value {
+            PolygonMode::Fill => Self::FILL,
+            PolygonMode::Line => Self::LINE,
+            PolygonMode::Point => Self::POINT,
+        }
+    }
+}
+
+/// This structure represents the depth test of the pipeline. The depth test is disabled by default, so the draw order
+/// decides about the visibility like in the 2D renderer.
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub(crate) struct DepthConfiguration {
+    #[serde(default)]
+    pub(crate) test: bool,
+
+    #[serde(default)]
+    pub(crate) write: bool,
+
+    #[serde(default)]
+    pub(crate) compare: CompareOp,
+}
+
+impl DepthConfiguration {
+    pub(crate) fn stencil_state(self) -> vk::PipelineDepthStencilStateCreateInfo<'static> {
+        vk::PipelineDepthStencilStateCreateInfo::default()
+            .depth_test_enable(self.test)
+            .depth_write_enable(self.write)
+            .depth_compare_op(self.compare.into())
+            .depth_bounds_test_enable(false)
+            .stencil_test_enable(false)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum CompareOp {
+    Never,
+    #[default]
+    Less,
+    Equal,
+    LessOrEqual,
+    Greater,
+    NotEqual,
+    GreaterOrEqual,
+    Always,
+}
+
+impl From<CompareOp> for vk::CompareOp {
+    #[inline]
+    fn from(value: CompareOp) -> Self {
+        match value {
+            CompareOp::Never => Self::NEVER,
+            CompareOp::Less => Self::LESS,
+            CompareOp::Equal => Self::EQUAL,
+            CompareOp::LessOrEqual => Self::LESS_OR_EQUAL,
+            CompareOp::Greater => Self::GREATER,
+            CompareOp::NotEqual => Self::NOT_EQUAL,
+            CompareOp::GreaterOrEqual => Self::GREATER_OR_EQUAL,
+            CompareOp::Always => Self::ALWAYS,
+        }
+    }
 }
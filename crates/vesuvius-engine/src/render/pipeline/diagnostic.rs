@@ -0,0 +1,150 @@
+use crate::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// This structure represents a single problem found while loading or compiling a render pipeline. The diagnostics of
+/// all pipelines are collected while reloading the renderer, so every broken asset is reported at once.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Diagnostic {
+    /// The name of the pipeline or font, or the name of the configuration file if the configuration can't be parsed
+    pub pipeline: String,
+
+    /// The file, which contains the problem. This is an included file, if the error is located in it.
+    pub file: PathBuf,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub message: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "[{}] {}", self.pipeline, self.file.display())?;
+        if let Some(line) = self.line {
+            write!(formatter, ":{}", line)?;
+            if let Some(column) = self.column {
+                write!(formatter, ":{}", column)?;
+            }
+        }
+        write!(formatter, ": {}", self.message)
+    }
+}
+
+impl Diagnostic {
+    pub(crate) fn new(pipeline: &str, file: &Path, message: impl Into<String>) -> Self {
+        Self {
+            pipeline: pipeline.to_string(),
+            file: file.to_path_buf(),
+            line: None,
+            column: None,
+            message: message.into(),
+        }
+    }
+
+    /// This function returns the diagnostic with the specified location in the file
+    pub(crate) fn at(mut self, line: u32, column: Option<u32>) -> Self {
+        self.line = Some(line);
+        self.column = column;
+        self
+    }
+
+    /// This function converts the specified error into diagnostics of the pipeline. Errors, which are already
+    /// diagnostics, are returned as they are and the messages of the shader compiler are split into their locations.
+    /// Every other error becomes a single diagnostic for the specified file.
+    pub(crate) fn from_error(pipeline: &str, file: &Path, error: Error) -> Vec<Self> {
+        match error {
+            Error::Diagnostics(diagnostics) => diagnostics,
+            #[cfg(feature = "shaderc")]
+            Error::ShaderCompiler(shaderc::Error::CompilationError(_, messages)) => {
+                Self::from_compiler_messages(pipeline, file, &messages)
+            }
+            error => vec![Self::new(pipeline, file, error.to_string())],
+        }
+    }
+
+    /// This function converts the error of a JSON file into a diagnostic. The location is removed from the message,
+    /// because the diagnostic reports it.
+    pub(crate) fn from_json_error(name: &str, file: &Path, error: &serde_json::Error) -> Self {
+        let location = format!(" at line {} column {}", error.line(), error.column());
+        let message = error.to_string();
+        Self::new(
+            name,
+            file,
+            message.strip_suffix(&location).unwrap_or(&message),
+        )
+        .at(error.line() as u32, Some(error.column() as u32))
+    }
+
+    /// This function parses the messages of the shader compiler into diagnostics. The compiler reports every error in
+    /// a separate line like `<file>:<line>: error: <message>`, lines without this structure (like the summary of the
+    /// error count) are ignored. Warnings are ignored too, because they don't break the pipeline and would be counted
+    /// as problems. If no line can be parsed, the complete message becomes a single diagnostic.
+    #[cfg(feature = "shaderc")]
+    pub(crate) fn from_compiler_messages(pipeline: &str, file: &Path, messages: &str) -> Vec<Self> {
+        let diagnostics = messages
+            .lines()
+            .filter_map(|line| {
+                let (location, message) = line.split_once(": error: ")?;
+
+                // The location is the file with an optional line and column number, the numbers are split from the
+                // end, because the file name can contain colons on Windows.
+                let (mut location_file, mut numbers) = (location, Vec::new());
+                while numbers.len() < 2 {
+                    let Some((file, number)) = location_file.rsplit_once(':') else {
+                        break;
+                    };
+                    let Ok(number) = number.trim().parse::<u32>() else {
+                        break;
+                    };
+                    numbers.insert(0, number);
+                    location_file = file;
+                }
+
+                let diagnostic = Self::new(pipeline, Path::new(location_file), message.trim());
+                Some(match numbers.as_slice() {
+                    [] => diagnostic,
+                    [line] => diagnostic.at(*line, None),
+                    [line, column, ..] => diagnostic.at(*line, Some(*column)),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        match diagnostics.is_empty() {
+            true => vec![Self::new(pipeline, file, messages.trim())],
+            false => diagnostics,
+        }
+    }
+}
+
+/// This function formats the specified diagnostics with one diagnostic per line
+pub(crate) fn format_diagnostics(diagnostics: &[Diagnostic]) -> String {
+    diagnostics
+        .iter()
+        .map(|diagnostic| format!("\n  {}", diagnostic))
+        .collect()
+}
+
+#[cfg(all(test, feature = "shaderc"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compiler_messages_without_warnings() {
+        let messages = [
+            "shader/quad.vertex.glsl:3: warning: '#extension' : extension not supported",
+            "shader/quad.vertex.glsl:7:12: error: 'position' : undeclared identifier",
+            "1 error generated.",
+        ]
+        .join("\n");
+        let diagnostics =
+            Diagnostic::from_compiler_messages("quad", Path::new("quad.json"), &messages);
+        assert_eq!(
+            diagnostics,
+            [Diagnostic::new(
+                "quad",
+                Path::new("shader/quad.vertex.glsl"),
+                "'position' : undeclared identifier"
+            )
+            .at(7, Some(12))]
+        );
+    }
+}
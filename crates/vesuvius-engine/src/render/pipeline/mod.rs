@@ -1,95 +1,290 @@
+pub mod compute;
 pub mod config;
+pub mod diagnostic;
 pub mod shader;
+pub mod specialization;
 
+use crate::error::Error;
+use crate::render::buffer::format::VertexFormat;
+use crate::render::buffer::layout::{VertexAttribute, VertexLayout};
 use crate::render::buffer::Buffer;
 use crate::render::image::Image;
-use crate::render::pipeline::config::PipelineConfiguration;
-use crate::render::pipeline::shader::{ShaderKind, ShaderModule};
+use crate::render::pipeline::config::{
+    Defines, PipelineConfiguration, PipelineKind, PipelineState, PolygonMode, Topology,
+};
+use crate::render::pipeline::diagnostic::Diagnostic;
+use crate::render::pipeline::shader::{self, ShaderKind, ShaderModule, ShaderReflection};
+use crate::render::pipeline::specialization::{Specialization, SpecializationValue};
 use crate::render::GameRenderer;
 use crate::App;
 use crate::Result;
 use ash::vk;
 use log::info;
-use std::path::PathBuf;
-use std::slice;
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::ffi::CString;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::{any, mem, slice};
+
+/// The maximal count of control points per patch, which every device with tessellation support provides at least. The
+/// assets are validated against this limit without a device.
+pub(crate) const GUARANTEED_TESSELLATION_PATCH_SIZE: u32 = 32;
+
+/// The descriptor types of a descriptor set layout by the binding number
+pub(crate) type BindingTypes = BTreeMap<u32, vk::DescriptorType>;
+
+/// The count of color attachments, the renderer draws into. The fragment shaders have to write exactly these outputs.
+const COLOR_ATTACHMENT_COUNT: u32 = 1;
+
+/// The vertex input binding of the per-vertex attributes
+pub(crate) const VERTEX_BINDING: u32 = 0;
+
+/// The vertex input binding of the per-instance attributes, which is used by pipelines with an instance location
+pub(crate) const INSTANCE_BINDING: u32 = 1;
+
+/// This structure describes the attachments, the renderer draws into. The pipelines have to be recompiled, when one of
+/// these formats changes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AttachmentFormats {
+    pub color_format: vk::Format,
+    pub depth_format: vk::Format,
+    pub samples: vk::SampleCountFlags,
+}
 
 /// This structure represents a render pipeline. The complete pipeline is re-compilable, when the
 /// source code or the configuration file changes. The re-compilation feature is used by the file
 /// watcher in the Game Renderer.
 #[derive(Clone)]
 pub struct RenderPipeline {
-    shader_modules: Vec<ShaderModule>,
+    pub(crate) shader_modules: Vec<ShaderModule>,
     application: App,
+    pipeline_cache: vk::PipelineCache,
+    state: PipelineState,
+    vertex_format: Option<VertexFormat>,
+    instance_location: Option<u32>,
+    specialization: Specialization,
+    pub(crate) config_path: PathBuf,
+
+    /// The name of the pipeline and the constants, this pipeline was created from by
+    /// [RenderPipeline::with_specialization]. The pipeline is rebuilt from these, when the configuration is reloaded.
+    pub(crate) derived_from: Option<(String, Specialization)>,
+
+    /// The formats of the attachments, the pipeline was compiled for the last time
+    pub(crate) attachment_formats: Option<AttachmentFormats>,
+
+    /// The per-vertex attributes of the vertex input ordered by their location and the size of a single vertex
+    vertex_attributes: Vec<vk::VertexInputAttributeDescription>,
+    vertex_stride: u32,
+
+    /// The size of a single instance in bytes, if the vertex shader has per-instance inputs
+    pub(crate) instance_stride: Option<u32>,
     pub(crate) vulkan_pipeline_layout: Option<vk::PipelineLayout>,
-    descriptor_set_layouts: Option<Vec<(vk::DescriptorSetLayout, Vec<vk::DescriptorType>)>>,
+    pub(crate) push_constant_range: Option<vk::PushConstantRange>,
+    descriptor_set_layouts: Option<Vec<(vk::DescriptorSetLayout, BindingTypes)>>,
     pub(crate) vulkan_pipeline: Option<vk::Pipeline>,
     pub(crate) name: String,
 }
 
 impl Drop for RenderPipeline {
     fn drop(&mut self) {
-        let device = self.application.main_device().virtual_device();
-        if let Some(descriptor_set_layouts) = self.descriptor_set_layouts.as_ref() {
-            for descriptor_set_layout in descriptor_set_layouts {
-                unsafe { device.destroy_descriptor_set_layout(descriptor_set_layout.0, None) };
-            }
-        }
-
-        if let Some(vulkan_pipeline_layout) = self.vulkan_pipeline_layout {
-            unsafe { device.destroy_pipeline_layout(vulkan_pipeline_layout, None) };
-        }
-
-        if let Some(vulkan_pipeline) = self.vulkan_pipeline {
-            unsafe { device.destroy_pipeline(vulkan_pipeline, None) };
-        }
+        destroy_handles(
+            &self.application,
+            self.descriptor_set_layouts.as_deref(),
+            self.vulkan_pipeline_layout,
+            self.vulkan_pipeline,
+        );
     }
 }
 
 impl RenderPipeline {
-    pub(crate) fn new(application: App, pipeline_config: PipelineConfiguration) -> Result<Self> {
-        // Create shader from file
-        let mut shader_modules = Vec::new();
-        for shader_configuration in pipeline_config.shader.iter() {
-            // Get shader path and validate
-            let shader_path = PathBuf::from_str(&shader_configuration.resource).unwrap();
-            if !shader_path.exists() || !shader_path.is_file() {
-                panic!(
-                    "Unable to create shader module => The path '{}' doesn't points to a file",
-                    shader_path.to_str().unwrap()
-                );
-            }
-
-            // Push shader into list
-            shader_modules.push(ShaderModule {
-                application: application.clone(),
-                shader_source_path: shader_path,
-                vulkan_shader_module: None,
-                kind: shader_configuration.kind,
-                shader_ir_code: Vec::new(),
-            })
-        }
+    /// This function creates the pipeline or the specified variant of the pipeline from the configuration. The shaders
+    /// are compiled with the defines of the pipeline and the variant.
+    pub(crate) fn new(
+        application: App,
+        pipeline_cache: vk::PipelineCache,
+        pipeline_config: &PipelineConfiguration,
+        variant: Option<&str>,
+    ) -> Result<Self> {
+        let (name, defines) = pipeline_config.variant(variant)?;
+        let shader_modules = create_shader_modules(&application, pipeline_config, &name, &defines)?;
         info!(
             "Internally created '{}' render pipeline with {} shaders",
-            pipeline_config.name,
+            name,
             shader_modules.len()
         );
 
         Ok(Self {
             application,
+            pipeline_cache,
+            state: pipeline_config.state,
+            vertex_format: pipeline_config.vertex_format,
+            instance_location: pipeline_config.instance_location,
+            specialization: pipeline_config.specialization.clone(),
+            config_path: pipeline_config.path.clone(),
+            derived_from: None,
+            attachment_formats: None,
+            vertex_attributes: Vec::new(),
+            vertex_stride: 0,
+            instance_stride: None,
             shader_modules,
             descriptor_set_layouts: None,
             vulkan_pipeline_layout: None,
+            push_constant_range: None,
             vulkan_pipeline: None,
-            name: pipeline_config.name,
+            name,
         })
     }
 
-    pub fn compile(&mut self) -> Result<()> {
+    /// This function compiles the shaders and (re)creates the pipeline for the attachments with the specified formats.
+    /// The new pipeline is built next to the old one, so the old pipeline stays usable, if the compilation fails. On
+    /// success the handles of the old pipeline are destroyed, so the caller has to ensure, that the GPU doesn't use
+    /// them anymore.
+    pub fn compile(&mut self, attachment_formats: AttachmentFormats) -> Result<()> {
+        let mut pipeline = self.uncompiled(self.name.clone(), self.specialization.clone());
+
+        // The partially created pipeline destroys its handles, when it's dropped because of an error
+        pipeline.create_handles(attachment_formats)?;
+        mem::swap(self, &mut pipeline);
+        Ok(())
+    }
+
+    /// This function returns an uncompiled copy of this pipeline, whose specialization constants are overridden by the
+    /// specified values. The copy is named after the pipeline and the values like `msdf_font[PX_RANGE=8]`, so it can
+    /// be added to the renderer next to this pipeline with [GameRenderer::add_pipeline].
+    pub fn with_specialization<V: Into<SpecializationValue> + Copy>(
+        &self,
+        constants: &[(&str, V)],
+    ) -> Self {
+        let constants = constants
+            .iter()
+            .map(|(name, value)| (name.to_string(), (*value).into()))
+            .collect::<Specialization>();
+        let values = constants
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>();
+        self.derive(format!("{}[{}]", self.name, values.join(",")), constants)
+    }
+
+    /// This function returns an uncompiled copy of this pipeline with the specified name, whose specialization
+    /// constants are overridden by the specified values. A copy of a derived pipeline is derived from the same
+    /// pipeline, so the constants of both copies are applied, when it's rebuilt.
+    pub(crate) fn derive(&self, name: String, constants: Specialization) -> Self {
+        let mut specialization = self.specialization.clone();
+        specialization.extend(constants.clone());
+
+        let (base_name, mut base_constants) = self
+            .derived_from
+            .clone()
+            .unwrap_or_else(|| (self.name.clone(), Specialization::new()));
+        base_constants.extend(constants);
+
+        let mut pipeline = self.uncompiled(name, specialization);
+        pipeline.derived_from = Some((base_name, base_constants));
+        pipeline
+    }
+
+    /// This function checks, that the vertex layout matches the per-vertex inputs of the vertex shader. The attributes
+    /// of the layout have to match the inputs at the same locations and offsets and the layout has to match the stride
+    /// of the pipeline, so the pipeline reads the vertices correctly.
+    #[inline]
+    pub fn validate_vertex_layout<V: VertexLayout>(&self) -> Result<()> {
+        self.validate_attributes(any::type_name::<V>(), V::ATTRIBUTES, V::stride())
+    }
+
+    pub(crate) fn validate_attributes(
+        &self,
+        layout: &str,
+        attributes: &[VertexAttribute],
+        stride: u32,
+    ) -> Result<()> {
+        let inputs = self
+            .vertex_attributes
+            .iter()
+            .map(|input_attribute| (input_attribute.location, input_attribute.format))
+            .collect::<BTreeMap<_, _>>();
+        let mut messages = compare_vertex_layout(&inputs, attributes);
+        messages.extend(compare_vertex_offsets(
+            &self.vertex_attributes,
+            self.vertex_stride,
+            attributes,
+            stride,
+        ));
+        match messages.is_empty() {
+            true => Ok(()),
+            false => Err(Error::VertexLayoutMismatch {
+                pipeline: self.name.clone(),
+                layout: layout.to_string(),
+                problems: messages.join(", "),
+            }),
+        }
+    }
+
+    /// This function returns the name of the pipeline, which is used to find the pipeline in the renderer
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// This function returns a copy of this pipeline without handles and with the specified name and specialization
+    fn uncompiled(&self, name: String, specialization: Specialization) -> Self {
+        Self {
+            application: self.application.clone(),
+            pipeline_cache: self.pipeline_cache,
+            state: self.state,
+            vertex_format: self.vertex_format,
+            instance_location: self.instance_location,
+            specialization,
+            config_path: self.config_path.clone(),
+            derived_from: self.derived_from.clone(),
+            attachment_formats: None,
+            vertex_attributes: Vec::new(),
+            vertex_stride: 0,
+            instance_stride: None,
+            shader_modules: uncompiled_shader_modules(&self.shader_modules),
+            descriptor_set_layouts: None,
+            vulkan_pipeline_layout: None,
+            push_constant_range: None,
+            vulkan_pipeline: None,
+            name,
+        }
+    }
+
+    /// This function compiles the shaders and creates the handles of this pipeline. Every handle is stored directly
+    /// after its creation, so the handles are destroyed by [Drop] if a later step fails.
+    fn create_handles(&mut self, attachment_formats: AttachmentFormats) -> Result<()> {
         let device = self.application.main_device().virtual_device();
-        for shader in self.shader_modules.iter_mut() {
-            shader.compile()?;
+        self.validate_state(attachment_formats)?;
+
+        let reflections = compile_shaders(&self.name, &mut self.shader_modules)?;
+        validate_interface(
+            &self.name,
+            &self.config_path,
+            PipelineKind::Graphics,
+            self.vertex_format,
+            self.instance_location,
+            &reflections,
+        )?;
+        let limits = unsafe {
+            self.application
+                .instance()
+                .get_physical_device_properties(self.application.main_device().physical_device())
         }
+        .limits;
+        validate_tessellation(
+            &self.name,
+            &self.config_path,
+            &self.state,
+            &reflections,
+            limits.max_tessellation_patch_size,
+        )?;
+        let specialization_data = specialization::specialize(
+            &self.name,
+            &self.config_path,
+            &self.specialization,
+            &reflections,
+        )?;
 
         // Viewport and scissor
         let viewport = vk::Viewport::default();
@@ -102,149 +297,696 @@ impl RenderPipeline {
         let rasterization_stage_create_info = vk::PipelineRasterizationStateCreateInfo::default()
             .rasterizer_discard_enable(false)
             .depth_clamp_enable(false)
-            .polygon_mode(vk::PolygonMode::FILL)
-            .cull_mode(vk::CullModeFlags::NONE)
-            .front_face(vk::FrontFace::CLOCKWISE)
+            .polygon_mode(self.state.polygon_mode.into())
+            .cull_mode(self.state.cull_mode.into())
+            .front_face(self.state.front_face.into())
             .depth_bias_enable(false)
             .line_width(1.0);
         let multisample_stage_create_info = vk::PipelineMultisampleStateCreateInfo::default()
-            .rasterization_samples(vk::SampleCountFlags::TYPE_1)
+            .rasterization_samples(attachment_formats.samples)
             .sample_shading_enable(false)
             .alpha_to_coverage_enable(false)
             .alpha_to_one_enable(false);
 
         // Color Blend infos
-        let pipeline_color_blend_attachment_info = vk::PipelineColorBlendAttachmentState::default()
-            .color_write_mask(vk::ColorComponentFlags::RGBA)
-            .src_color_blend_factor(vk::BlendFactor::SRC_ALPHA)
-            .dst_color_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
-            .color_blend_op(vk::BlendOp::ADD)
-            .src_alpha_blend_factor(vk::BlendFactor::ONE)
-            .dst_alpha_blend_factor(vk::BlendFactor::ZERO)
-            .alpha_blend_op(vk::BlendOp::ADD)
-            .blend_enable(true);
+        let pipeline_color_blend_attachment_info = self.state.blend.attachment_state();
         let pipeline_color_blend_state_create_info =
             vk::PipelineColorBlendStateCreateInfo::default()
                 .attachments(slice::from_ref(&pipeline_color_blend_attachment_info));
 
-        // Create descriptor sets and pipeline layout
-        let mut descriptor_sets = Vec::new();
-        for shader in self.shader_modules.iter() {
-            for descriptor_set in shader.create_descriptor_sets() {
-                let descriptor_set_layout_info = vk::DescriptorSetLayoutCreateInfo::default()
-                    .bindings(descriptor_set.as_slice());
-                let descriptor_set_layout = unsafe {
-                    device.create_descriptor_set_layout(&descriptor_set_layout_info, None)
-                }?;
-                descriptor_sets.push((
-                    descriptor_set_layout,
-                    descriptor_set
-                        .iter()
-                        .map(|desc| desc.descriptor_type)
-                        .collect(),
-                ));
-            }
-        }
-
-        let raw_descriptor_sets = descriptor_sets
-            .iter()
-            .map(|value| value.0)
-            .collect::<Vec<_>>();
-        let layout_create_info =
-            vk::PipelineLayoutCreateInfo::default().set_layouts(raw_descriptor_sets.as_slice());
-        let layout = unsafe { device.create_pipeline_layout(&layout_create_info, None) }?;
+        let descriptor_set_layouts = self.descriptor_set_layouts.insert(Vec::new());
+        let (layout, push_constant_range) = create_pipeline_layout(
+            &self.application,
+            &self.name,
+            &reflections,
+            descriptor_set_layouts,
+        )?;
+        self.vulkan_pipeline_layout = Some(layout);
+        self.push_constant_range = push_constant_range;
 
         // Create pipeline with recompiled shader modules
         let mut pipeline_rendering_create_info = vk::PipelineRenderingCreateInfo::default()
-            .color_attachment_formats(&[vk::Format::B8G8R8A8_UNORM]);
+            .color_attachment_formats(slice::from_ref(&attachment_formats.color_format))
+            .depth_attachment_format(attachment_formats.depth_format);
+        let depth_stencil_state_create_info = self.state.depth.stencil_state();
         let dynamic_states = [vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
         let dynamic_state_create_info =
             vk::PipelineDynamicStateCreateInfo::default().dynamic_states(&dynamic_states);
         let input_assembly_state_create_info = vk::PipelineInputAssemblyStateCreateInfo::default()
-            .topology(vk::PrimitiveTopology::TRIANGLE_LIST) // Weather draw the stuff as triangles, lines etc.
+            .topology(self.state.topology.into()) // Weather draw the stuff as triangles, lines etc.
             .primitive_restart_enable(false); // Ignore lol
 
-        // Configure pipeline input state
-        let vertex_shader = self
-            .shader_modules
+        // Configure pipeline input state, the existence of the vertex shader is validated before
+        let vertex_shader = reflections
+            .iter()
+            .find(|reflection| reflection.kind == ShaderKind::Vertex);
+        let (input_attrs, binding_descs) = vertex_shader
+            .map(|reflection| vertex_input(reflection, self.vertex_format, self.instance_location))
+            .unwrap_or_default();
+        self.vertex_attributes = input_attrs
             .iter()
-            .find(|module| module.kind == ShaderKind::Vertex)
-            .unwrap();
-        let (input_attrs, binding_desc) = vertex_shader.reflect_input_attributes();
+            .filter(|input_attr| input_attr.binding == VERTEX_BINDING)
+            .copied()
+            .collect();
+        let binding_stride = |binding| {
+            binding_descs
+                .iter()
+                .find(|binding_desc| binding_desc.binding == binding)
+                .map(|binding_desc| binding_desc.stride)
+        };
+        self.vertex_stride = binding_stride(VERTEX_BINDING).unwrap_or_default();
+        self.instance_stride = binding_stride(INSTANCE_BINDING);
 
         let vertex_input_state_create_info = vk::PipelineVertexInputStateCreateInfo::default()
             .vertex_attribute_descriptions(input_attrs.as_slice())
-            .vertex_binding_descriptions(slice::from_ref(&binding_desc));
+            .vertex_binding_descriptions(binding_descs.as_slice());
 
-        // Create pipeline with recompiled shader modules
+        // The tessellation state is ignored, if the pipeline has no tessellation shaders
+        let tessellation_state_create_info = vk::PipelineTessellationStateCreateInfo::default()
+            .patch_control_points(self.state.patch_control_points.unwrap_or_default());
+
+        // Create pipeline with recompiled shader modules, the specialization data is in the order of the shaders
+        let specialization_infos = specialization_data
+            .iter()
+            .map(|data| data.info())
+            .collect::<Vec<_>>();
         let stages = self
             .shader_modules
             .iter()
-            .map(|module| module.into())
+            .zip(specialization_infos.iter())
+            .map(|(module, specialization_info)| {
+                vk::PipelineShaderStageCreateInfo::from(module)
+                    .specialization_info(specialization_info)
+            })
             .collect::<Vec<_>>();
 
         let graphics_pipeline_create_info = vk::GraphicsPipelineCreateInfo::default()
             .push_next(&mut pipeline_rendering_create_info)
             .vertex_input_state(&vertex_input_state_create_info)
             .input_assembly_state(&input_assembly_state_create_info)
+            .tessellation_state(&tessellation_state_create_info)
             .color_blend_state(&pipeline_color_blend_state_create_info)
             .rasterization_state(&rasterization_stage_create_info)
             .multisample_state(&multisample_stage_create_info)
+            .depth_stencil_state(&depth_stencil_state_create_info)
             .viewport_state(&viewport_state_create_info)
             .dynamic_state(&dynamic_state_create_info)
             .stages(stages.as_slice())
             .base_pipeline_handle(vk::Pipeline::null())
             .layout(layout);
 
-        // Destroy old handles in memory
-        if let Some(descriptor_set_layouts) = self.descriptor_set_layouts.as_ref() {
-            for descriptor_set_layout in descriptor_set_layouts {
-                unsafe { device.destroy_descriptor_set_layout(descriptor_set_layout.0, None) };
+        let pipeline = unsafe {
+            device.create_graphics_pipelines(
+                self.pipeline_cache,
+                slice::from_ref(&graphics_pipeline_create_info),
+                None,
+            )
+        }
+        .map_err(|(_, error)| error)?[0];
+        self.vulkan_pipeline = Some(pipeline);
+        self.attachment_formats = Some(attachment_formats);
+
+        // Name the pipeline, so validation messages reference the pipeline by name
+        self.application
+            .main_device()
+            .set_object_name(pipeline, &self.name);
+        Ok(())
+    }
+
+    /// This function checks, that the fixed-function state of the configuration is supported by the device and
+    /// compatible with the attachments of the renderer.
+    fn validate_state(&self, attachment_formats: AttachmentFormats) -> Result<()> {
+        if let Some(samples) = self.state.samples {
+            if vk::SampleCountFlags::from_raw(samples) != attachment_formats.samples {
+                return Err(Error::SampleCountMismatch {
+                    pipeline: self.name.clone(),
+                    samples,
+                    renderer_samples: attachment_formats.samples.as_raw(),
+                });
             }
         }
 
-        if let Some(old_pipeline) = self.vulkan_pipeline {
-            unsafe { device.destroy_pipeline(old_pipeline, None) };
+        let enabled_features = self.application.main_device().enabled_features();
+        if self.state.polygon_mode != PolygonMode::Fill
+            && enabled_features.fill_mode_non_solid != vk::TRUE
+        {
+            return Err(Error::UnsupportedFeature {
+                pipeline: self.name.clone(),
+                feature: "fillModeNonSolid",
+            });
         }
 
-        if let Some(old_layout_handle) = self.vulkan_pipeline_layout {
-            unsafe { device.destroy_pipeline_layout(old_layout_handle, None) };
+        // Geometry and tessellation shaders are optional features, which are enabled if the device supports them
+        for shader in self.shader_modules.iter() {
+            let (feature, enabled) = match shader.kind {
+                ShaderKind::Geometry => ("geometryShader", enabled_features.geometry_shader),
+                ShaderKind::TessellationControl | ShaderKind::TessellationEvaluation => {
+                    ("tessellationShader", enabled_features.tessellation_shader)
+                }
+                _ => continue,
+            };
+            if enabled != vk::TRUE {
+                return Err(Error::UnsupportedFeature {
+                    pipeline: self.name.clone(),
+                    feature,
+                });
+            }
         }
+        Ok(())
+    }
+}
 
-        // Replace old handles with new handles
-        self.descriptor_set_layouts = Some(descriptor_sets);
-        self.vulkan_pipeline_layout = Some(layout);
-        self.vulkan_pipeline = Some(
-            unsafe {
-                device.create_graphics_pipelines(
-                    vk::PipelineCache::null(),
-                    slice::from_ref(&graphics_pipeline_create_info),
-                    None,
-                )
+/// This function creates the shader modules of the pipeline configuration with the specified defines. The shaders are
+/// compiled later by the pipeline.
+pub(crate) fn create_shader_modules(
+    application: &App,
+    pipeline_config: &PipelineConfiguration,
+    name: &str,
+    defines: &Defines,
+) -> Result<Vec<ShaderModule>> {
+    let mut shader_modules = Vec::new();
+    for shader_configuration in pipeline_config.shader.iter() {
+        // Get shader path and validate
+        let shader_path = PathBuf::from_str(&shader_configuration.resource).unwrap();
+        if !shader_path.is_file() {
+            return Err(Error::InvalidAsset {
+                path: shader_path,
+                reason: format!("The shader of pipeline '{}' doesn't point to a file", name),
+            });
+        }
+
+        let entry_point = shader_configuration
+            .entry_point
+            .as_deref()
+            .unwrap_or(shader::DEFAULT_ENTRY_POINT);
+        let Ok(entry_point) = CString::new(entry_point) else {
+            return Err(Error::InvalidAsset {
+                path: shader_path,
+                reason: format!(
+                    "The entry point of the shader in pipeline '{}' contains a null character",
+                    name
+                ),
+            });
+        };
+
+        // Push shader into list
+        shader_modules.push(ShaderModule::new(
+            application.clone(),
+            shader_path,
+            shader_configuration.kind,
+            entry_point,
+            defines.clone(),
+        ))
+    }
+    Ok(shader_modules)
+}
+
+/// This function returns uncompiled copies of the specified shader modules, so a pipeline can be rebuilt next to the
+/// old one.
+pub(crate) fn uncompiled_shader_modules(shader_modules: &[ShaderModule]) -> Vec<ShaderModule> {
+    shader_modules
+        .iter()
+        .map(|shader| {
+            ShaderModule::new(
+                shader.application.clone(),
+                shader.shader_source_path.clone(),
+                shader.kind,
+                shader.entry_point.clone(),
+                shader.defines.clone(),
+            )
+        })
+        .collect()
+}
+
+/// This function compiles and reflects the specified shaders. The errors are reported as diagnostics of the shader
+/// source file.
+pub(crate) fn compile_shaders(
+    pipeline: &str,
+    shader_modules: &mut [ShaderModule],
+) -> Result<Vec<ShaderReflection>> {
+    let mut reflections = Vec::new();
+    for shader in shader_modules.iter_mut() {
+        let reflection = shader
+            .compile()
+            .and_then(|_| shader.reflect())
+            .map_err(|error| {
+                Error::Diagnostics(Diagnostic::from_error(
+                    pipeline,
+                    &shader.shader_source_path,
+                    error,
+                ))
+            })?;
+        reflections.push(reflection);
+    }
+    Ok(reflections)
+}
+
+/// This function creates the descriptor set layouts and the pipeline layout from the reflected shaders. Every
+/// descriptor set layout is pushed into the specified list directly after its creation, so the owner of the list
+/// destroys them if a later step fails.
+pub(crate) fn create_pipeline_layout(
+    application: &App,
+    pipeline: &str,
+    reflections: &[ShaderReflection],
+    descriptor_set_layouts: &mut Vec<(vk::DescriptorSetLayout, BindingTypes)>,
+) -> Result<(vk::PipelineLayout, Option<vk::PushConstantRange>)> {
+    let device = application.main_device().virtual_device();
+    let mut merged_descriptor_sets = merge_descriptor_sets(pipeline, reflections)?;
+
+    // Create descriptor set layouts and pipeline layout. The layouts are indexed by the set number, so set numbers
+    // without bindings get an empty layout.
+    let set_count = merged_descriptor_sets
+        .last_key_value()
+        .map_or(0, |(set, _)| set + 1);
+    for set in 0..set_count {
+        let bindings = merged_descriptor_sets
+            .remove(&set)
+            .unwrap_or_default()
+            .into_values()
+            .collect::<Vec<_>>();
+        let descriptor_set_layout_info =
+            vk::DescriptorSetLayoutCreateInfo::default().bindings(bindings.as_slice());
+        let descriptor_set_layout =
+            unsafe { device.create_descriptor_set_layout(&descriptor_set_layout_info, None) }?;
+        descriptor_set_layouts.push((
+            descriptor_set_layout,
+            bindings
+                .iter()
+                .map(|binding| (binding.binding, binding.descriptor_type))
+                .collect(),
+        ));
+        application.main_device().set_object_name(
+            descriptor_set_layout,
+            &format!("{} (Set {})", pipeline, set),
+        );
+    }
+
+    let raw_descriptor_sets = descriptor_set_layouts
+        .iter()
+        .map(|value| value.0)
+        .collect::<Vec<_>>();
+
+    // Merge the push constant ranges of all stages into a single range, which is visible to all of these stages
+    let push_constant_range = reflections
+        .iter()
+        .filter_map(|reflection| reflection.push_constant_range)
+        .reduce(|merged_range, range| {
+            let offset = merged_range.offset.min(range.offset);
+            let end = (merged_range.offset + merged_range.size).max(range.offset + range.size);
+            vk::PushConstantRange::default()
+                .stage_flags(merged_range.stage_flags | range.stage_flags)
+                .offset(offset)
+                .size(end - offset)
+        });
+
+    let layout_create_info = vk::PipelineLayoutCreateInfo::default()
+        .set_layouts(raw_descriptor_sets.as_slice())
+        .push_constant_ranges(push_constant_range.as_slice());
+    let layout = unsafe { device.create_pipeline_layout(&layout_create_info, None) }?;
+    application
+        .main_device()
+        .set_object_name(layout, &format!("{} (Layout)", pipeline));
+    Ok((layout, push_constant_range))
+}
+
+/// This function destroys the handles of a render or compute pipeline, which have been created
+pub(crate) fn destroy_handles(
+    application: &App,
+    descriptor_set_layouts: Option<&[(vk::DescriptorSetLayout, BindingTypes)]>,
+    vulkan_pipeline_layout: Option<vk::PipelineLayout>,
+    vulkan_pipeline: Option<vk::Pipeline>,
+) {
+    let device = application.main_device().virtual_device();
+    for descriptor_set_layout in descriptor_set_layouts.unwrap_or_default() {
+        unsafe { device.destroy_descriptor_set_layout(descriptor_set_layout.0, None) };
+    }
+
+    if let Some(vulkan_pipeline_layout) = vulkan_pipeline_layout {
+        unsafe { device.destroy_pipeline_layout(vulkan_pipeline_layout, None) };
+    }
+
+    if let Some(vulkan_pipeline) = vulkan_pipeline {
+        unsafe { device.destroy_pipeline(vulkan_pipeline, None) };
+    }
+}
+
+/// The descriptor bindings of a pipeline by the set number and the binding number
+pub(crate) type MergedDescriptorSets =
+    BTreeMap<u32, BTreeMap<u32, vk::DescriptorSetLayoutBinding<'static>>>;
+
+/// This function merges the descriptor bindings of all stages by the set and binding number. The stage flags of
+/// bindings used by multiple stages are combined, but the stages have to agree on the type and count of the binding.
+pub(crate) fn merge_descriptor_sets(
+    pipeline: &str,
+    reflections: &[ShaderReflection],
+) -> Result<MergedDescriptorSets> {
+    let mut merged_descriptor_sets = MergedDescriptorSets::new();
+    for reflection in reflections.iter() {
+        for (set, bindings) in reflection.descriptor_sets.iter().cloned() {
+            let merged_bindings = merged_descriptor_sets.entry(set).or_default();
+            for binding in bindings {
+                match merged_bindings.entry(binding.binding) {
+                    Entry::Vacant(entry) => {
+                        entry.insert(binding);
+                    }
+                    Entry::Occupied(mut entry) => {
+                        let merged_binding = entry.get_mut();
+                        if merged_binding.descriptor_type != binding.descriptor_type
+                            || merged_binding.descriptor_count != binding.descriptor_count
+                        {
+                            return Err(Error::DescriptorBindingConflict {
+                                pipeline: pipeline.to_string(),
+                                set,
+                                binding: binding.binding,
+                            });
+                        }
+                        merged_binding.stage_flags |= binding.stage_flags;
+                    }
+                }
             }
-            .unwrap()[0],
+        }
+    }
+    Ok(merged_descriptor_sets)
+}
+
+/// This function checks, that the shaders match the kind of the pipeline. The per-vertex inputs of the vertex shader
+/// have to match the declared vertex format and the outputs of the fragment shader have to match the color
+/// attachments. All problems are returned as diagnostics at once.
+pub(crate) fn validate_interface(
+    pipeline: &str,
+    config_path: &Path,
+    pipeline_kind: PipelineKind,
+    vertex_format: Option<VertexFormat>,
+    instance_location: Option<u32>,
+    reflections: &[ShaderReflection],
+) -> Result<()> {
+    let shader_of_kind = |kind| {
+        reflections
+            .iter()
+            .find(|reflection| reflection.kind == kind)
+    };
+
+    let mut diagnostics = Vec::new();
+    if pipeline_kind == PipelineKind::Compute {
+        let compute_shader_count = reflections
+            .iter()
+            .filter(|reflection| reflection.kind == ShaderKind::Compute)
+            .count();
+        if compute_shader_count != 1 {
+            diagnostics.push(Diagnostic::new(
+                pipeline,
+                config_path,
+                format!(
+                    "The compute pipeline has {} compute shaders, but it requires exactly one",
+                    compute_shader_count
+                ),
+            ));
+        }
+
+        diagnostics.extend(
+            reflections
+                .iter()
+                .filter(|reflection| reflection.kind != ShaderKind::Compute)
+                .map(|reflection| {
+                    Diagnostic::new(
+                        pipeline,
+                        &reflection.source_path,
+                        format!(
+                            "The {:?} shader isn't allowed in a compute pipeline",
+                            reflection.kind
+                        ),
+                    )
+                }),
         );
-        Ok(())
+        return match diagnostics.is_empty() {
+            true => Ok(()),
+            false => Err(Error::Diagnostics(diagnostics)),
+        };
+    }
+
+    if let Some(reflection) = shader_of_kind(ShaderKind::Compute) {
+        diagnostics.push(Diagnostic::new(
+            pipeline,
+            &reflection.source_path,
+            "The render pipeline contains a compute shader, compute shaders require the pipeline kind 'compute'",
+        ));
+    }
+
+    match shader_of_kind(ShaderKind::Vertex) {
+        Some(reflection) => {
+            if let Some(vertex_format) = vertex_format {
+                let inputs = per_vertex_inputs(reflection, instance_location);
+                diagnostics.extend(
+                    compare_vertex_layout(&inputs, vertex_format.attributes())
+                        .into_iter()
+                        .map(|message| Diagnostic::new(pipeline, &reflection.source_path, message)),
+                );
+            }
+        }
+        None => diagnostics.push(Diagnostic::new(
+            pipeline,
+            config_path,
+            "The pipeline has no vertex shader",
+        )),
+    }
+
+    if let Some(reflection) = shader_of_kind(ShaderKind::Fragment) {
+        diagnostics.extend(
+            validate_fragment_outputs(reflection)
+                .into_iter()
+                .map(|message| Diagnostic::new(pipeline, &reflection.source_path, message)),
+        );
+    }
+
+    match diagnostics.is_empty() {
+        true => Ok(()),
+        false => Err(Error::Diagnostics(diagnostics)),
+    }
+}
+
+/// This function checks, that tessellation shaders are used with the topology `patch_list` and the count of control
+/// points per patch, which has to be between 1 and the maximal patch size of the device. A pipeline has either both
+/// tessellation shaders or none of them. All problems are returned as diagnostics at once.
+pub(crate) fn validate_tessellation(
+    pipeline: &str,
+    config_path: &Path,
+    state: &PipelineState,
+    reflections: &[ShaderReflection],
+    max_patch_size: u32,
+) -> Result<()> {
+    let has_shader = |kind| reflections.iter().any(|reflection| reflection.kind == kind);
+    let (has_control, has_evaluation) = (
+        has_shader(ShaderKind::TessellationControl),
+        has_shader(ShaderKind::TessellationEvaluation),
+    );
+
+    let mut messages = Vec::new();
+    if has_control != has_evaluation {
+        messages.push(
+            "The pipeline requires both a tessellation control and a tessellation evaluation shader"
+                .to_string(),
+        );
+    }
+
+    let tessellation = has_control || has_evaluation;
+    match (tessellation, state.topology == Topology::PatchList) {
+        (true, false) => messages.push(format!(
+            "Tessellation shaders require the topology 'patch_list', but the pipeline uses {:?}",
+            state.topology
+        )),
+        (false, true) => {
+            messages.push("The topology 'patch_list' requires tessellation shaders".to_string())
+        }
+        _ => {}
+    }
+
+    if tessellation {
+        match state.patch_control_points {
+            Some(patch_control_points) if (1..=max_patch_size).contains(&patch_control_points) => {}
+            Some(patch_control_points) => messages.push(format!(
+                "The pipeline declares {} control points per patch, but only 1 to {} are supported",
+                patch_control_points, max_patch_size
+            )),
+            None => messages.push(
+                "Tessellation shaders require the count of control points per patch in 'patch_control_points'"
+                    .to_string(),
+            ),
+        }
+    }
+
+    match messages.is_empty() {
+        true => Ok(()),
+        false => Err(Error::Diagnostics(
+            messages
+                .into_iter()
+                .map(|message| Diagnostic::new(pipeline, config_path, message))
+                .collect(),
+        )),
+    }
+}
+
+/// This function returns the formats of the per-vertex inputs of the vertex shader by their location. The inputs at and
+/// after the instance location are per-instance inputs, which aren't part of the vertex layout.
+fn per_vertex_inputs(
+    reflection: &ShaderReflection,
+    instance_location: Option<u32>,
+) -> BTreeMap<u32, vk::Format> {
+    reflection
+        .input_formats()
+        .into_iter()
+        .filter(|(location, _)| {
+            instance_location.map_or(true, |instance_location| *location < instance_location)
+        })
+        .collect()
+}
+
+/// This function returns the vertex attributes and the bindings of the pipeline. If the pipeline declares a vertex
+/// format, the per-vertex attributes and their stride are taken from the format, so the pipeline reads the vertices
+/// with the offsets of the vertex struct. Otherwise the attributes are reflected from the vertex shader and packed
+/// without padding. The formats of the declared attributes are validated against the vertex shader before.
+fn vertex_input(
+    reflection: &ShaderReflection,
+    vertex_format: Option<VertexFormat>,
+    instance_location: Option<u32>,
+) -> (
+    Vec<vk::VertexInputAttributeDescription>,
+    Vec<vk::VertexInputBindingDescription>,
+) {
+    let (mut input_attributes, mut input_bindings) = reflection.vertex_input(instance_location);
+    if let Some(vertex_format) = vertex_format {
+        input_attributes.retain(|input_attribute| input_attribute.binding != VERTEX_BINDING);
+        input_attributes.splice(
+            0..0,
+            (0u32..)
+                .zip(vertex_format.attributes().iter())
+                .map(|(location, attribute)| {
+                    vk::VertexInputAttributeDescription::default()
+                        .location(location)
+                        .binding(VERTEX_BINDING)
+                        .format(attribute.format)
+                        .offset(attribute.offset)
+                }),
+        );
+        for input_binding in input_bindings
+            .iter_mut()
+            .filter(|input_binding| input_binding.binding == VERTEX_BINDING)
+        {
+            input_binding.stride = vertex_format.stride();
+        }
+    }
+    (input_attributes, input_bindings)
+}
+
+/// This function compares the per-vertex inputs of the vertex shader with the attributes of the vertex layout and
+/// returns a message for every mismatch. The location of every attribute is its index in the layout.
+fn compare_vertex_layout(
+    inputs: &BTreeMap<u32, vk::Format>,
+    attributes: &[VertexAttribute],
+) -> Vec<String> {
+    let mut messages = Vec::new();
+    for (location, attribute) in (0u32..).zip(attributes.iter()) {
+        match inputs.get(&location) {
+            Some(input_format) if *input_format != attribute.format => messages.push(format!(
+                "The vertex input at location {} is {:?}, but the attribute '{}' is {:?}",
+                location, input_format, attribute.name, attribute.format
+            )),
+            Some(_) => {}
+            None => messages.push(format!(
+                "The attribute '{}' at location {} has no vertex input",
+                attribute.name, location
+            )),
+        }
+    }
+
+    for location in inputs.keys() {
+        if *location as usize >= attributes.len() {
+            messages.push(format!(
+                "The vertex input at location {} has no attribute",
+                location
+            ));
+        }
     }
+    messages
 }
 
+/// This function compares the offsets of the per-vertex attributes and the stride, the pipeline reads the vertices
+/// with, with the attributes and the stride of the vertex layout and returns a message for every mismatch. Missing
+/// attributes are reported by [compare_vertex_layout].
+fn compare_vertex_offsets(
+    input_attributes: &[vk::VertexInputAttributeDescription],
+    input_stride: u32,
+    attributes: &[VertexAttribute],
+    stride: u32,
+) -> Vec<String> {
+    let mut messages = Vec::new();
+    for (location, attribute) in (0u32..).zip(attributes.iter()) {
+        if let Some(input_attribute) = input_attributes.iter().find(|input_attribute| {
+            input_attribute.location == location && input_attribute.offset != attribute.offset
+        }) {
+            messages.push(format!(
+                "The vertex input at location {} is read at offset {}, but the attribute '{}' is at offset {}",
+                location, input_attribute.offset, attribute.name, attribute.offset
+            ));
+        }
+    }
+
+    if input_stride != stride {
+        messages.push(format!(
+            "The pipeline reads vertices of {} bytes, but the vertices of the layout have {} bytes",
+            input_stride, stride
+        ));
+    }
+    messages
+}
+
+/// This function compares the outputs of the fragment shader with the color attachments of the renderer and returns a
+/// message for every mismatch.
+fn validate_fragment_outputs(reflection: &ShaderReflection) -> Vec<String> {
+    let outputs = reflection.output_formats();
+
+    let mut messages = Vec::new();
+    for location in 0..COLOR_ATTACHMENT_COUNT {
+        if !outputs
+            .iter()
+            .any(|(output_location, _)| *output_location == location)
+        {
+            messages.push(format!(
+                "The fragment shader doesn't write the color attachment at location {}",
+                location
+            ));
+        }
+    }
+
+    for (location, _) in outputs {
+        if location >= COLOR_ATTACHMENT_COUNT {
+            messages.push(format!(
+                "The fragment output at location {} has no color attachment, the renderer has {} color attachments",
+                location, COLOR_ATTACHMENT_COUNT
+            ));
+        }
+    }
+    messages
+}
+
+/// This structure represents a descriptor set allocated from the renderer's descriptor pool. The descriptor set only
+/// references the application and the pool, so the renderer can keep descriptor sets of frames in flight alive
+/// without creating a reference cycle.
 #[derive(Clone)]
 pub struct DescriptorSet {
     pub(crate) vk_descriptor_set: vk::DescriptorSet,
-    renderer: GameRenderer,
-    binding_types: Vec<vk::DescriptorType>,
+    application: App,
+    descriptor_pool: vk::DescriptorPool,
+    binding_types: BindingTypes,
 }
 
 impl Drop for DescriptorSet {
     fn drop(&mut self) {
         unsafe {
-            self.renderer
-                .0
-                .application
+            self.application
                 .main_device()
                 .virtual_device()
                 .free_descriptor_sets(
-                    self.renderer.0.descriptor_pool,
+                    self.descriptor_pool,
                     slice::from_ref(&self.vk_descriptor_set),
                 )
                 .expect("Unable to free descriptor set");
@@ -253,21 +995,22 @@ impl Drop for DescriptorSet {
 }
 
 impl DescriptorSet {
+    /// This function allocates a descriptor set with the layout of the specified set of the render or compute pipeline
     pub fn allocate(renderer: &GameRenderer, pipeline: &str, set_index: usize) -> Result<Self> {
-        let found_pipeline = renderer
-            .find_pipeline(pipeline)
-            .unwrap_or_else(|| panic!("Invalid pipeline name '{}'", pipeline));
-        let (descriptor_set, binding_types) = found_pipeline
-            .descriptor_set_layouts
-            .as_ref()
-            .unwrap()
-            .get(set_index)
-            .unwrap_or_else(|| {
-                panic!(
-                    "Unable to find descriptor set by index '{}' in pipeline '{}'",
-                    set_index, pipeline
-                )
-            });
+        let descriptor_set_layouts = match renderer.find_pipeline(pipeline) {
+            Some(render_pipeline) => render_pipeline.descriptor_set_layouts.as_ref(),
+            None => renderer
+                .find_compute_pipeline(pipeline)
+                .ok_or_else(|| Error::PipelineNotFound(pipeline.to_string()))?
+                .descriptor_set_layouts
+                .as_ref(),
+        };
+        let (descriptor_set, binding_types) = descriptor_set_layouts
+            .and_then(|descriptor_set_layouts| descriptor_set_layouts.get(set_index))
+            .ok_or_else(|| Error::DescriptorSetNotFound {
+                pipeline: pipeline.to_string(),
+                set: set_index,
+            })?;
 
         let descriptor_set_allocate_info = vk::DescriptorSetAllocateInfo::default()
             .descriptor_pool(renderer.0.descriptor_pool)
@@ -281,7 +1024,8 @@ impl DescriptorSet {
 
         Ok(Self {
             vk_descriptor_set: descriptor_set,
-            renderer: renderer.clone(),
+            application: renderer.0.application.clone(),
+            descriptor_pool: renderer.0.descriptor_pool,
             binding_types: binding_types.clone(),
         })
     }
@@ -298,15 +1042,13 @@ impl WriteDescriptorSet for Buffer {
             .range(vk::WHOLE_SIZE);
         let write_descriptor_set = vk::WriteDescriptorSet::default()
             .descriptor_count(1)
-            .descriptor_type(descriptor_set.binding_types[binding as usize])
+            .descriptor_type(descriptor_set.binding_types[&binding])
             .buffer_info(slice::from_ref(&descriptor_buffer_info))
             .dst_set(descriptor_set.vk_descriptor_set)
             .dst_binding(binding);
 
         unsafe {
             descriptor_set
-                .renderer
-                .0
                 .application
                 .main_device()
                 .virtual_device()
@@ -317,21 +1059,20 @@ impl WriteDescriptorSet for Buffer {
 
 impl WriteDescriptorSet for Image {
     fn write_to_set(&self, descriptor_set: &DescriptorSet, binding: u32) {
+        // The sampler is ignored by storage image descriptors
         let descriptor_image_info = vk::DescriptorImageInfo::default()
-            .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+            .image_layout(self.0.layout)
             .image_view(self.0.image_view)
             .sampler(self.0.sampler);
         let write_descriptor_set = vk::WriteDescriptorSet::default()
             .dst_set(descriptor_set.vk_descriptor_set)
             .dst_binding(binding)
             .dst_array_element(0)
-            .descriptor_type(descriptor_set.binding_types[binding as usize])
+            .descriptor_type(descriptor_set.binding_types[&binding])
             .image_info(slice::from_ref(&descriptor_image_info));
 
         unsafe {
             descriptor_set
-                .renderer
-                .0
                 .application
                 .main_device()
                 .virtual_device()
@@ -339,3 +1080,164 @@ impl WriteDescriptorSet for Image {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::buffer::format::{PositionColor, PositionTexCoordColor};
+    use spirv_reflect::types::{ReflectFormat, ReflectInterfaceVariable};
+
+    /// This function returns the per-vertex inputs of a vertex shader with the specified formats at the locations of
+    /// their index
+    fn inputs(formats: &[vk::Format]) -> BTreeMap<u32, vk::Format> {
+        (0u32..).zip(formats.iter().copied()).collect()
+    }
+
+    #[test]
+    fn matching_vertex_layout() {
+        let inputs = inputs(&[vk::Format::R32G32_SFLOAT, vk::Format::R32G32B32A32_SFLOAT]);
+        assert!(compare_vertex_layout(&inputs, PositionColor::ATTRIBUTES).is_empty());
+    }
+
+    #[test]
+    fn mismatching_vertex_layout() {
+        let inputs = inputs(&[vk::Format::R32G32_SFLOAT, vk::Format::R32G32B32_SFLOAT]);
+        assert_eq!(
+            compare_vertex_layout(&inputs, PositionColor::ATTRIBUTES),
+            ["The vertex input at location 1 is R32G32B32_SFLOAT, but the attribute 'color' is R32G32B32A32_SFLOAT"]
+        );
+    }
+
+    #[test]
+    fn missing_vertex_input() {
+        let inputs = inputs(&[vk::Format::R32G32_SFLOAT, vk::Format::R32G32B32A32_SFLOAT]);
+        assert_eq!(
+            compare_vertex_layout(&inputs, PositionTexCoordColor::ATTRIBUTES),
+            ["The attribute 'uv' at location 2 has no vertex input"]
+        );
+    }
+
+    #[test]
+    fn missing_vertex_attribute() {
+        let inputs = inputs(&[
+            vk::Format::R32G32_SFLOAT,
+            vk::Format::R32G32B32A32_SFLOAT,
+            vk::Format::R32G32_SFLOAT,
+        ]);
+        assert_eq!(
+            compare_vertex_layout(&inputs, PositionColor::ATTRIBUTES),
+            ["The vertex input at location 2 has no attribute"]
+        );
+    }
+
+    /// This function returns the per-vertex attributes of a pipeline with the specified formats and offsets at the
+    /// locations of their index
+    fn input_attributes(
+        attributes: &[(vk::Format, u32)],
+    ) -> Vec<vk::VertexInputAttributeDescription> {
+        (0u32..)
+            .zip(attributes.iter())
+            .map(|(location, (format, offset))| {
+                vk::VertexInputAttributeDescription::default()
+                    .location(location)
+                    .binding(VERTEX_BINDING)
+                    .format(*format)
+                    .offset(*offset)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn matching_vertex_offsets() {
+        let input_attributes = input_attributes(&[
+            (vk::Format::R32G32_SFLOAT, 0),
+            (vk::Format::R32G32B32A32_SFLOAT, 8),
+        ]);
+        assert!(compare_vertex_offsets(
+            &input_attributes,
+            PositionColor::stride(),
+            PositionColor::ATTRIBUTES,
+            PositionColor::stride()
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn mismatching_vertex_offsets() {
+        // The color is read after 8 bytes of padding, but the layout stores it directly after the position
+        let input_attributes = input_attributes(&[
+            (vk::Format::R32G32_SFLOAT, 0),
+            (vk::Format::R32G32B32A32_SFLOAT, 16),
+        ]);
+        assert_eq!(
+            compare_vertex_offsets(
+                &input_attributes,
+                32,
+                PositionColor::ATTRIBUTES,
+                PositionColor::stride()
+            ),
+            [
+                "The vertex input at location 1 is read at offset 16, but the attribute 'color' is at offset 8",
+                "The pipeline reads vertices of 32 bytes, but the vertices of the layout have 24 bytes",
+            ]
+        );
+    }
+
+    #[test]
+    fn declared_vertex_input() {
+        // The vertex shader of the msdf_font pipeline with a per-instance input after the declared attributes
+        let reflection = ShaderReflection {
+            source_path: PathBuf::from("msdf_font.vertex.glsl"),
+            kind: ShaderKind::Vertex,
+            descriptor_sets: Vec::new(),
+            push_constant_range: None,
+            input_variables: [
+                (0, ReflectFormat::R32G32_SFLOAT),
+                (1, ReflectFormat::R32G32B32A32_SFLOAT),
+                (2, ReflectFormat::R32G32_SFLOAT),
+                (3, ReflectFormat::R32G32B32A32_SFLOAT),
+            ]
+            .into_iter()
+            .map(|(location, format)| ReflectInterfaceVariable {
+                location,
+                format,
+                ..Default::default()
+            })
+            .collect(),
+            output_variables: Vec::new(),
+            specialization_constants: Vec::new(),
+        };
+
+        let (input_attributes, input_bindings) = vertex_input(
+            &reflection,
+            Some(VertexFormat::PositionTexCoordColor),
+            Some(3),
+        );
+        let declared_attributes = input_attributes
+            .iter()
+            .filter(|input_attribute| input_attribute.binding == VERTEX_BINDING)
+            .map(|input_attribute| {
+                (
+                    input_attribute.location,
+                    input_attribute.format,
+                    input_attribute.offset,
+                )
+            })
+            .collect::<Vec<_>>();
+        let expected_attributes = (0u32..)
+            .zip(PositionTexCoordColor::ATTRIBUTES.iter())
+            .map(|(location, attribute)| (location, attribute.format, attribute.offset))
+            .collect::<Vec<_>>();
+        assert_eq!(declared_attributes, expected_attributes);
+        assert_eq!(
+            input_bindings
+                .iter()
+                .map(|input_binding| (input_binding.binding, input_binding.stride))
+                .collect::<Vec<_>>(),
+            [
+                (VERTEX_BINDING, PositionTexCoordColor::stride()),
+                (INSTANCE_BINDING, 16),
+            ]
+        );
+    }
+}
@@ -1,13 +1,33 @@
 use crate::error::Error;
+use crate::render::cache;
+use crate::render::pipeline::config::Defines;
+use crate::render::pipeline::specialization::{self, SpecializationConstant};
+use crate::render::pipeline::{INSTANCE_BINDING, VERTEX_BINDING};
 use crate::App;
 use crate::Result;
-use ash::vk;
+use ash::{util, vk};
+#[cfg(feature = "shaderc")]
+use log::warn;
 use serde::{Deserialize, Serialize};
-use shaderc::{CompileOptions, Compiler};
-use spirv_reflect::types::{ReflectDescriptorType, ReflectFormat};
-use std::ffi::CStr;
+#[cfg(feature = "shaderc")]
+use shaderc::{CompileOptions, Compiler, IncludeType, ResolvedInclude, SourceLanguage};
+use spirv_reflect::types::{
+    ReflectDecorationFlags, ReflectDescriptorType, ReflectFormat, ReflectInterfaceVariable,
+};
+#[cfg(feature = "shaderc")]
+use std::cell::RefCell;
+use std::ffi::CString;
 use std::fs;
-use std::path::PathBuf;
+use std::io::Cursor;
+use std::iter;
+use std::path::{Path, PathBuf};
+
+/// The directory, which contains the shader source files. Included files are searched in this directory.
+#[cfg(feature = "shaderc")]
+const SHADER_DIRECTORY: &str = "assets/shader";
+
+/// The entry point of shaders, which don't specify another entry point in the pipeline configuration
+pub(crate) const DEFAULT_ENTRY_POINT: &str = "main";
 
 /// This structure represents a shader module. This shader module is re-compilable, when the source
 /// code of the shader changes. The re-compilation features is used by the render pipeline while
@@ -28,6 +48,15 @@ pub(crate) struct ShaderModule {
 
     /// This field contains the kind of the shader (like fragment or vertex)
     pub(crate) kind: ShaderKind,
+
+    /// The name of the function, which is executed by the shader stage
+    pub(crate) entry_point: CString,
+
+    /// The preprocessor macros, which are defined while compiling the shader
+    pub(crate) defines: Defines,
+
+    /// The paths of all files, which are included by the shader source file
+    pub(crate) dependencies: Vec<PathBuf>,
 }
 
 impl Drop for ShaderModule {
@@ -44,36 +73,69 @@ impl Drop for ShaderModule {
 }
 
 /// Convert reference of shader module into [vk::PipelineShaderStageCreateInfo]
-impl From<&ShaderModule> for vk::PipelineShaderStageCreateInfo<'_> {
-    fn from(value: &ShaderModule) -> Self {
+impl<'a> From<&'a ShaderModule> for vk::PipelineShaderStageCreateInfo<'a> {
+    fn from(value: &'a ShaderModule) -> Self {
         vk::PipelineShaderStageCreateInfo::default()
             .stage(value.kind.into())
             .module(value.vulkan_shader_module.unwrap())
-            .name(unsafe { CStr::from_ptr(b"main\0".as_ptr().cast()) })
+            .name(&value.entry_point)
     }
 }
 
 impl ShaderModule {
+    pub(crate) fn new(
+        application: App,
+        shader_source_path: PathBuf,
+        kind: ShaderKind,
+        entry_point: CString,
+        defines: Defines,
+    ) -> Self {
+        Self {
+            application,
+            shader_source_path,
+            shader_ir_code: Vec::new(),
+            vulkan_shader_module: None,
+            kind,
+            entry_point,
+            defines,
+            dependencies: Vec::new(),
+        }
+    }
+
     pub(crate) fn compile(&mut self) -> Result<()> {
-        let file_content = String::from_utf8(fs::read(&self.shader_source_path)?)?;
-        let file_name = self
-            .shader_source_path
-            .file_name()
-            .unwrap()
-            .to_str()
-            .unwrap();
-
-        // Compile Shader
-        let compiler = Compiler::new().ok_or(Error::CompilerCreation)?;
-        let options = CompileOptions::new().ok_or(Error::CompilerCreation)?;
-        let result = compiler.compile_into_spirv(
-            &file_content,
-            self.kind.into(),
-            file_name,
-            "main",
-            Some(&options),
-        )?;
-        self.shader_ir_code = result.as_binary_u8().to_vec();
+        let entry_point = self.entry_point.to_string_lossy();
+        (self.shader_ir_code, self.dependencies) =
+            match ShaderLanguage::of_path(&self.shader_source_path) {
+                // Precompiled shaders are loaded directly, they don't include other files
+                ShaderLanguage::Spirv => (fs::read(&self.shader_source_path)?, Vec::new()),
+                ShaderLanguage::Glsl | ShaderLanguage::Hlsl => {
+                    let file_content = String::from_utf8(fs::read(&self.shader_source_path)?)?;
+
+                    // Load the shader from the shader cache or compile it, if the source code or an included file
+                    // has changed
+                    let cache_key = cache::shader_cache_key(
+                        &self.shader_source_path,
+                        &file_content,
+                        self.kind,
+                        &entry_point,
+                        &self.defines,
+                    );
+                    match cache::load_shader(cache_key) {
+                        Some(cached_shader) => (cached_shader.ir_code, cached_shader.dependencies),
+                        None => {
+                            let (shader_ir_code, dependencies) = compile_source(
+                                &self.shader_source_path,
+                                &file_content,
+                                self.kind,
+                                &entry_point,
+                                &self.defines,
+                            )?;
+                            cache::store_shader(cache_key, &shader_ir_code, &dependencies);
+                            (shader_ir_code, dependencies)
+                        }
+                    }
+                }
+            };
 
         // Create shader
         let device = self.application.main_device().virtual_device();
@@ -81,86 +143,352 @@ impl ShaderModule {
             unsafe { device.destroy_shader_module(old_shader_module, None) };
         }
 
-        let shader_module_create_info =
-            vk::ShaderModuleCreateInfo::default().code(result.as_binary());
+        let shader_code = util::read_spv(&mut Cursor::new(&self.shader_ir_code))?;
+        let shader_module_create_info = vk::ShaderModuleCreateInfo::default().code(&shader_code);
         let shader = unsafe { device.create_shader_module(&shader_module_create_info, None) }?;
+        self.application
+            .main_device()
+            .set_object_name(shader, &self.shader_source_path.to_string_lossy());
         self.vulkan_shader_module = Some(shader);
         Ok(())
     }
 
-    pub(crate) fn reflect_input_attributes(
+    /// This function returns the paths of the shader source file and all included files
+    pub(crate) fn source_files(&self) -> impl Iterator<Item = &PathBuf> {
+        iter::once(&self.shader_source_path).chain(self.dependencies.iter())
+    }
+
+    /// This function reflects the interface of the compiled shader. This fails, if the SPIR-V code can't be parsed.
+    pub(crate) fn reflect(&self) -> Result<ShaderReflection> {
+        reflect_spirv(
+            &self.shader_source_path,
+            &self.shader_ir_code,
+            self.kind,
+            &self.entry_point.to_string_lossy(),
+        )
+    }
+}
+
+/// This structure contains the interface of a compiled shader, which is reflected from the SPIR-V code
+pub(crate) struct ShaderReflection {
+    pub(crate) source_path: PathBuf,
+    pub(crate) kind: ShaderKind,
+
+    /// The bindings of every descriptor set used by the shader together with the set number. The stage flags of the
+    /// bindings only contain the stage of this shader.
+    pub(crate) descriptor_sets: Vec<(u32, Vec<vk::DescriptorSetLayoutBinding<'static>>)>,
+
+    /// The push constant range of the shader, if the shader uses push constants
+    pub(crate) push_constant_range: Option<vk::PushConstantRange>,
+
+    pub(crate) input_variables: Vec<ReflectInterfaceVariable>,
+    pub(crate) output_variables: Vec<ReflectInterfaceVariable>,
+
+    /// The specialization constants declared by the shader, which can be set by the pipeline
+    pub(crate) specialization_constants: Vec<SpecializationConstant>,
+}
+
+impl ShaderReflection {
+    /// This function returns the vertex attributes and the bindings of the vertex shader. The attributes are ordered by
+    /// their location and built-in variables are skipped. If an instance location is specified, the attributes at this
+    /// and all following locations are read from the binding [INSTANCE_BINDING], which advances per instance. All other
+    /// attributes are read from the binding [VERTEX_BINDING].
+    pub(crate) fn vertex_input(
         &self,
+        instance_location: Option<u32>,
     ) -> (
         Vec<vk::VertexInputAttributeDescription>,
-        vk::VertexInputBindingDescription,
+        Vec<vk::VertexInputBindingDescription>,
     ) {
-        let reflected_module =
-            spirv_reflect::create_shader_module(self.shader_ir_code.as_slice()).unwrap();
-        let mut input_attributes = Vec::new();
-        let mut offset = 0;
-
-        for input_variable in reflected_module.enumerate_input_variables(None).unwrap() {
-            input_attributes.push(
-                vk::VertexInputAttributeDescription::default()
+        let mut strides = [0; 2];
+        let input_attributes = interface_variables(&self.input_variables)
+            .into_iter()
+            .map(|input_variable| {
+                let binding = match instance_location {
+                    Some(instance_location) if input_variable.location >= instance_location => {
+                        INSTANCE_BINDING
+                    }
+                    _ => VERTEX_BINDING,
+                };
+                let input_attribute = vk::VertexInputAttributeDescription::default()
                     .location(input_variable.location)
+                    .binding(binding)
                     .format(reflect_to_vulkan_format(input_variable.format))
-                    .offset(offset),
+                    .offset(strides[binding as usize]);
+                strides[binding as usize] += reflect_format_to_offset(input_variable.format);
+                input_attribute
+            })
+            .collect::<Vec<_>>();
+
+        // The instance binding only exists, if at least one attribute is read from it
+        let mut input_bindings = vec![vk::VertexInputBindingDescription::default()
+            .binding(VERTEX_BINDING)
+            .stride(strides[VERTEX_BINDING as usize])
+            .input_rate(vk::VertexInputRate::VERTEX)];
+        if input_attributes
+            .iter()
+            .any(|input_attribute| input_attribute.binding == INSTANCE_BINDING)
+        {
+            input_bindings.push(
+                vk::VertexInputBindingDescription::default()
+                    .binding(INSTANCE_BINDING)
+                    .stride(strides[INSTANCE_BINDING as usize])
+                    .input_rate(vk::VertexInputRate::INSTANCE),
             );
-            offset += reflect_format_to_offset(input_variable.format);
         }
+        (input_attributes, input_bindings)
+    }
 
-        (
-            input_attributes,
-            vk::VertexInputBindingDescription::default()
-                .stride(offset)
-                .input_rate(vk::VertexInputRate::VERTEX),
-        )
+    /// This function returns the locations and formats of the input variables without built-in variables, ordered by
+    /// the location.
+    pub(crate) fn input_formats(&self) -> Vec<(u32, vk::Format)> {
+        interface_formats(&self.input_variables)
+    }
+
+    /// This function returns the locations and formats of the output variables without built-in variables, ordered by
+    /// the location.
+    pub(crate) fn output_formats(&self) -> Vec<(u32, vk::Format)> {
+        interface_formats(&self.output_variables)
     }
+}
 
-    pub(crate) fn create_descriptor_sets(&self) -> Vec<Vec<vk::DescriptorSetLayoutBinding>> {
-        let reflected_module =
-            spirv_reflect::create_shader_module(self.shader_ir_code.as_slice()).unwrap();
+/// This function returns the interface variables without built-in variables, ordered by the location. The reflection
+/// doesn't guarantee any order of the variables.
+fn interface_variables(variables: &[ReflectInterfaceVariable]) -> Vec<&ReflectInterfaceVariable> {
+    let mut variables = variables
+        .iter()
+        .filter(|variable| {
+            !variable
+                .decoration_flags
+                .contains(ReflectDecorationFlags::BUILT_IN)
+        })
+        .collect::<Vec<_>>();
+    variables.sort_unstable_by_key(|variable| variable.location);
+    variables
+}
 
-        let mut vulkan_descriptor_sets = Vec::new();
-        for descriptor_set in reflected_module
-            .enumerate_descriptor_sets(Some("main"))
-            .unwrap()
-            .iter()
-        {
-            let mut descriptor_set_bindings = Vec::new();
-            for descriptor_binding in &descriptor_set.bindings {
-                let descriptor_set_layout_binding = vk::DescriptorSetLayoutBinding::default()
-                    .descriptor_type(reflect_to_vulkan_descriptor_type(
-                        descriptor_binding.descriptor_type,
-                    ))
-                    .binding(descriptor_binding.binding)
-                    .descriptor_count(descriptor_binding.count)
-                    .stage_flags(self.kind.into());
-                descriptor_set_bindings.push(descriptor_set_layout_binding);
-            }
-            vulkan_descriptor_sets.push(descriptor_set_bindings);
+fn interface_formats(variables: &[ReflectInterfaceVariable]) -> Vec<(u32, vk::Format)> {
+    interface_variables(variables)
+        .into_iter()
+        .map(|variable| (variable.location, reflect_to_vulkan_format(variable.format)))
+        .collect()
+}
+
+/// This enum represents the language of a shader resource, which is detected by the extension of the file
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum ShaderLanguage {
+    Glsl,
+
+    /// HLSL sources with the extension `.hlsl`
+    Hlsl,
+
+    /// Precompiled SPIR-V binaries with the extension `.spv`, which are loaded without compilation
+    Spirv,
+}
+
+impl ShaderLanguage {
+    pub(crate) fn of_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("spv") => Self::Spirv,
+            Some("hlsl") => Self::Hlsl,
+            _ => Self::Glsl,
+        }
+    }
+}
+
+/// This function loads the SPIR-V code of the specified shader file. Precompiled SPIR-V binaries are read directly,
+/// shader sources are compiled. The SPIR-V code is returned with the paths of all included files, the shader cache
+/// isn't used.
+pub(crate) fn load_file(
+    source_path: &Path,
+    kind: ShaderKind,
+    entry_point: &str,
+    defines: &Defines,
+) -> Result<(Vec<u8>, Vec<PathBuf>)> {
+    match ShaderLanguage::of_path(source_path) {
+        ShaderLanguage::Spirv => Ok((fs::read(source_path)?, Vec::new())),
+        ShaderLanguage::Glsl | ShaderLanguage::Hlsl => {
+            let source = fs::read_to_string(source_path)?;
+            compile_source(source_path, &source, kind, entry_point, defines)
+        }
+    }
+}
+
+/// This function compiles the GLSL or HLSL source code of the specified shader file into SPIR-V and returns the SPIR-V
+/// code with the paths of all included files. This doesn't require a device, so assets can be validated without a GPU.
+#[cfg(feature = "shaderc")]
+pub(crate) fn compile_source(
+    source_path: &Path,
+    source: &str,
+    kind: ShaderKind,
+    entry_point: &str,
+    defines: &Defines,
+) -> Result<(Vec<u8>, Vec<PathBuf>)> {
+    let included_files = RefCell::new(Vec::new());
+    let compiler = Compiler::new().ok_or(Error::CompilerCreation)?;
+    let mut options = CompileOptions::new().ok_or(Error::CompilerCreation)?;
+    if ShaderLanguage::of_path(source_path) == ShaderLanguage::Hlsl {
+        options.set_source_language(SourceLanguage::HLSL);
+    }
+    for (name, value) in defines.iter() {
+        options.add_macro_definition(name, (!value.is_empty()).then_some(value.as_str()));
+    }
+    options.set_include_callback(
+        |requested_source, include_type, requesting_source, _include_depth| {
+            let path = resolve_include(requested_source, include_type, requesting_source)?;
+            let content = fs::read_to_string(&path)
+                .map_err(|error| format!("Unable to read include {:?} => {}", path, error))?;
+            included_files.borrow_mut().push(path.clone());
+            Ok(ResolvedInclude {
+                resolved_name: path.to_string_lossy().into_owned(),
+                content,
+            })
+        },
+    );
+
+    let result = compiler.compile_into_spirv(
+        source,
+        kind.into(),
+        &source_path.to_string_lossy(),
+        entry_point,
+        Some(&options),
+    )?;
+    if result.get_num_warnings() > 0 {
+        warn!(
+            "Shader {:?} compiled with warnings:\n{}",
+            source_path,
+            result.get_warning_messages()
+        );
+    }
+
+    let mut dependencies = included_files.take();
+    dependencies.sort_unstable();
+    dependencies.dedup();
+    Ok((result.as_binary_u8().to_vec(), dependencies))
+}
+
+/// This function fails for every shader source, because the engine is built without the shader compiler. Only
+/// precompiled SPIR-V binaries can be loaded.
+#[cfg(not(feature = "shaderc"))]
+pub(crate) fn compile_source(
+    source_path: &Path,
+    _source: &str,
+    _kind: ShaderKind,
+    _entry_point: &str,
+    _defines: &Defines,
+) -> Result<(Vec<u8>, Vec<PathBuf>)> {
+    Err(Error::ShaderCompilerUnavailable(source_path.to_path_buf()))
+}
+
+/// This function reflects the interface of the SPIR-V code compiled from the specified shader file. This fails, if the
+/// SPIR-V code can't be parsed.
+pub(crate) fn reflect_spirv(
+    source_path: &Path,
+    ir_code: &[u8],
+    kind: ShaderKind,
+    entry_point: &str,
+) -> Result<ShaderReflection> {
+    let reflected_module =
+        spirv_reflect::create_shader_module(ir_code).map_err(Error::Reflection)?;
+
+    let mut descriptor_sets = Vec::new();
+    for descriptor_set in reflected_module
+        .enumerate_descriptor_sets(Some(entry_point))
+        .map_err(Error::Reflection)?
+        .iter()
+    {
+        let mut descriptor_set_bindings = Vec::new();
+        for descriptor_binding in &descriptor_set.bindings {
+            let descriptor_set_layout_binding = vk::DescriptorSetLayoutBinding::default()
+                .descriptor_type(reflect_to_vulkan_descriptor_type(
+                    descriptor_binding.descriptor_type,
+                ))
+                .binding(descriptor_binding.binding)
+                .descriptor_count(descriptor_binding.count)
+                .stage_flags(kind.into());
+            descriptor_set_bindings.push(descriptor_set_layout_binding);
         }
-        vulkan_descriptor_sets
+        descriptor_sets.push((descriptor_set.set, descriptor_set_bindings));
     }
+
+    let push_constant_range = reflected_module
+        .enumerate_push_constant_blocks(Some(entry_point))
+        .map_err(Error::Reflection)?
+        .first()
+        .map(|push_constant_block| {
+            vk::PushConstantRange::default()
+                .stage_flags(kind.into())
+                .offset(push_constant_block.offset)
+                .size(push_constant_block.size)
+        });
+
+    Ok(ShaderReflection {
+        source_path: source_path.to_path_buf(),
+        kind,
+        descriptor_sets,
+        push_constant_range,
+        input_variables: reflected_module
+            .enumerate_input_variables(Some(entry_point))
+            .map_err(Error::Reflection)?,
+        output_variables: reflected_module
+            .enumerate_output_variables(Some(entry_point))
+            .map_err(Error::Reflection)?,
+        specialization_constants: specialization::reflect_constants(ir_code)?,
+    })
 }
 
-/// This enum represents all supported kinds of shader in the Vesuvius game engine. Currently only
-/// vertex and fragment shader are supported, because we only need them now.
+/// This function returns the path of the included file. Includes with quotes are searched relative to the including
+/// file first, all includes are searched in [SHADER_DIRECTORY].
+#[cfg(feature = "shaderc")]
+fn resolve_include(
+    requested_source: &str,
+    include_type: IncludeType,
+    requesting_source: &str,
+) -> std::result::Result<PathBuf, String> {
+    let mut candidates = Vec::new();
+    if matches!(include_type, IncludeType::Relative) {
+        if let Some(directory) = Path::new(requesting_source).parent() {
+            candidates.push(directory.join(requested_source));
+        }
+    }
+    candidates.push(Path::new(SHADER_DIRECTORY).join(requested_source));
+
+    candidates
+        .into_iter()
+        .find(|candidate| candidate.is_file())
+        .ok_or_else(|| format!("Unable to find include '{}'", requested_source))
+}
+
+/// This enum represents all supported kinds of shader in the Vesuvius game engine. Render pipelines consist of vertex
+/// and fragment shaders with optional geometry and tessellation shaders, compute pipelines of a single compute shader.
 #[derive(Serialize, Deserialize, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
 pub(crate) enum ShaderKind {
     #[serde(rename = "fragment")]
     Fragment,
     #[serde(rename = "vertex")]
     Vertex,
+    #[serde(rename = "geometry")]
+    Geometry,
+    #[serde(rename = "tessellation_control")]
+    TessellationControl,
+    #[serde(rename = "tessellation_evaluation")]
+    TessellationEvaluation,
+    #[serde(rename = "compute")]
+    Compute,
 }
 
 /// Convert own shader kind into [shaderc::ShaderKind] of the shaderc crate
+#[cfg(feature = "shaderc")]
 impl From<ShaderKind> for shaderc::ShaderKind {
     #[inline]
     fn from(value: ShaderKind) -> Self {
         match value {
             ShaderKind::Vertex => Self::Vertex,
             ShaderKind::Fragment => Self::Fragment,
+            ShaderKind::Geometry => Self::Geometry,
+            ShaderKind::TessellationControl => Self::TessControl,
+            ShaderKind::TessellationEvaluation => Self::TessEvaluation,
+            ShaderKind::Compute => Self::Compute,
         }
     }
 }
@@ -172,6 +500,10 @@ impl From<ShaderKind> for vk::ShaderStageFlags {
         match value {
             ShaderKind::Vertex => Self::VERTEX,
             ShaderKind::Fragment => Self::FRAGMENT,
+            ShaderKind::Geometry => Self::GEOMETRY,
+            ShaderKind::TessellationControl => Self::TESSELLATION_CONTROL,
+            ShaderKind::TessellationEvaluation => Self::TESSELLATION_EVALUATION,
+            ShaderKind::Compute => Self::COMPUTE,
         }
     }
 }
@@ -207,7 +539,7 @@ const fn reflect_to_vulkan_format(format: ReflectFormat) -> vk::Format {
         ReflectFormat::R32_SINT => vk::Format::R32_SINT,
         ReflectFormat::R32_SFLOAT => vk::Format::R32_SFLOAT,
         ReflectFormat::R32G32_UINT => vk::Format::R32G32_UINT,
-        ReflectFormat::R32G32_SINT => vk::Format::R32G32_UINT,
+        ReflectFormat::R32G32_SINT => vk::Format::R32G32_SINT,
         ReflectFormat::R32G32_SFLOAT => vk::Format::R32G32_SFLOAT,
         ReflectFormat::R32G32B32_UINT => vk::Format::R32G32B32_UINT,
         ReflectFormat::R32G32B32_SINT => vk::Format::R32G32B32_SINT,
@@ -231,3 +563,152 @@ const fn reflect_format_to_offset(format: ReflectFormat) -> u32 {
         ReflectFormat::R32G32B32A32_SFLOAT => 16,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variable(location: u32, format: ReflectFormat) -> ReflectInterfaceVariable {
+        ReflectInterfaceVariable {
+            location,
+            format,
+            ..Default::default()
+        }
+    }
+
+    /// This function returns the reflection of a vertex shader with the specified input variables
+    fn reflection(input_variables: Vec<ReflectInterfaceVariable>) -> ShaderReflection {
+        ShaderReflection {
+            source_path: PathBuf::from("test.vertex.glsl"),
+            kind: ShaderKind::Vertex,
+            descriptor_sets: Vec::new(),
+            push_constant_range: None,
+            input_variables,
+            output_variables: Vec::new(),
+            specialization_constants: Vec::new(),
+        }
+    }
+
+    /// This function returns the location, binding, format and offset of every attribute and the binding, stride and
+    /// input rate of every binding of the vertex input
+    #[allow(clippy::type_complexity)]
+    fn vertex_input(
+        reflection: &ShaderReflection,
+        instance_location: Option<u32>,
+    ) -> (
+        Vec<(u32, u32, vk::Format, u32)>,
+        Vec<(u32, u32, vk::VertexInputRate)>,
+    ) {
+        let (input_attributes, input_bindings) = reflection.vertex_input(instance_location);
+        (
+            input_attributes
+                .iter()
+                .map(|attribute| {
+                    (
+                        attribute.location,
+                        attribute.binding,
+                        attribute.format,
+                        attribute.offset,
+                    )
+                })
+                .collect(),
+            input_bindings
+                .iter()
+                .map(|binding| (binding.binding, binding.stride, binding.input_rate))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn interface_variables_are_sorted() {
+        let mut built_in = variable(u32::MAX, ReflectFormat::R32_SINT);
+        built_in.decoration_flags = ReflectDecorationFlags::BUILT_IN;
+        let variables = [
+            variable(2, ReflectFormat::R32G32_SINT),
+            built_in,
+            variable(0, ReflectFormat::R32G32_SFLOAT),
+            variable(1, ReflectFormat::R32G32B32A32_SFLOAT),
+        ];
+
+        let locations = interface_variables(&variables)
+            .iter()
+            .map(|variable| variable.location)
+            .collect::<Vec<_>>();
+        assert_eq!(locations, [0, 1, 2]);
+        assert_eq!(
+            interface_formats(&variables),
+            [
+                (0, vk::Format::R32G32_SFLOAT),
+                (1, vk::Format::R32G32B32A32_SFLOAT),
+                (2, vk::Format::R32G32_SINT),
+            ]
+        );
+    }
+
+    #[test]
+    fn vertex_input_without_instances() {
+        let reflection = reflection(vec![
+            variable(1, ReflectFormat::R32G32B32A32_SFLOAT),
+            variable(2, ReflectFormat::R32G32_SFLOAT),
+            variable(0, ReflectFormat::R32G32_SFLOAT),
+        ]);
+
+        let (attributes, bindings) = vertex_input(&reflection, None);
+        assert_eq!(
+            attributes,
+            [
+                (0, VERTEX_BINDING, vk::Format::R32G32_SFLOAT, 0),
+                (1, VERTEX_BINDING, vk::Format::R32G32B32A32_SFLOAT, 8),
+                (2, VERTEX_BINDING, vk::Format::R32G32_SFLOAT, 24),
+            ]
+        );
+        assert_eq!(
+            bindings,
+            [(VERTEX_BINDING, 32, vk::VertexInputRate::VERTEX)]
+        );
+    }
+
+    #[test]
+    fn vertex_input_with_instances() {
+        // The vertex input of the sprite pipeline, whose attributes after the corner are read per instance
+        let mut built_in = variable(u32::MAX, ReflectFormat::R32_SINT);
+        built_in.decoration_flags = ReflectDecorationFlags::BUILT_IN;
+        let reflection = reflection(vec![
+            variable(4, ReflectFormat::R32G32B32A32_SFLOAT),
+            variable(0, ReflectFormat::R32G32_SFLOAT),
+            built_in,
+            variable(3, ReflectFormat::R32G32B32A32_SFLOAT),
+            variable(1, ReflectFormat::R32G32_SFLOAT),
+            variable(2, ReflectFormat::R32G32_SFLOAT),
+        ]);
+
+        let (attributes, bindings) = vertex_input(&reflection, Some(1));
+        assert_eq!(
+            attributes,
+            [
+                (0, VERTEX_BINDING, vk::Format::R32G32_SFLOAT, 0),
+                (1, INSTANCE_BINDING, vk::Format::R32G32_SFLOAT, 0),
+                (2, INSTANCE_BINDING, vk::Format::R32G32_SFLOAT, 8),
+                (3, INSTANCE_BINDING, vk::Format::R32G32B32A32_SFLOAT, 16),
+                (4, INSTANCE_BINDING, vk::Format::R32G32B32A32_SFLOAT, 32),
+            ]
+        );
+        assert_eq!(
+            bindings,
+            [
+                (VERTEX_BINDING, 8, vk::VertexInputRate::VERTEX),
+                (INSTANCE_BINDING, 48, vk::VertexInputRate::INSTANCE),
+            ]
+        );
+
+        // The instance binding is omitted, if no attribute is read from it
+        let (attributes, bindings) = vertex_input(&reflection, Some(5));
+        assert!(attributes
+            .iter()
+            .all(|(_, binding, _, _)| *binding == VERTEX_BINDING));
+        assert_eq!(
+            bindings,
+            [(VERTEX_BINDING, 56, vk::VertexInputRate::VERTEX)]
+        );
+    }
+}
@@ -0,0 +1,438 @@
+use crate::error::Error;
+use crate::render::pipeline::diagnostic::Diagnostic;
+use crate::render::pipeline::shader::ShaderReflection;
+use crate::Result;
+use ash::{util, vk};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display, Formatter};
+use std::io::Cursor;
+use std::path::Path;
+
+/// The values of the specialization constants of a pipeline by the name or the constant ID of the constant
+pub(crate) type Specialization = BTreeMap<String, SpecializationValue>;
+
+// The opcodes and decorations of the SPIR-V instructions, which declare specialization constants
+const OP_NAME: u32 = 5;
+const OP_TYPE_BOOL: u32 = 20;
+const OP_TYPE_INT: u32 = 21;
+const OP_TYPE_FLOAT: u32 = 22;
+const OP_SPEC_CONSTANT_TRUE: u32 = 48;
+const OP_SPEC_CONSTANT_FALSE: u32 = 49;
+const OP_SPEC_CONSTANT: u32 = 50;
+const OP_DECORATE: u32 = 71;
+const DECORATION_SPEC_ID: u32 = 1;
+
+/// The count of words in the header of a SPIR-V module, which are followed by the instructions
+const SPIRV_HEADER_LENGTH: usize = 5;
+
+/// This enum represents the value of a specialization constant in the pipeline configuration. The value is converted
+/// into the type of the constant, which is declared by the shader.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(untagged)]
+pub enum SpecializationValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+}
+
+impl Display for SpecializationValue {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(value) => write!(formatter, "{}", value),
+            Self::Int(value) => write!(formatter, "{}", value),
+            Self::Float(value) => write!(formatter, "{}", value),
+        }
+    }
+}
+
+impl From<bool> for SpecializationValue {
+    #[inline]
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i32> for SpecializationValue {
+    #[inline]
+    fn from(value: i32) -> Self {
+        Self::Int(value as i64)
+    }
+}
+
+impl From<u32> for SpecializationValue {
+    #[inline]
+    fn from(value: u32) -> Self {
+        Self::Int(value as i64)
+    }
+}
+
+impl From<f32> for SpecializationValue {
+    #[inline]
+    fn from(value: f32) -> Self {
+        Self::Float(value as f64)
+    }
+}
+
+impl SpecializationValue {
+    /// This function returns the bytes of the value converted into the specified type of a constant. Integers are
+    /// converted into floats, but not the other way around, so the shader never sees a truncated value.
+    fn to_bytes(self, kind: ConstantKind) -> Option<[u8; 4]> {
+        match (kind, self) {
+            (ConstantKind::Bool, Self::Bool(value)) => Some(vk::Bool32::from(value).to_ne_bytes()),
+            (ConstantKind::Int, Self::Int(value)) => {
+                i32::try_from(value).ok().map(i32::to_ne_bytes)
+            }
+            (ConstantKind::UInt, Self::Int(value)) => {
+                u32::try_from(value).ok().map(u32::to_ne_bytes)
+            }
+            (ConstantKind::Float, Self::Int(value)) => Some((value as f32).to_ne_bytes()),
+            (ConstantKind::Float, Self::Float(value)) => Some((value as f32).to_ne_bytes()),
+            _ => None,
+        }
+    }
+}
+
+/// This enum represents the type of a specialization constant. Only 32-bit scalars can be specialized by the
+/// pipeline, constants with other types keep the default value of the shader.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub(crate) enum ConstantKind {
+    Bool,
+    Int,
+    UInt,
+    Float,
+    Unsupported,
+}
+
+/// This structure represents a specialization constant, which is declared by a shader
+#[derive(Clone, Debug)]
+pub(crate) struct SpecializationConstant {
+    /// The name of the constant, which is missing if the SPIR-V binary was stripped of its debug information
+    pub(crate) name: Option<String>,
+    pub(crate) id: u32,
+    pub(crate) kind: ConstantKind,
+}
+
+impl SpecializationConstant {
+    /// This function returns true, if the constant is specified by the key in the configuration. A constant is
+    /// specified by its name or by its constant ID.
+    fn matches(&self, key: &str) -> bool {
+        self.name.as_deref() == Some(key) || self.id.to_string() == key
+    }
+}
+
+/// This structure contains the specialization data of a single shader stage. The data is referenced by the
+/// specialization info, so it has to live until the pipeline is created.
+#[derive(Default)]
+pub(crate) struct SpecializationData {
+    map_entries: Vec<vk::SpecializationMapEntry>,
+    data: Vec<u8>,
+}
+
+impl SpecializationData {
+    pub(crate) fn info(&self) -> vk::SpecializationInfo<'_> {
+        vk::SpecializationInfo::default()
+            .map_entries(&self.map_entries)
+            .data(&self.data)
+    }
+}
+
+/// This function reads the specialization constants declared by the SPIR-V binary. The constants are looked up in the
+/// instructions directly, because the reflection library doesn't report them.
+pub(crate) fn reflect_constants(ir_code: &[u8]) -> Result<Vec<SpecializationConstant>> {
+    let words = util::read_spv(&mut Cursor::new(ir_code))?;
+
+    let (mut names, mut ids, mut types) = (HashMap::new(), HashMap::new(), HashMap::new());
+    let mut constants = Vec::new();
+    let mut offset = SPIRV_HEADER_LENGTH;
+    while offset < words.len() {
+        let word_count = (words[offset] >> 16) as usize;
+        let opcode = words[offset] & 0xFFFF;
+        if word_count == 0 || offset + word_count > words.len() {
+            return Err(Error::Reflection("Invalid instruction in SPIR-V binary"));
+        }
+
+        match (opcode, &words[offset + 1..offset + word_count]) {
+            (OP_NAME, [target, name @ ..]) => {
+                names.insert(*target, literal_string(name));
+            }
+            (OP_DECORATE, [target, DECORATION_SPEC_ID, id, ..]) => {
+                ids.insert(*target, *id);
+            }
+            (OP_TYPE_BOOL, [result]) => {
+                types.insert(*result, ConstantKind::Bool);
+            }
+            (OP_TYPE_INT, [result, width, signedness]) => {
+                let kind = match (width, signedness) {
+                    (32, 0) => ConstantKind::UInt,
+                    (32, _) => ConstantKind::Int,
+                    _ => ConstantKind::Unsupported,
+                };
+                types.insert(*result, kind);
+            }
+            (OP_TYPE_FLOAT, [result, width, ..]) => {
+                let kind = match width {
+                    32 => ConstantKind::Float,
+                    _ => ConstantKind::Unsupported,
+                };
+                types.insert(*result, kind);
+            }
+            (
+                OP_SPEC_CONSTANT_TRUE | OP_SPEC_CONSTANT_FALSE | OP_SPEC_CONSTANT,
+                [result_type, result, ..],
+            ) => constants.push((*result_type, *result)),
+            _ => {}
+        }
+        offset += word_count;
+    }
+
+    // Constants without constant ID are only used by other constants and can't be specialized
+    Ok(constants
+        .into_iter()
+        .filter_map(|(result_type, result)| {
+            Some(SpecializationConstant {
+                name: names.get(&result).cloned(),
+                id: *ids.get(&result)?,
+                kind: types
+                    .get(&result_type)
+                    .copied()
+                    .unwrap_or(ConstantKind::Unsupported),
+            })
+        })
+        .collect())
+}
+
+/// This function decodes a null-terminated string literal of a SPIR-V instruction
+fn literal_string(words: &[u32]) -> String {
+    let bytes = words
+        .iter()
+        .flat_map(|word| word.to_le_bytes())
+        .take_while(|byte| *byte != 0)
+        .collect::<Vec<_>>();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// This function creates the specialization data of every shader from the values of the specialization. The data is
+/// returned in the order of the reflections. Values of constants, which aren't declared by any shader or have an
+/// incompatible type, are returned as diagnostics at once. A constant can be specified by its name and its constant ID,
+/// but Vulkan doesn't allow two values for the same constant ID, so setting a constant by both keys is reported too.
+pub(crate) fn specialize(
+    pipeline: &str,
+    config_path: &Path,
+    specialization: &Specialization,
+    reflections: &[ShaderReflection],
+) -> Result<Vec<SpecializationData>> {
+    let mut specialization_data = reflections
+        .iter()
+        .map(|_| SpecializationData::default())
+        .collect::<Vec<_>>();
+
+    // The key, which has set the constant ID in the shader
+    let mut constant_keys = reflections
+        .iter()
+        .map(|_| HashMap::new())
+        .collect::<Vec<_>>();
+
+    let mut diagnostics = Vec::new();
+    for (key, value) in specialization.iter() {
+        let mut declared = false;
+        for ((reflection, data), keys) in reflections
+            .iter()
+            .zip(specialization_data.iter_mut())
+            .zip(constant_keys.iter_mut())
+        {
+            for constant in reflection
+                .specialization_constants
+                .iter()
+                .filter(|constant| constant.matches(key))
+            {
+                declared = true;
+                if let Some(other_key) = keys.insert(constant.id, key.as_str()) {
+                    if other_key != key.as_str() {
+                        diagnostics.push(Diagnostic::new(
+                            pipeline,
+                            config_path,
+                            format!(
+                                "The specialization constants '{}' and '{}' both set the constant ID {} in {}",
+                                other_key,
+                                key,
+                                constant.id,
+                                reflection.source_path.display()
+                            ),
+                        ));
+                    }
+                    continue;
+                }
+
+                let Some(bytes) = value.to_bytes(constant.kind) else {
+                    diagnostics.push(Diagnostic::new(
+                        pipeline,
+                        config_path,
+                        format!(
+                            "The value {} of the specialization constant '{}' can't be converted into the type {:?} of the constant in {}",
+                            value,
+                            key,
+                            constant.kind,
+                            reflection.source_path.display()
+                        ),
+                    ));
+                    continue;
+                };
+
+                data.map_entries.push(
+                    vk::SpecializationMapEntry::default()
+                        .constant_id(constant.id)
+                        .offset(data.data.len() as u32)
+                        .size(bytes.len()),
+                );
+                data.data.extend_from_slice(&bytes);
+            }
+        }
+
+        if !declared {
+            diagnostics.push(Diagnostic::new(
+                pipeline,
+                config_path,
+                format!(
+                    "The specialization constant '{}' isn't declared by any shader of the pipeline",
+                    key
+                ),
+            ));
+        }
+    }
+
+    match diagnostics.is_empty() {
+        true => Ok(specialization_data),
+        false => Err(Error::Diagnostics(diagnostics)),
+    }
+}
+
+#[cfg(all(test, feature = "shaderc"))]
+mod tests {
+    use super::*;
+    use crate::render::pipeline::shader::{self, ShaderKind};
+    use std::path::PathBuf;
+
+    /// This function compiles the specified fragment shader source and reflects its specialization constants
+    fn reflect_source(source: &str) -> Vec<SpecializationConstant> {
+        let (ir_code, _) = shader::compile_source(
+            Path::new("test.fragment.glsl"),
+            source,
+            ShaderKind::Fragment,
+            shader::DEFAULT_ENTRY_POINT,
+            &Default::default(),
+        )
+        .unwrap();
+        reflect_constants(&ir_code).unwrap()
+    }
+
+    #[test]
+    fn msdf_distance_range() {
+        let source = format!(
+            "#version 450\n{}\n\
+             layout(set = 0, binding = 0) uniform sampler2D msdfSampler;\n\
+             layout(location = 0) in vec2 texCoord;\n\
+             layout(location = 0) out vec4 outColor;\n\
+             void main() {{ outColor = vec4(screenPxRange(msdfSampler, texCoord)); }}\n",
+            include_str!("../../../../../assets/shader/include/msdf.glsl")
+        );
+
+        let constants = reflect_source(&source);
+        assert_eq!(constants.len(), 1);
+        assert_eq!(constants[0].name.as_deref(), Some("PX_RANGE"));
+        assert_eq!(constants[0].id, 0);
+        assert_eq!(constants[0].kind, ConstantKind::Float);
+        assert!(constants[0].matches("PX_RANGE"));
+        assert!(constants[0].matches("0"));
+    }
+
+    #[test]
+    fn constant_kinds() {
+        let constants = reflect_source(
+            "#version 450\n\
+             layout(constant_id = 3) const bool ENABLED = true;\n\
+             layout(constant_id = 5) const int OFFSET = -1;\n\
+             layout(constant_id = 7) const uint COUNT = 4;\n\
+             layout(constant_id = 9) const double SCALE = 1.0;\n\
+             const int DERIVED = OFFSET * 2;\n\
+             layout(location = 0) out vec4 outColor;\n\
+             void main() { outColor = vec4(ENABLED ? float(DERIVED + int(COUNT)) : float(SCALE)); }\n",
+        );
+
+        let mut kinds = constants
+            .iter()
+            .map(|constant| (constant.id, constant.name.clone().unwrap(), constant.kind))
+            .collect::<Vec<_>>();
+        kinds.sort_by_key(|(id, _, _)| *id);
+        assert_eq!(
+            kinds,
+            [
+                (3, "ENABLED".to_string(), ConstantKind::Bool),
+                (5, "OFFSET".to_string(), ConstantKind::Int),
+                (7, "COUNT".to_string(), ConstantKind::UInt),
+                (9, "SCALE".to_string(), ConstantKind::Unsupported),
+            ]
+        );
+    }
+
+    /// This function returns the reflection of a fragment shader, which only declares the specified constants
+    fn constant_reflection(constants: Vec<SpecializationConstant>) -> ShaderReflection {
+        ShaderReflection {
+            source_path: PathBuf::from("test.fragment.glsl"),
+            kind: ShaderKind::Fragment,
+            descriptor_sets: Vec::new(),
+            push_constant_range: None,
+            input_variables: Vec::new(),
+            output_variables: Vec::new(),
+            specialization_constants: constants,
+        }
+    }
+
+    #[test]
+    fn constant_by_name_and_id() {
+        let reflections = [constant_reflection(vec![SpecializationConstant {
+            name: Some("PX_RANGE".to_string()),
+            id: 0,
+            kind: ConstantKind::Float,
+        }])];
+
+        let specialization =
+            Specialization::from([("0".to_string(), SpecializationValue::Float(8.0))]);
+        let data = specialize(
+            "msdf_font",
+            Path::new("msdf_font.json"),
+            &specialization,
+            &reflections,
+        )
+        .unwrap();
+        assert_eq!(data[0].map_entries.len(), 1);
+        assert_eq!(data[0].data, 8.0f32.to_ne_bytes());
+
+        let specialization = Specialization::from([
+            ("PX_RANGE".to_string(), SpecializationValue::Float(4.0)),
+            ("0".to_string(), SpecializationValue::Float(8.0)),
+        ]);
+        let Err(Error::Diagnostics(diagnostics)) = specialize(
+            "msdf_font",
+            Path::new("msdf_font.json"),
+            &specialization,
+            &reflections,
+        ) else {
+            panic!("Setting a constant by its name and ID has to be reported");
+        };
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].message.contains("'0' and 'PX_RANGE'"));
+    }
+
+    #[test]
+    fn invalid_instruction() {
+        let mut words = vec![0x0723_0203, 0x0001_0000, 0, 1, 0];
+        // The word count of the instruction exceeds the length of the binary
+        words.push((4 << 16) | OP_NAME);
+        let ir_code = words
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect::<Vec<_>>();
+        assert!(reflect_constants(&ir_code).is_err());
+    }
+}
@@ -0,0 +1,156 @@
+use crate::render::color;
+use ash::vk;
+use log::warn;
+
+/// This structure represents the user-configurable settings of the swapchain. These settings are only preferences, the
+/// renderer negotiates them against the capabilities of the surface and falls back to supported values.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SwapchainConfig {
+    /// When enabled, the presentation is synchronized with the vertical blank of the display
+    pub vsync: bool,
+
+    /// When enabled, the mailbox present mode is preferred over FIFO while vsync is enabled. This reduces the latency,
+    /// but the GPU renders frames which are never displayed.
+    pub prefer_mailbox: bool,
+
+    /// The preferred format of the swapchain images
+    pub preferred_format: vk::Format,
+
+    /// The minimal count of images in the swapchain. The count is clamped to the limits of the surface.
+    pub min_image_count: u32,
+
+    /// The sample count of the multisample anti-aliasing. The count is reduced to the highest sample count, which is
+    /// supported by the device.
+    pub samples: vk::SampleCountFlags,
+}
+
+impl Default for SwapchainConfig {
+    fn default() -> Self {
+        Self {
+            vsync: true,
+            prefer_mailbox: false,
+            preferred_format: vk::Format::B8G8R8A8_SRGB,
+            min_image_count: 2,
+            samples: vk::SampleCountFlags::TYPE_1,
+        }
+    }
+}
+
+impl SwapchainConfig {
+    /// This function selects the surface format. The preferred format with the sRGB color space is selected, if
+    /// available. Otherwise the first 8-bit sRGB format, the first 8-bit UNORM format or the first supported format is
+    /// used.
+    pub(crate) fn choose_surface_format(
+        &self,
+        formats: &[vk::SurfaceFormatKHR],
+    ) -> vk::SurfaceFormatKHR {
+        let preferred_surface_format = vk::SurfaceFormatKHR {
+            format: self.preferred_format,
+            color_space: vk::ColorSpaceKHR::SRGB_NONLINEAR,
+        };
+
+        // A single undefined format means, that the surface has no preferred format
+        if formats.is_empty() || (formats.len() == 1 && formats[0].format == vk::Format::UNDEFINED)
+        {
+            return preferred_surface_format;
+        }
+
+        if formats.contains(&preferred_surface_format) {
+            return preferred_surface_format;
+        }
+
+        let find_format = |candidates: &[vk::Format]| {
+            formats.iter().copied().find(|surface_format| {
+                surface_format.color_space == vk::ColorSpaceKHR::SRGB_NONLINEAR
+                    && candidates.contains(&surface_format.format)
+            })
+        };
+        let fallback_format = find_format(&[vk::Format::B8G8R8A8_SRGB, vk::Format::R8G8B8A8_SRGB])
+            .or_else(|| find_format(&[vk::Format::B8G8R8A8_UNORM, vk::Format::R8G8B8A8_UNORM]))
+            .unwrap_or(formats[0]);
+        warn!(
+            "Preferred swapchain format {:?} is not supported, falling back to {:?}",
+            self.preferred_format, fallback_format.format
+        );
+        if !color::is_srgb_format(fallback_format.format) {
+            warn!("The swapchain format is not sRGB, colors are blended in gamma space");
+        }
+        fallback_format
+    }
+
+    /// This function selects the present mode. FIFO is the only mode, which is guaranteed to be supported, so this
+    /// is the fallback for every preference.
+    pub(crate) fn choose_present_mode(
+        &self,
+        present_modes: &[vk::PresentModeKHR],
+    ) -> vk::PresentModeKHR {
+        let preferences: &[vk::PresentModeKHR] = match (self.vsync, self.prefer_mailbox) {
+            (true, false) => &[vk::PresentModeKHR::FIFO],
+            (true, true) => &[vk::PresentModeKHR::MAILBOX],
+            (false, _) => &[
+                vk::PresentModeKHR::IMMEDIATE,
+                vk::PresentModeKHR::MAILBOX,
+                vk::PresentModeKHR::FIFO_RELAXED,
+            ],
+        };
+
+        preferences
+            .iter()
+            .find(|present_mode| present_modes.contains(present_mode))
+            .copied()
+            .unwrap_or(vk::PresentModeKHR::FIFO)
+    }
+
+    /// This function clamps the configured image count to the limits of the surface. A maximal image count of zero
+    /// means, that the surface has no limit.
+    pub(crate) fn choose_image_count(&self, capabilities: &vk::SurfaceCapabilitiesKHR) -> u32 {
+        let image_count = self.min_image_count.max(capabilities.min_image_count);
+        if capabilities.max_image_count == 0 {
+            image_count
+        } else {
+            image_count.min(capabilities.max_image_count)
+        }
+    }
+}
+
+/// This function selects the extent of the swapchain images. If the surface doesn't define the extent, the window size
+/// is clamped to the limits of the surface.
+pub(crate) fn choose_extent(
+    capabilities: &vk::SurfaceCapabilitiesKHR,
+    window_width: u32,
+    window_height: u32,
+) -> vk::Extent2D {
+    if capabilities.current_extent.width != u32::MAX {
+        return capabilities.current_extent;
+    }
+
+    vk::Extent2D {
+        width: window_width.clamp(
+            capabilities.min_image_extent.width,
+            capabilities.max_image_extent.width,
+        ),
+        height: window_height.clamp(
+            capabilities.min_image_extent.height,
+            capabilities.max_image_extent.height,
+        ),
+    }
+}
+
+/// This function selects the first supported composite alpha mode, opaque composition is preferred
+pub(crate) fn choose_composite_alpha(
+    capabilities: &vk::SurfaceCapabilitiesKHR,
+) -> vk::CompositeAlphaFlagsKHR {
+    [
+        vk::CompositeAlphaFlagsKHR::OPAQUE,
+        vk::CompositeAlphaFlagsKHR::PRE_MULTIPLIED,
+        vk::CompositeAlphaFlagsKHR::POST_MULTIPLIED,
+        vk::CompositeAlphaFlagsKHR::INHERIT,
+    ]
+    .into_iter()
+    .find(|composite_alpha| {
+        capabilities
+            .supported_composite_alpha
+            .contains(*composite_alpha)
+    })
+    .unwrap_or(vk::CompositeAlphaFlagsKHR::OPAQUE)
+}
@@ -10,7 +10,7 @@ pub struct Bounds {
 
 #[derive(Deserialize, Serialize)]
 pub(crate) struct Glyph {
-    pub(crate) unicode: u8,
+    pub(crate) unicode: u32,
     pub(crate) advance: f32,
     #[serde(rename = "planeBounds", default = "Bounds::default")]
     pub(crate) plane_bounds: Bounds,
@@ -37,7 +37,7 @@ pub(crate) struct AtlasMeta {
     #[serde(rename = "type")]
     pub(crate) kind: String,
     #[serde(rename = "distanceRange")]
-    pub(crate) distance_range: u8,
+    pub(crate) distance_range: f32,
     pub(crate) size: u8,
     pub(crate) width: u16,
     pub(crate) height: u16,
@@ -1,69 +1,85 @@
+use crate::error::Error;
 use crate::render::buffer::builder::BufferBuilder;
-use crate::render::buffer::format::{Topology, VertexFormat};
+use crate::render::buffer::format::{PositionTexCoordColor, Topology};
 use crate::render::text::config::FontAtlas;
 use crate::render::GameRenderer;
 use crate::Result;
-use glam::Vec3;
+use glam::Vec4;
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::fs;
 use std::path::Path;
-use crate::render::image::Image;
+use crate::render::image::{ColorSpace, Image};
 
 pub mod config;
 
 pub struct FontRenderer {
     renderer: RefCell<GameRenderer>,
     atlas: FontAtlas,
-    atlas_image: Image
+    atlas_image: Image,
+    pipeline: String
 }
 
 impl FontRenderer {
-    pub fn new<P: AsRef<Path>>(renderer: GameRenderer, path: P) -> Result<Self> {
+    /// This function loads the font atlas in the specified directory. The font pipeline is specialized for the
+    /// distance range of the atlas, so the renderer has to contain the `msdf_font` pipeline.
+    pub fn new<P: AsRef<Path>>(mut renderer: GameRenderer, path: P) -> Result<Self> {
         let path = path.as_ref();
         let atlas: FontAtlas = {
             let atlas_config_path = path.join("atlas.json");
             if !atlas_config_path.exists() {
-                panic!("Atlas config file 'atlas.json' not found in '{:?}'", path);
+                return Err(Error::InvalidAsset {
+                    path: path.to_path_buf(),
+                    reason: "Atlas config file 'atlas.json' not found".to_string(),
+                });
             }
 
             serde_json::from_slice(fs::read(atlas_config_path)?.as_slice())?
         };
+
+        // The distance range of the atlas is a specialization constant of the font pipeline
+        let pipeline = renderer
+            .find_pipeline("msdf_font")
+            .ok_or_else(|| Error::PipelineNotFound("msdf_font".to_string()))?
+            .with_specialization(&[("PX_RANGE", atlas.atlas.distance_range)]);
+        // Fonts with the same distance range share the variant of the pipeline
+        let pipeline_name = pipeline.name().to_string();
+        if renderer.find_pipeline(&pipeline_name).is_none() {
+            renderer.add_pipeline(pipeline)?;
+        }
+
         Ok(Self {
-            atlas_image: Image::from_file(&renderer.0.application, path.join("atlas.png"))?,
+            // The atlas stores distances instead of colors, so it must not be converted
+            atlas_image: Image::from_file_with_color_space(
+                &renderer.0.application,
+                path.join("atlas.png"),
+                ColorSpace::Linear,
+            )?,
             renderer: RefCell::new(renderer),
             atlas,
+            pipeline: pipeline_name,
         })
     }
 
-    pub fn draw(&self, x: f32, y: f32, text: Cow<str>, size: f32, color: Vec3) -> Result<()> {
-        let mut builder = BufferBuilder::builder(
-            VertexFormat::PositionTexCoordColor,
-            Topology::Quad,
-            "msdf_font",
-        );
+    pub fn draw(&self, x: f32, y: f32, text: Cow<str>, size: f32, color: Vec4) -> Result<()> {
+        let mut builder = BufferBuilder::builder(Topology::Quad, &self.pipeline);
         builder.image(&self.atlas_image);
         let mut text_x = x;
 
         // Enumerate characters
         #[rustfmt::skip]
         for character in text.chars() {
-            text_x += self.visit(&mut builder, text_x, y, character, size, color);
+            text_x += self.visit(&mut builder, text_x, y, character, size, color)?;
         }
 
         builder.build(&mut self.renderer.borrow_mut());
         Ok(())
     }
 
-    fn visit(&self, buffer_builder: &mut BufferBuilder, x: f32, y: f32, character: char, size: f32, color: Vec3) -> f32 {
-        let Some(glyph) = self.atlas.glyphs.iter()
-            .find(|value| value.unicode.eq(&(character as u8)))
-            else {
-                panic!(
-                    "Unable to draw char => Character '{}' not found in font atlas",
-                    character
-                );
-            };
+    fn visit(&self, buffer_builder: &mut BufferBuilder<PositionTexCoordColor>, x: f32, y: f32, character: char, size: f32, color: Vec4) -> Result<f32> {
+        let glyph = self.atlas.glyphs.iter()
+            .find(|value| value.unicode == character as u32)
+            .ok_or(Error::MissingGlyph(character))?;
 
         let plane_bounds = &glyph.plane_bounds;
         let atlas_bounds = &glyph.atlas_bounds;
@@ -83,11 +99,12 @@ impl FontRenderer {
             let v0 = atlas_bounds.top / atlas_meta.height as f32;
             let v1 = atlas_bounds.bottom / atlas_meta.height as f32;
 
-            buffer_builder.begin(x0, y0).uv(u0, 1.0 - v0).color(color.x, color.y, color.z).end();
-            buffer_builder.begin(x0, y1).uv(u0, 1.0 - v1).color(color.x, color.y, color.z).end();
-            buffer_builder.begin(x1, y1).uv(u1, 1.0 - v1).color(color.x, color.y, color.z).end();
-            buffer_builder.begin(x1, y0).uv(u1, 1.0 - v0).color(color.x, color.y, color.z).end();
+            buffer_builder
+                .vertex(PositionTexCoordColor::new(x0, y0, u0, 1.0 - v0, color))
+                .vertex(PositionTexCoordColor::new(x0, y1, u0, 1.0 - v1, color))
+                .vertex(PositionTexCoordColor::new(x1, y1, u1, 1.0 - v1, color))
+                .vertex(PositionTexCoordColor::new(x1, y0, u1, 1.0 - v0, color));
         }
-        return size * glyph.advance;
+        Ok(size * glyph.advance)
     }
 }
@@ -0,0 +1,92 @@
+use crate::Result;
+use log::warn;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+use std::{fs, mem};
+
+/// The time without new file events, after which the collected changes are reported. Editors often write a file with
+/// multiple operations, so this prevents the compilation of half-written shaders.
+const DEBOUNCE_DURATION: Duration = Duration::from_millis(100);
+
+/// This structure represents the file watcher over the pipeline configurations and shader sources. The watcher only
+/// watches the directories of the files, because many editors replace a file instead of writing into it.
+pub(crate) struct AssetWatcher {
+    watcher: RecommendedWatcher,
+    receiver: Receiver<notify::Result<Event>>,
+    watched_directories: HashSet<PathBuf>,
+    pending_paths: HashSet<PathBuf>,
+    last_event: Option<Instant>,
+}
+
+impl AssetWatcher {
+    pub(crate) fn new() -> Result<Self> {
+        let (sender, receiver) = mpsc::channel();
+        Ok(Self {
+            watcher: notify::recommended_watcher(sender)?,
+            receiver,
+            watched_directories: HashSet::new(),
+            pending_paths: HashSet::new(),
+            last_event: None,
+        })
+    }
+
+    /// This function starts watching the specified directory, if it isn't watched already
+    pub(crate) fn watch_directory(&mut self, directory: &Path) -> Result<()> {
+        let directory = fs::canonicalize(directory)?;
+        if !self.watched_directories.contains(&directory) {
+            self.watcher
+                .watch(&directory, RecursiveMode::NonRecursive)?;
+            self.watched_directories.insert(directory);
+        }
+        Ok(())
+    }
+
+    /// This function starts watching the directory of the specified file
+    pub(crate) fn watch_file(&mut self, file: &Path) -> Result<()> {
+        self.watch_directory(parent_directory(file))
+    }
+
+    /// This function returns the normalized paths of all changed files. The changes are only reported, when no new
+    /// events were received for [DEBOUNCE_DURATION].
+    pub(crate) fn changed_paths(&mut self) -> HashSet<PathBuf> {
+        for event in self.receiver.try_iter() {
+            match event {
+                Ok(event) if !matches!(event.kind, EventKind::Access(_)) => {
+                    self.pending_paths
+                        .extend(event.paths.iter().map(|path| normalize_path(path)));
+                    self.last_event = Some(Instant::now());
+                }
+                Ok(_) => {}
+                Err(error) => warn!("Error while watching assets => {}", error),
+            }
+        }
+
+        match self.last_event {
+            Some(last_event) if last_event.elapsed() >= DEBOUNCE_DURATION => {
+                self.last_event = None;
+                mem::take(&mut self.pending_paths)
+            }
+            _ => HashSet::new(),
+        }
+    }
+}
+
+/// This function returns the directory of the specified file. Relative file names without a directory are located in
+/// the current working directory.
+fn parent_directory(file: &Path) -> &Path {
+    file.parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+}
+
+/// This function returns the absolute path of the specified file. Only the directory is canonicalized, so this also
+/// works for files, which are removed temporarily while an editor saves them.
+pub(crate) fn normalize_path(file: &Path) -> PathBuf {
+    match (fs::canonicalize(parent_directory(file)), file.file_name()) {
+        (Ok(directory), Some(file_name)) => directory.join(file_name),
+        _ => file.to_path_buf(),
+    }
+}
@@ -0,0 +1,161 @@
+use crate::Result;
+use ash::extensions::ext::DebugUtils;
+use ash::{vk, Entry, Instance};
+use log::{debug, error, info, warn};
+use std::ffi::{c_void, CStr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// The name of the environment variable, which enables the Khronos validation layer
+pub const VALIDATION_LAYER_ENV_VAR: &str = "VALIDATION_LAYER";
+
+pub(crate) const VALIDATION_LAYER_NAME: &CStr =
+    unsafe { CStr::from_bytes_with_nul_unchecked(b"VK_LAYER_KHRONOS_validation\0") };
+
+pub(crate) const DEBUG_UTILS_EXTENSION_NAME: &CStr =
+    unsafe { CStr::from_bytes_with_nul_unchecked(b"VK_EXT_debug_utils\0") };
+
+static PANIC_ON_VALIDATION_ERROR: AtomicBool = AtomicBool::new(false);
+
+/// This function enables or disables the panic on validation errors. This is meant for tests, so validation errors fail
+/// the test instead of only being logged. The panic is raised at the end of the frame, which caused the error.
+pub fn set_panic_on_validation_error(enabled: bool) {
+    PANIC_ON_VALIDATION_ERROR.store(enabled, Ordering::SeqCst);
+}
+
+/// This function reads the `VALIDATION_LAYER` environment variable. Invalid values are reported and disable the
+/// validation layer.
+pub(crate) fn validation_layer_requested() -> bool {
+    match std::env::var(VALIDATION_LAYER_ENV_VAR) {
+        Ok(value) => match value.trim().to_lowercase().as_str() {
+            "true" | "1" | "on" => true,
+            "false" | "0" | "off" | "" => false,
+            _ => {
+                warn!(
+                    "Unable to interpret {}='{}' as boolean, validation layer disabled",
+                    VALIDATION_LAYER_ENV_VAR, value
+                );
+                false
+            }
+        },
+        Err(_) => false,
+    }
+}
+
+/// This function returns true, if the instance layer with the specified name is available
+pub(crate) fn is_layer_available(entry: &Entry, layer_name: &CStr) -> Result<bool> {
+    Ok(unsafe { entry.enumerate_instance_layer_properties() }?
+        .iter()
+        .any(|layer| unsafe { CStr::from_ptr(layer.layer_name.as_ptr()) }.eq(layer_name)))
+}
+
+/// This function returns true, if the instance extension with the specified name is available
+pub(crate) fn is_extension_available(entry: &Entry, extension_name: &CStr) -> Result<bool> {
+    Ok(
+        unsafe { entry.enumerate_instance_extension_properties(None) }?
+            .iter()
+            .any(|extension| {
+                unsafe { CStr::from_ptr(extension.extension_name.as_ptr()) }.eq(extension_name)
+            }),
+    )
+}
+
+/// This structure represents the debug utils messenger, which forwards the messages of the validation layer and the
+/// driver into the `log` crate
+pub(crate) struct DebugMessenger {
+    debug_utils: DebugUtils,
+    messenger: vk::DebugUtilsMessengerEXT,
+
+    /// The first validation error of this instance since the last check. Panicking in the messenger callback would
+    /// unwind across the FFI boundary, so the error is stored and the panic is raised by
+    /// [DebugMessenger::check_validation_errors]. The error is boxed, because the callback receives its address.
+    validation_error: Box<Mutex<Option<String>>>,
+}
+
+impl DebugMessenger {
+    pub(crate) fn new(entry: &Entry, instance: &Instance) -> Result<Self> {
+        let debug_utils = DebugUtils::new(entry, instance);
+        let validation_error = Box::new(Mutex::new(None));
+        let messenger_create_info = vk::DebugUtilsMessengerCreateInfoEXT::default()
+            .message_severity(
+                vk::DebugUtilsMessageSeverityFlagsEXT::VERBOSE
+                    | vk::DebugUtilsMessageSeverityFlagsEXT::INFO
+                    | vk::DebugUtilsMessageSeverityFlagsEXT::WARNING
+                    | vk::DebugUtilsMessageSeverityFlagsEXT::ERROR,
+            )
+            .message_type(
+                vk::DebugUtilsMessageTypeFlagsEXT::GENERAL
+                    | vk::DebugUtilsMessageTypeFlagsEXT::VALIDATION
+                    | vk::DebugUtilsMessageTypeFlagsEXT::PERFORMANCE,
+            )
+            .pfn_user_callback(Some(debug_callback))
+            .user_data(validation_error.as_ref() as *const Mutex<Option<String>> as *mut c_void);
+        let messenger =
+            unsafe { debug_utils.create_debug_utils_messenger(&messenger_create_info, None) }?;
+        Ok(Self {
+            debug_utils,
+            messenger,
+            validation_error,
+        })
+    }
+
+    /// This function panics with the first validation error of this instance since the last check, if the panic on
+    /// validation errors is enabled
+    pub(crate) fn check_validation_errors(&self) {
+        if !PANIC_ON_VALIDATION_ERROR.load(Ordering::SeqCst) {
+            return;
+        }
+
+        if let Some(message) = self.validation_error.lock().unwrap().take() {
+            panic!("Vulkan validation error => {}", message);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn debug_utils(&self) -> &DebugUtils {
+        &self.debug_utils
+    }
+
+    /// This function destroys the messenger. This must be called before the instance is destroyed.
+    pub(crate) fn destroy(&self) {
+        unsafe {
+            self.debug_utils
+                .destroy_debug_utils_messenger(self.messenger, None)
+        };
+    }
+}
+
+unsafe extern "system" fn debug_callback(
+    message_severity: vk::DebugUtilsMessageSeverityFlagsEXT,
+    message_type: vk::DebugUtilsMessageTypeFlagsEXT,
+    callback_data: *const vk::DebugUtilsMessengerCallbackDataEXT<'_>,
+    user_data: *mut c_void,
+) -> vk::Bool32 {
+    let message = if callback_data.is_null() || (*callback_data).p_message.is_null() {
+        "<no message>".into()
+    } else {
+        CStr::from_ptr((*callback_data).p_message).to_string_lossy()
+    };
+
+    match message_severity {
+        vk::DebugUtilsMessageSeverityFlagsEXT::ERROR => {
+            error!(target: "vulkan", "[{:?}] {}", message_type, message);
+            if PANIC_ON_VALIDATION_ERROR.load(Ordering::SeqCst) && !user_data.is_null() {
+                let validation_error = &*(user_data as *const Mutex<Option<String>>);
+                if let Ok(mut validation_error) = validation_error.lock() {
+                    validation_error.get_or_insert_with(|| message.to_string());
+                }
+            }
+        }
+        vk::DebugUtilsMessageSeverityFlagsEXT::WARNING => {
+            warn!(target: "vulkan", "[{:?}] {}", message_type, message)
+        }
+        vk::DebugUtilsMessageSeverityFlagsEXT::INFO => {
+            info!(target: "vulkan", "[{:?}] {}", message_type, message)
+        }
+        _ => debug!(target: "vulkan", "[{:?}] {}", message_type, message),
+    }
+
+    // The call, which triggered the message, must not be aborted
+    vk::FALSE
+}
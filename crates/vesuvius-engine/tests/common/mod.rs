@@ -0,0 +1,171 @@
+//! This module contains the golden-image test harness. The harness boots the engine headless, renders a scene into an
+//! offscreen image, reads the frame back and compares it against a reference PNG in `tests/golden`.
+//!
+//! The tests are meant to run on a software Vulkan implementation like lavapipe (e.g. with
+//! `VK_ICD_FILENAMES=/usr/share/vulkan/icd.d/lvp_icd.x86_64.json`), so the reference images are reproducible. They run
+//! with every `cargo test` and are only skipped on machines without Vulkan. Set `VESUVIUS_REQUIRE_VULKAN` to fail
+//! instead of skipping, so a CI machine without a working Vulkan driver is noticed. A missing reference image fails the
+//! test, set `VESUVIUS_BLESS` to write missing references and to overwrite the existing ones.
+#![allow(dead_code)]
+
+use ash::vk;
+use image::{Rgba, RgbaImage};
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+use vesuvius_engine::error::Error;
+use vesuvius_engine::render::GameRenderer;
+use vesuvius_engine::{validation, App, Result};
+
+/// The size of the images, rendered by the harness
+pub const WIDTH: u32 = 320;
+pub const HEIGHT: u32 = 240;
+
+/// The maximal difference per color channel, until a pixel is counted as mismatch
+pub const DEFAULT_TOLERANCE: u8 = 2;
+
+static WORKING_DIRECTORY: Once = Once::new();
+
+/// This structure represents the headless engine instance used by a single golden-image test
+pub struct GoldenHarness {
+    pub app: App,
+    pub renderer: GameRenderer,
+}
+
+impl GoldenHarness {
+    /// This function boots the engine headless and compiles all pipelines. If no Vulkan implementation is available,
+    /// none is returned and the test is skipped, unless `VESUVIUS_REQUIRE_VULKAN` is set. All other errors panic, so a
+    /// golden-image test never passes because of a broken engine.
+    pub fn new() -> Option<Self> {
+        // The engine loads the assets relative to the workspace root
+        WORKING_DIRECTORY.call_once(|| {
+            std::env::set_current_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("../.."))
+                .expect("Unable to change into workspace root");
+        });
+
+        // Validation errors fail the test, if the validation layer is enabled
+        validation::set_panic_on_validation_error(true);
+
+        let app = match App::headless() {
+            Ok(app) => app,
+            Err(error) if is_vulkan_unavailable(&error) => {
+                assert!(
+                    std::env::var_os("VESUVIUS_REQUIRE_VULKAN").is_none(),
+                    "Vulkan is required, but unavailable => {}",
+                    error
+                );
+                eprintln!(
+                    "Skip golden-image test, because Vulkan is unavailable => {}",
+                    error
+                );
+                return None;
+            }
+            Err(error) => panic!("Unable to boot engine headless => {}", error),
+        };
+
+        let mut renderer = GameRenderer::headless(app.clone(), WIDTH, HEIGHT)
+            .expect("Unable to create headless renderer");
+        renderer.reload(true).expect("Unable to compile pipelines");
+        Some(Self { app, renderer })
+    }
+
+    /// This function renders a single frame with the specified scene and returns the captured frame
+    pub fn render<F: FnOnce(&App, &mut GameRenderer) -> Result<()>>(
+        &mut self,
+        scene: F,
+    ) -> RgbaImage {
+        self.renderer.begin().expect("Unable to begin frame");
+        self.renderer.clear_color(0.0, 0.0, 0.0, 1.0);
+        scene(&self.app, &mut self.renderer).expect("Unable to record scene");
+        self.renderer
+            .queue_buffer_builder()
+            .expect("Unable to queue buffer builder");
+
+        let frame = self
+            .renderer
+            .capture_frame()
+            .expect("Unable to capture frame");
+        self.renderer.end().expect("Unable to end frame");
+        frame
+    }
+}
+
+/// This function compares the frame against the reference image with the specified name. On failure, the frame and a
+/// diff image are written into `target/golden` and the test panics.
+pub fn assert_golden(name: &str, frame: &RgbaImage, tolerance: u8) {
+    let reference_path = golden_directory().join(format!("{}.png", name));
+    if std::env::var_os("VESUVIUS_BLESS").is_some() {
+        frame
+            .save(&reference_path)
+            .expect("Unable to write reference image");
+        eprintln!("Written reference image '{}'", reference_path.display());
+        return;
+    }
+
+    assert!(
+        reference_path.exists(),
+        "The reference image '{}' is missing => Render it with VESUVIUS_BLESS=1 on lavapipe",
+        reference_path.display()
+    );
+
+    let reference = image::open(&reference_path)
+        .expect("Unable to read reference image")
+        .to_rgba8();
+    assert_eq!(
+        reference.dimensions(),
+        frame.dimensions(),
+        "Size of frame doesn't match the reference image '{}'",
+        name
+    );
+
+    // Compare pixels and mark mismatches in the diff image
+    let mut mismatches = 0;
+    let mut diff = RgbaImage::new(frame.width(), frame.height());
+    for (x, y, pixel) in frame.enumerate_pixels() {
+        let expected = reference.get_pixel(x, y);
+        let matches = pixel
+            .0
+            .iter()
+            .zip(expected.0.iter())
+            .all(|(actual, expected)| actual.abs_diff(*expected) <= tolerance);
+        if matches {
+            let luminance = (pixel.0[0] / 4) + (pixel.0[1] / 4) + (pixel.0[2] / 4);
+            diff.put_pixel(x, y, Rgba([luminance, luminance, luminance, 255]));
+        } else {
+            mismatches += 1;
+            diff.put_pixel(x, y, Rgba([255, 0, 255, 255]));
+        }
+    }
+
+    if mismatches > 0 {
+        let output_directory = Path::new("target/golden");
+        std::fs::create_dir_all(output_directory).expect("Unable to create output directory");
+        let actual_path = output_directory.join(format!("{}.actual.png", name));
+        let diff_path = output_directory.join(format!("{}.diff.png", name));
+        frame.save(&actual_path).expect("Unable to write frame");
+        diff.save(&diff_path).expect("Unable to write diff image");
+        panic!(
+            "{} pixels of '{}' differ from the reference (tolerance {}) => See '{}' and '{}'",
+            mismatches,
+            name,
+            tolerance,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+/// This function returns true, if the error means, that the machine has no Vulkan loader, driver or device
+fn is_vulkan_unavailable(error: &Error) -> bool {
+    matches!(
+        error,
+        Error::Loading(_)
+            | Error::NoSuitableDevice(_)
+            | Error::Vulkan(vk::Result::ERROR_INCOMPATIBLE_DRIVER)
+            | Error::Vulkan(vk::Result::ERROR_INITIALIZATION_FAILED)
+    )
+}
+
+#[inline]
+fn golden_directory() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use ash::vk;
+use common::{assert_golden, GoldenHarness, DEFAULT_TOLERANCE};
+use glam::{Vec2, Vec4};
+use std::borrow::Cow;
+use std::slice;
+use vesuvius_engine::error::Error;
+use vesuvius_engine::render::buffer::builder::BufferBuilder;
+use vesuvius_engine::render::buffer::format::{PositionColor, PositionTexCoord, Topology};
+use vesuvius_engine::render::buffer::instance::{InstanceBatch, SpriteInstance};
+use vesuvius_engine::render::image::Image;
+use vesuvius_engine::render::pipeline::{DescriptorSet, WriteDescriptorSet};
+use vesuvius_engine::render::text::FontRenderer;
+
+#[test]
+fn colored_quads() {
+    let Some(mut harness) = GoldenHarness::new() else {
+        return;
+    };
+
+    let frame = harness.render(|_, renderer| {
+        let mut buffer_builder = BufferBuilder::builder(Topology::Quad, "position_color");
+        let colors = [
+            Vec4::new(1.0, 0.0, 0.0, 1.0),
+            Vec4::new(0.0, 1.0, 0.0, 1.0),
+            Vec4::new(0.0, 0.0, 1.0, 1.0),
+        ];
+        for (index, color) in colors.into_iter().enumerate() {
+            let x = 20.0 + index as f32 * 100.0;
+            buffer_builder
+                .vertex(PositionColor::new(x, 20.0, color))
+                .vertex(PositionColor::new(x + 80.0, 20.0, color))
+                .vertex(PositionColor::new(x + 80.0, 100.0, color))
+                .vertex(PositionColor::new(x, 100.0, color));
+        }
+        buffer_builder.build(renderer);
+        Ok(())
+    });
+    assert_golden("colored_quads", &frame, DEFAULT_TOLERANCE);
+}
+
+#[test]
+fn textured_image() {
+    let Some(mut harness) = GoldenHarness::new() else {
+        return;
+    };
+
+    let image = Image::from_file(&harness.app, "assets/resources/fonts/roboto-thin/atlas.png")
+        .expect("Unable to load image");
+    let frame = harness.render(|_, renderer| {
+        let mut buffer_builder = BufferBuilder::builder(Topology::Quad, "position_texcoord");
+        buffer_builder
+            .image(&image)
+            .vertex(PositionTexCoord::new(40.0, 20.0, 0.0, 0.0))
+            .vertex(PositionTexCoord::new(240.0, 20.0, 1.0, 0.0))
+            .vertex(PositionTexCoord::new(240.0, 220.0, 1.0, 1.0))
+            .vertex(PositionTexCoord::new(40.0, 220.0, 0.0, 1.0));
+        buffer_builder.build(renderer);
+        Ok(())
+    });
+    assert_golden("textured_image", &frame, DEFAULT_TOLERANCE);
+}
+
+#[test]
+fn msdf_text() {
+    let Some(mut harness) = GoldenHarness::new() else {
+        return;
+    };
+
+    let font_renderer = FontRenderer::new(
+        harness.renderer.clone(),
+        "assets/resources/fonts/roboto-thin",
+    )
+    .expect("Unable to load font");
+    let frame = harness.render(|_, _| {
+        font_renderer.draw(
+            10.0,
+            80.0,
+            Cow::Borrowed("Vesuvius"),
+            48.0,
+            Vec4::new(1.0, 0.5, 0.0, 1.0),
+        )
+    });
+    assert_golden("msdf_text", &frame, DEFAULT_TOLERANCE);
+}
+
+#[test]
+fn compute_gradient() {
+    let Some(mut harness) = GoldenHarness::new() else {
+        return;
+    };
+
+    // The descriptor set has to stay alive until the frame is finished
+    let image = Image::storage(&harness.app, 64, 64, vk::Format::R8G8B8A8_UNORM, "Gradient")
+        .expect("Unable to create storage image");
+    let descriptor_set = DescriptorSet::allocate(&harness.renderer, "fill_gradient", 0)
+        .expect("Unable to allocate descriptor set");
+    image.write_to_set(&descriptor_set, 0);
+
+    let frame = harness.render(|_, renderer| {
+        let pipeline = renderer
+            .find_compute_pipeline("fill_gradient")
+            .ok_or_else(|| Error::PipelineNotFound("fill_gradient".to_string()))?;
+        renderer.dispatch(pipeline, slice::from_ref(&descriptor_set), 8, 8, 1);
+
+        // The image is sampled after the dispatch, so the draw has to wait for the compute shader
+        let mut buffer_builder = BufferBuilder::builder(Topology::Quad, "position_texcoord");
+        buffer_builder
+            .image(&image)
+            .vertex(PositionTexCoord::new(40.0, 20.0, 0.0, 0.0))
+            .vertex(PositionTexCoord::new(240.0, 20.0, 1.0, 0.0))
+            .vertex(PositionTexCoord::new(240.0, 220.0, 1.0, 1.0))
+            .vertex(PositionTexCoord::new(40.0, 220.0, 0.0, 1.0));
+        buffer_builder.build(renderer);
+        Ok(())
+    });
+    assert_golden("compute_gradient", &frame, DEFAULT_TOLERANCE);
+}
+
+#[test]
+fn instanced_sprites() {
+    let Some(mut harness) = GoldenHarness::new() else {
+        return;
+    };
+
+    let image = Image::from_file(&harness.app, "assets/resources/fonts/roboto-thin/atlas.png")
+        .expect("Unable to load image");
+    let extent = harness.renderer.extent();
+    let mut batch = InstanceBatch::new("sprite");
+    batch
+        .image(&image)
+        .push_constants(&[extent.width as f32, extent.height as f32]);
+    for index in 0..12 {
+        let (column, row) = ((index % 4) as f32, (index / 4) as f32);
+        batch.push(SpriteInstance {
+            position: Vec2::new(20.0 + column * 70.0, 20.0 + row * 70.0),
+            scale: Vec2::splat(60.0),
+            color: Vec4::new(1.0, column / 3.0, row / 2.0, 1.0),
+            atlas_rect: Vec4::new(0.0, 0.0, 1.0, 1.0),
+        });
+    }
+
+    let frame = harness.render(|_, renderer| renderer.draw_instances(&batch));
+    assert_golden("instanced_sprites", &frame, DEFAULT_TOLERANCE);
+}
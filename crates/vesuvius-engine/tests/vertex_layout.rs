@@ -0,0 +1,80 @@
+use ash::vk;
+use bytemuck::{Pod, Zeroable};
+use glam::{Vec2, Vec4};
+use std::mem;
+use vesuvius_engine::render::buffer::format::PositionTexCoordColor;
+use vesuvius_engine::render::buffer::layout::{VertexAttribute, VertexLayout};
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable, VertexLayout)]
+struct TerrainVertex {
+    #[vertex(position)]
+    position: Vec2,
+    height: f32,
+    province: u32,
+
+    #[vertex(color)]
+    tint: Vec4,
+}
+
+#[test]
+fn derived_attributes() {
+    assert_eq!(
+        TerrainVertex::ATTRIBUTES,
+        &[
+            VertexAttribute {
+                name: "position",
+                format: vk::Format::R32G32_SFLOAT,
+                offset: 0,
+            },
+            VertexAttribute {
+                name: "height",
+                format: vk::Format::R32_SFLOAT,
+                offset: 8,
+            },
+            VertexAttribute {
+                name: "province",
+                format: vk::Format::R32_UINT,
+                offset: 12,
+            },
+            VertexAttribute {
+                name: "tint",
+                format: vk::Format::R32G32B32A32_SFLOAT,
+                offset: 16,
+            },
+        ]
+    );
+    assert_eq!(TerrainVertex::stride(), 32);
+}
+
+/// This function returns the offset of the field in the vertex in bytes
+fn offset_of<V, F>(vertex: &V, field: &F) -> usize {
+    field as *const F as usize - vertex as *const V as usize
+}
+
+#[test]
+fn builtin_layouts() {
+    let vertex = PositionTexCoordColor::default();
+    let formats = PositionTexCoordColor::ATTRIBUTES
+        .iter()
+        .map(|attribute| (attribute.format, attribute.offset as usize))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        formats,
+        [
+            (
+                vk::Format::R32G32_SFLOAT,
+                offset_of(&vertex, &vertex.position)
+            ),
+            (
+                vk::Format::R32G32B32A32_SFLOAT,
+                offset_of(&vertex, &vertex.color)
+            ),
+            (vk::Format::R32G32_SFLOAT, offset_of(&vertex, &vertex.uv)),
+        ]
+    );
+    assert_eq!(
+        PositionTexCoordColor::stride() as usize,
+        mem::size_of::<PositionTexCoordColor>()
+    );
+}
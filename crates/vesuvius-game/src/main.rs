@@ -1,15 +1,20 @@
 pub mod screens;
 
-use screens::MainMenuScreen;
+use screens::{ErrorScreen, MainMenuScreen, SettingsScreen};
+use std::time::{SystemTime, UNIX_EPOCH};
 #[cfg(feature = "debug_extensions")]
 use vesuvius_engine::debug::DebugExtension;
+use vesuvius_engine::error::Error;
 use vesuvius_engine::render::text::FontRenderer;
 use vesuvius_engine::render::GameRenderer;
+use vesuvius_engine::screen::Screen;
 use vesuvius_engine::vesuvius_winit::dpi::PhysicalSize;
-use vesuvius_engine::vesuvius_winit::event::{ElementState, Event, ModifiersState, WindowEvent};
+use vesuvius_engine::vesuvius_winit::event::{
+    ElementState, Event, ModifiersState, VirtualKeyCode, WindowEvent,
+};
 use vesuvius_engine::vesuvius_winit::event_loop::{ControlFlow, EventLoop};
 use vesuvius_engine::vesuvius_winit::window::WindowBuilder;
-use vesuvius_engine::App;
+use vesuvius_engine::{App, Result};
 
 fn main() {
     simple_logger::init().unwrap();
@@ -33,15 +38,19 @@ fn main() {
     // Create application
     let mut app = App::new(window).unwrap();
     let mut renderer = GameRenderer::new(app.clone()).unwrap();
-    renderer.reload(true).unwrap();
+    if let Err(error) = renderer.reload(true) {
+        log::error!("{}", error);
+        std::process::exit(1);
+    }
 
-    let font_renderer =
-        FontRenderer::new(renderer.clone(), "assets/resources/fonts/roboto-thin").unwrap();
+    // Recompile changed shaders and pipelines while developing the game
+    if cfg!(debug_assertions) {
+        if let Err(error) = renderer.enable_hot_reload() {
+            log::warn!("Unable to enable hot reload => {}", error);
+        }
+    }
 
-    app.open_screen(Box::new(MainMenuScreen {
-        image: None,
-        font_renderer,
-    }));
+    open_screen(&mut app, &renderer, main_menu_screen(&renderer));
     log::info!("Successfully created application and renderer");
 
     #[cfg(feature = "debug_extensions")]
@@ -51,20 +60,51 @@ fn main() {
     }
 
     // Game Loop
-    app.window().set_visible(true);
+    app.window().unwrap().set_visible(true);
     log::info!("Init game loop and display game");
     let mut current_modifiers_state = ModifiersState::empty();
+    let mut screenshot_requested = false;
     window_event_loop.run(move |event, _, control_flow| {
         *control_flow = ControlFlow::Wait;
         match event {
-            Event::MainEventsCleared => app.window().request_redraw(),
-            Event::WindowEvent { event, window_id } if window_id == app.window().id() => {
+            Event::MainEventsCleared => app.window().unwrap().request_redraw(),
+            Event::WindowEvent { event, window_id } if window_id == app.window().unwrap().id() => {
                 match event {
                     WindowEvent::ModifiersChanged(modifiers) => current_modifiers_state = modifiers,
                     WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
                     WindowEvent::Resized(_resized_size) => renderer.reload(false).unwrap(),
                     WindowEvent::KeyboardInput { input, .. } => {
                         if let Some(keycode) = input.virtual_keycode {
+                            // Take a screenshot of the next frame with F12
+                            if keycode == VirtualKeyCode::F12
+                                && input.state == ElementState::Pressed
+                            {
+                                screenshot_requested = true;
+                            }
+
+                            // Open the settings with F10 and return to the main menu with Escape
+                            if input.state == ElementState::Pressed {
+                                match keycode {
+                                    VirtualKeyCode::F10 => {
+                                        let settings_screen =
+                                            create_font_renderer(&renderer).map(|font_renderer| {
+                                                SettingsScreen::new(&renderer, font_renderer)
+                                            });
+                                        open_screen(&mut app, &renderer, settings_screen);
+                                        return;
+                                    }
+                                    VirtualKeyCode::Escape => {
+                                        open_screen(
+                                            &mut app,
+                                            &renderer,
+                                            main_menu_screen(&renderer),
+                                        );
+                                        return;
+                                    }
+                                    _ => {}
+                                }
+                            }
+
                             if let Some(screen) = app.screen_mut() {
                                 match input.state {
                                     ElementState::Pressed => {
@@ -89,8 +129,26 @@ fn main() {
                 renderer.begin().unwrap();
                 renderer.clear_color(0.0, 0.0, 0.0, 1.0);
 
-                if let Some(screen) = app.screen() {
-                    screen.render(&mut renderer);
+                // Errors of the screen are displayed by the error screen in the next frame
+                let render_result = match app.screen() {
+                    Some(screen) => screen.render(&mut renderer),
+                    None => Ok(()),
+                };
+                if let Err(error) = render_result {
+                    show_error(&mut app, &renderer, error);
+                }
+
+                if screenshot_requested {
+                    screenshot_requested = false;
+                    let timestamp = SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .unwrap()
+                        .as_secs();
+                    if let Err(error) = renderer
+                        .capture_frame_to_file(format!("screenshots/screenshot-{}.png", timestamp))
+                    {
+                        log::error!("Unable to take screenshot => {}", error);
+                    }
                 }
 
                 renderer.end().unwrap();
@@ -99,3 +157,33 @@ fn main() {
         }
     });
 }
+
+fn create_font_renderer(renderer: &GameRenderer) -> Result<FontRenderer> {
+    FontRenderer::new(renderer.clone(), "assets/resources/fonts/roboto-thin")
+}
+
+fn main_menu_screen(renderer: &GameRenderer) -> Result<MainMenuScreen> {
+    Ok(MainMenuScreen {
+        image: None,
+        font_renderer: create_font_renderer(renderer)?,
+    })
+}
+
+/// This function opens the specified screen. If the screen can't be created or initialized, the error is displayed
+/// by the error screen instead of crashing the game.
+fn open_screen<S: Screen + 'static>(app: &mut App, renderer: &GameRenderer, screen: Result<S>) {
+    if let Err(error) = screen.and_then(|screen| app.open_screen(Box::new(screen))) {
+        show_error(app, renderer, error);
+    }
+}
+
+fn show_error(app: &mut App, renderer: &GameRenderer, error: Error) {
+    log::error!("{}", error);
+    let error_screen = ErrorScreen {
+        font_renderer: create_font_renderer(renderer).ok(),
+        message: error.to_string(),
+    };
+
+    // The initialization of the error screen can't fail
+    let _ = app.open_screen(Box::new(error_screen));
+}
@@ -1,12 +1,15 @@
 use std::borrow::Cow;
-use glam::Vec3;
+use std::cell::Cell;
+use glam::Vec4;
 use vesuvius_engine::render::image::Image;
 use vesuvius_engine::render::text::FontRenderer;
 use vesuvius_engine::render::GameRenderer;
 use vesuvius_engine::screen::Screen;
-use vesuvius_engine::App;
+use vesuvius_engine::{App, Result};
 use vesuvius_engine::render::buffer::builder::BufferBuilder;
-use vesuvius_engine::render::buffer::format::{Topology, VertexFormat};
+use vesuvius_engine::render::buffer::format::{PositionColor, Topology};
+use vesuvius_engine::render::swapchain::SwapchainConfig;
+use vesuvius_engine::vesuvius_winit::event::{ModifiersState, VirtualKeyCode};
 
 pub struct MainMenuScreen {
     pub(crate) image: Option<Image>,
@@ -14,17 +17,19 @@ pub struct MainMenuScreen {
 }
 
 impl Screen for MainMenuScreen {
-    fn init(&mut self, application: &App) {
-        self.image =
-            Some(Image::from_file(application, "assets/resources/images/image.png").unwrap());
+    fn init(&mut self, application: &App) -> Result<()> {
+        self.image = Some(Image::from_file(application, "assets/resources/images/image.png")?);
+        Ok(())
     }
 
-    fn render(&self, renderer: &mut GameRenderer) {
-        let mut buffer_builder = BufferBuilder::builder(VertexFormat::PositionColor, Topology::Quad, "position_color");
-        buffer_builder.begin(10.0, 10.0).color(1.0, 0.0, 0.0).end();
-        buffer_builder.begin(200.0, 10.0).color(1.0, 0.0, 0.0).end();
-        buffer_builder.begin(200.0, 200.0).color(1.0, 0.0, 0.0).end();
-        buffer_builder.begin(10.0, 200.0).color(1.0, 0.0, 0.0).end();
+    fn render(&self, renderer: &mut GameRenderer) -> Result<()> {
+        let red = Vec4::new(1.0, 0.0, 0.0, 1.0);
+        let mut buffer_builder = BufferBuilder::builder(Topology::Quad, "position_color");
+        buffer_builder
+            .vertex(PositionColor::new(10.0, 10.0, red))
+            .vertex(PositionColor::new(200.0, 10.0, red))
+            .vertex(PositionColor::new(200.0, 200.0, red))
+            .vertex(PositionColor::new(10.0, 200.0, red));
         buffer_builder.build(renderer);
 
         self.font_renderer
@@ -33,9 +38,113 @@ impl Screen for MainMenuScreen {
                 0.1,
                 Cow::Borrowed("It's working"),
                 100.0,
-                Vec3::new(1.0, 1.0, 1.0),
-            )
-            .unwrap();
-        renderer.queue_buffer_builder().unwrap();
+                Vec4::new(1.0, 1.0, 1.0, 1.0),
+            )?;
+        renderer.queue_buffer_builder()
+    }
+}
+
+/// This screen displays the swapchain settings. V toggles vsync, M toggles the mailbox preference and the arrow keys
+/// change the minimal image count. The changed settings are applied to the renderer on the next render call.
+pub struct SettingsScreen {
+    pub(crate) font_renderer: FontRenderer,
+    pub(crate) config: Cell<SwapchainConfig>,
+    pub(crate) changed: Cell<bool>,
+}
+
+impl SettingsScreen {
+    pub fn new(renderer: &GameRenderer, font_renderer: FontRenderer) -> Self {
+        Self {
+            font_renderer,
+            config: Cell::new(renderer.swapchain_config()),
+            changed: Cell::new(false),
+        }
+    }
+}
+
+impl Screen for SettingsScreen {
+    fn init(&mut self, _application: &App) -> Result<()> {
+        Ok(())
+    }
+
+    fn on_key_pressed(&mut self, key: VirtualKeyCode, _modifiers: ModifiersState) {
+        let mut config = self.config.get();
+        match key {
+            VirtualKeyCode::V => config.vsync = !config.vsync,
+            VirtualKeyCode::M => config.prefer_mailbox = !config.prefer_mailbox,
+            VirtualKeyCode::Up => config.min_image_count = (config.min_image_count + 1).min(8),
+            VirtualKeyCode::Down => config.min_image_count = config.min_image_count.saturating_sub(1).max(1),
+            _ => return,
+        }
+        self.config.set(config);
+        self.changed.set(true);
+    }
+
+    fn render(&self, renderer: &mut GameRenderer) -> Result<()> {
+        let config = self.config.get();
+        if self.changed.replace(false) {
+            renderer.set_swapchain_config(config);
+        }
+
+        let lines = [
+            Cow::Borrowed("Settings"),
+            Cow::Owned(format!("[V] VSync: {}", config.vsync)),
+            Cow::Owned(format!("[M] Prefer Mailbox: {}", config.prefer_mailbox)),
+            Cow::Owned(format!("[Up/Down] Image Count: {}", config.min_image_count)),
+        ];
+        for (index, line) in lines.into_iter().enumerate() {
+            self.font_renderer.draw(
+                10.0,
+                10.0 + index as f32 * 60.0,
+                line,
+                50.0,
+                Vec4::new(1.0, 1.0, 1.0, 1.0),
+            )?;
+        }
+        renderer.queue_buffer_builder()
+    }
+}
+
+/// This screen displays an error, which occurred while opening or rendering another screen (e.g. a broken asset). If
+/// the font itself is broken, only the background is drawn and the error is visible in the log.
+pub struct ErrorScreen {
+    pub(crate) font_renderer: Option<FontRenderer>,
+    pub(crate) message: String,
+}
+
+impl Screen for ErrorScreen {
+    fn init(&mut self, _application: &App) -> Result<()> {
+        Ok(())
+    }
+
+    fn render(&self, renderer: &mut GameRenderer) -> Result<()> {
+        let mut buffer_builder = BufferBuilder::builder(Topology::Quad, "position_color");
+        let (width, height) = (renderer.extent().width as f32, renderer.extent().height as f32);
+        let background = Vec4::new(0.3, 0.0, 0.0, 1.0);
+        buffer_builder
+            .vertex(PositionColor::new(0.0, 0.0, background))
+            .vertex(PositionColor::new(width, 0.0, background))
+            .vertex(PositionColor::new(width, height, background))
+            .vertex(PositionColor::new(0.0, height, background));
+        buffer_builder.build(renderer);
+
+        if let Some(font_renderer) = self.font_renderer.as_ref() {
+            let lines = [
+                "An error occurred",
+                self.message.as_str(),
+                "Press Escape to return to the main menu",
+            ];
+            for (index, line) in lines.into_iter().enumerate() {
+                // Errors while drawing the error are ignored, the message is in the log anyway
+                let _ = font_renderer.draw(
+                    10.0,
+                    10.0 + index as f32 * 40.0,
+                    Cow::Borrowed(line),
+                    30.0,
+                    Vec4::new(1.0, 1.0, 1.0, 1.0),
+                );
+            }
+        }
+        renderer.queue_buffer_builder()
     }
 }